@@ -3,12 +3,20 @@ use agent_holder::{
     offer::{command::OfferCommand, queries::ReceivedOfferView},
     state::HolderState,
 };
-use agent_shared::handlers::{command_handler, query_handler};
+use agent_shared::{
+    handlers::{command_handler, query_handler},
+    notification::NotificationEvent,
+};
 use axum::{
     extract::{Path, State},
     response::{IntoResponse, Response},
 };
 use hyper::StatusCode;
+use std::time::Duration;
+use tracing::info;
+
+const DEFERRED_CREDENTIAL_POLLING_INTERVAL_MS: u64 = 1000;
+const MAX_DEFERRED_CREDENTIAL_POLLING_ATTEMPTS: u64 = 10;
 
 #[axum_macros::debug_handler]
 pub(crate) async fn accept(State(state): State<HolderState>, Path(offer_id): Path<String>) -> Response {
@@ -17,8 +25,10 @@ pub(crate) async fn accept(State(state): State<HolderState>, Path(offer_id): Pat
     // Requests and Responses.
     // Furthermore, the Application Layer (not implemented yet) should be kept very thin as well. See: https://github.com/impierce/ssi-agent/issues/114
 
-    // Accept the Credential Offer if it exists
+    // Accept the Credential Offer if it exists. When the issuance of its credentials was deferred during a previous
+    // attempt, the Deferred Credential Endpoint is polled again instead.
     match query_handler(&offer_id, &state.query.received_offer).await {
+        Ok(Some(ReceivedOfferView { transaction_ids, .. })) if !transaction_ids.is_empty() => {}
         Ok(Some(ReceivedOfferView { .. })) => {
             let command = OfferCommand::AcceptCredentialOffer {
                 offer_id: offer_id.clone(),
@@ -29,20 +39,43 @@ pub(crate) async fn accept(State(state): State<HolderState>, Path(offer_id): Pat
                 // https://github.com/impierce/openid4vc/issues/78 is fixed.
                 return StatusCode::INTERNAL_SERVER_ERROR.into_response();
             }
+
+            let command = OfferCommand::SendCredentialRequest {
+                offer_id: offer_id.clone(),
+            };
+
+            // Send the Credential Request
+            if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
+                // TODO: add better Error responses. This needs to be done properly in all endpoints once
+                // https://github.com/impierce/openid4vc/issues/78 is fixed.
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
         }
         Ok(None) => return StatusCode::NOT_FOUND.into_response(),
         _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     }
 
-    let command = OfferCommand::SendCredentialRequest {
-        offer_id: offer_id.clone(),
-    };
+    // Poll the Deferred Credential Endpoint until the credentials whose issuance is deferred are available.
+    for attempt in 0..=MAX_DEFERRED_CREDENTIAL_POLLING_ATTEMPTS {
+        match query_handler(&offer_id, &state.query.received_offer).await {
+            Ok(Some(ReceivedOfferView { transaction_ids, .. })) if transaction_ids.is_empty() => break,
+            Ok(Some(_)) if attempt == MAX_DEFERRED_CREDENTIAL_POLLING_ATTEMPTS => {
+                info!("The issuance of the credentials is still pending, the Credential Offer can be accepted again later");
+                return StatusCode::ACCEPTED.into_response();
+            }
+            Ok(Some(_)) => {}
+            _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
 
-    // Send the Credential Request
-    if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
-        // TODO: add better Error responses. This needs to be done properly in all endpoints once
-        // https://github.com/impierce/openid4vc/issues/78 is fixed.
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        tokio::time::sleep(Duration::from_millis(DEFERRED_CREDENTIAL_POLLING_INTERVAL_MS)).await;
+
+        let command = OfferCommand::PollDeferredCredentials {
+            offer_id: offer_id.clone(),
+        };
+
+        if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
     }
 
     let credentials = match query_handler(&offer_id, &state.query.received_offer).await {
@@ -68,6 +101,16 @@ pub(crate) async fn accept(State(state): State<HolderState>, Path(offer_id): Pat
         }
     }
 
+    // Let the Credential Issuer know that the Credentials have been accepted now that they are stored.
+    let command = OfferCommand::SendCredentialNotifications {
+        offer_id: offer_id.clone(),
+        notification_event: NotificationEvent::CredentialAccepted,
+    };
+
+    if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
     // TODO: What do we return here?
     StatusCode::OK.into_response()
 }
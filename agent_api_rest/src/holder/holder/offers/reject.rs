@@ -1,5 +1,12 @@
-use agent_holder::{offer::command::OfferCommand, state::HolderState};
-use agent_shared::handlers::command_handler;
+use agent_holder::{
+    credential::{command::CredentialCommand, queries::all_credentials::AllHolderCredentialsView},
+    offer::command::OfferCommand,
+    state::HolderState,
+};
+use agent_shared::{
+    handlers::{command_handler, query_handler},
+    notification::NotificationEvent,
+};
 use axum::{
     extract::{Path, State},
     response::{IntoResponse, Response},
@@ -19,6 +26,40 @@ pub(crate) async fn reject(State(state): State<HolderState>, Path(offer_id): Pat
         return StatusCode::INTERNAL_SERVER_ERROR.into_response();
     }
 
+    // Delete the Credentials of this Credential Offer that were already stored.
+    let credential_ids = match query_handler("all_holder_credentials", &state.query.all_holder_credentials).await {
+        Ok(Some(AllHolderCredentialsView { credentials })) => credentials
+            .into_iter()
+            .filter(|(_, credential)| credential.offer_id.as_ref() == Some(&offer_id))
+            .map(|(credential_id, _)| credential_id)
+            .collect(),
+        Ok(None) => vec![],
+        _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    for credential_id in credential_ids {
+        let command = CredentialCommand::DeleteCredential {
+            credential_id: credential_id.clone(),
+        };
+
+        if command_handler(&credential_id, &state.command.credential, command)
+            .await
+            .is_err()
+        {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    // Let the Credential Issuer know that the Credentials that were already received have been deleted.
+    let command = OfferCommand::SendCredentialNotifications {
+        offer_id: offer_id.clone(),
+        notification_event: NotificationEvent::CredentialDeleted,
+    };
+
+    if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
     // TODO: What do we return here?
     StatusCode::OK.into_response()
 }
@@ -0,0 +1,271 @@
+use agent_issuance::{
+    server_config::{command::ServerConfigCommand, error::ServerConfigError, queries::ServerConfigView},
+    state::{IssuanceState, SERVER_CONFIG_ID},
+};
+use agent_shared::{
+    config::CredentialConfiguration,
+    handlers::{command_handler, query_handler},
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use cqrs_es::AggregateError;
+use serde_json::Value;
+use tracing::info;
+
+#[axum_macros::debug_handler]
+pub(crate) async fn credential_configurations(
+    State(state): State<IssuanceState>,
+    Json(payload): Json<Value>,
+) -> Response {
+    info!("Request Body: {}", payload);
+
+    let Ok(credential_configuration) = serde_json::from_value::<CredentialConfiguration>(payload) else {
+        return (StatusCode::BAD_REQUEST, "invalid payload").into_response();
+    };
+
+    let credential_configuration_id = credential_configuration.credential_configuration_id.clone();
+
+    let command = ServerConfigCommand::AddCredentialConfiguration {
+        credential_configuration,
+    };
+
+    match command_handler(SERVER_CONFIG_ID, &state.command.server_config, command).await {
+        Ok(_) => credential_configuration_response(&state, &credential_configuration_id, StatusCode::CREATED).await,
+        // An existing Credential Configuration is not silently overwritten.
+        Err(AggregateError::UserError(err @ ServerConfigError::CredentialConfigurationAlreadyExistsError(_))) => {
+            (StatusCode::CONFLICT, err.to_string()).into_response()
+        }
+        Err(AggregateError::UserError(
+            err @ (ServerConfigError::InvalidCredentialSchemaError(_)
+            | ServerConfigError::InvalidCredentialTemplateError(..)),
+        )) => (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[axum_macros::debug_handler]
+pub(crate) async fn update_credential_configuration(
+    State(state): State<IssuanceState>,
+    Path(credential_configuration_id): Path<String>,
+    Json(mut payload): Json<Value>,
+) -> Response {
+    info!("Request Body: {}", payload);
+
+    // The `credential_configuration_id` is taken from the path, so it can be omitted from the request body.
+    match payload.get("credential_configuration_id") {
+        None => {
+            if let Some(payload) = payload.as_object_mut() {
+                payload.insert(
+                    "credential_configuration_id".to_string(),
+                    Value::String(credential_configuration_id.clone()),
+                );
+            }
+        }
+        Some(id) if *id != Value::String(credential_configuration_id.clone()) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                "`credential_configuration_id` does not match the id in the path",
+            )
+                .into_response();
+        }
+        Some(_) => {}
+    }
+
+    let Ok(credential_configuration) = serde_json::from_value::<CredentialConfiguration>(payload) else {
+        return (StatusCode::BAD_REQUEST, "invalid payload").into_response();
+    };
+
+    let command = ServerConfigCommand::UpdateCredentialConfiguration {
+        credential_configuration,
+    };
+
+    match command_handler(SERVER_CONFIG_ID, &state.command.server_config, command).await {
+        Ok(_) => credential_configuration_response(&state, &credential_configuration_id, StatusCode::OK).await,
+        Err(AggregateError::UserError(err @ ServerConfigError::CredentialConfigurationNotFoundError(_))) => {
+            (StatusCode::NOT_FOUND, err.to_string()).into_response()
+        }
+        Err(AggregateError::UserError(
+            err @ (ServerConfigError::InvalidCredentialSchemaError(_)
+            | ServerConfigError::InvalidCredentialTemplateError(..)),
+        )) => (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[axum_macros::debug_handler]
+pub(crate) async fn delete_credential_configuration(
+    State(state): State<IssuanceState>,
+    Path(credential_configuration_id): Path<String>,
+) -> Response {
+    let command = ServerConfigCommand::RemoveCredentialConfiguration {
+        credential_configuration_id,
+    };
+
+    match command_handler(SERVER_CONFIG_ID, &state.command.server_config, command).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(AggregateError::UserError(err @ ServerConfigError::CredentialConfigurationNotFoundError(_))) => {
+            (StatusCode::NOT_FOUND, err.to_string()).into_response()
+        }
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// Returns the Credential Configuration as it is advertised in the Credential Issuer Metadata.
+async fn credential_configuration_response(
+    state: &IssuanceState,
+    credential_configuration_id: &str,
+    status_code: StatusCode,
+) -> Response {
+    match query_handler(SERVER_CONFIG_ID, &state.query.server_config).await {
+        Ok(Some(ServerConfigView {
+            credential_issuer_metadata: Some(credential_issuer_metadata),
+            ..
+        })) => match credential_issuer_metadata
+            .credential_configurations_supported
+            .get(credential_configuration_id)
+        {
+            Some(credential_configuration) => (status_code, Json(credential_configuration)).into_response(),
+            None => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        },
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{issuance::router, tests::BASE_URL, API_VERSION};
+    use agent_issuance::{startup_commands::startup_commands, state::initialize};
+    use agent_secret_manager::service::Service;
+    use agent_store::in_memory;
+    use axum::{
+        body::Body,
+        http::{self, Request},
+        Router,
+    };
+    use serde_json::json;
+    use tower::Service as _;
+
+    async fn request(app: &mut Router, method: http::Method, uri: String, body: Option<Value>) -> (StatusCode, Value) {
+        let response = app
+            .call(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(
+                        body.map(|body| Body::from(body.to_string()))
+                            .unwrap_or_else(Body::empty),
+                    )
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status_code = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status_code, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    async fn credential_configurations_supported(app: &mut Router) -> Value {
+        let (status_code, body) = request(
+            app,
+            http::Method::GET,
+            "/.well-known/openid-credential-issuer".to_string(),
+            None,
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::OK);
+
+        body["credential_configurations_supported"].clone()
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_credential_configurations_endpoints() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        let degree = json!({
+            "credential_configuration_id": "degree",
+            "format": "vc+sd-jwt",
+            "vct": "https://example.com/credentials/degree",
+            "display": [{
+                "name": "Degree Credential",
+                "locale": "en"
+            }]
+        });
+
+        // Add a new Credential Configuration.
+        let (status_code, body) = request(
+            &mut app,
+            http::Method::POST,
+            format!("{API_VERSION}/credential-configurations"),
+            Some(degree.clone()),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::CREATED);
+        assert_eq!(body["vct"], "https://example.com/credentials/degree");
+
+        let credential_configurations_supported = credential_configurations_supported(&mut app).await;
+        assert!(credential_configurations_supported.get("badge").is_some());
+        assert!(credential_configurations_supported.get("degree").is_some());
+
+        // An existing Credential Configuration cannot be added twice.
+        let (status_code, _) = request(
+            &mut app,
+            http::Method::POST,
+            format!("{API_VERSION}/credential-configurations"),
+            Some(degree),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::CONFLICT);
+
+        // Update the display of the Credential Configuration.
+        let (status_code, body) = request(
+            &mut app,
+            http::Method::PUT,
+            format!("{API_VERSION}/credential-configurations/degree"),
+            Some(json!({
+                "format": "vc+sd-jwt",
+                "vct": "https://example.com/credentials/degree",
+                "display": [{
+                    "name": "University Degree",
+                    "locale": "en"
+                }]
+            })),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(body["display"][0]["name"], "University Degree");
+
+        // Remove the Credential Configuration.
+        let (status_code, _) = request(
+            &mut app,
+            http::Method::DELETE,
+            format!("{API_VERSION}/credential-configurations/degree"),
+            None,
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::NO_CONTENT);
+
+        let credential_configurations_supported = credential_configurations_supported(&mut app).await;
+        assert!(credential_configurations_supported.get("degree").is_none());
+
+        // A Credential Configuration that does not exist cannot be removed.
+        let (status_code, _) = request(
+            &mut app,
+            http::Method::DELETE,
+            format!("{API_VERSION}/credential-configurations/degree"),
+            None,
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,343 @@
+use agent_issuance::{
+    offer::{
+        aggregate::CodeChallengeMethod,
+        command::OfferCommand,
+        queries::{issuer_state::IssuerStateView, OfferView},
+    },
+    state::IssuanceState,
+};
+use agent_shared::{
+    config::config,
+    handlers::{command_handler, query_handler},
+};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use cqrs_es::AggregateError;
+use hyper::header;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::info;
+use url::Url;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AuthorizationRequest {
+    pub response_type: String,
+    pub client_id: String,
+    pub redirect_uri: Option<String>,
+    pub state: Option<String>,
+    pub issuer_state: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<CodeChallengeMethod>,
+    pub scope: Option<String>,
+    pub authorization_details: Option<String>,
+    /// The Transaction Code of the Credential Offer, required when the Credential Offer has one.
+    pub tx_code: Option<String>,
+}
+
+#[axum_macros::debug_handler]
+pub(crate) async fn authorize(
+    State(state): State<IssuanceState>,
+    Query(authorization_request): Query<AuthorizationRequest>,
+) -> Response {
+    info!("Request Query: {}", json!(authorization_request));
+
+    let AuthorizationRequest {
+        response_type,
+        client_id,
+        redirect_uri,
+        state: client_state,
+        issuer_state,
+        code_challenge,
+        code_challenge_method,
+        tx_code,
+        ..
+    } = authorization_request;
+
+    // The Authorization Code is only sent to a `redirect_uri` that was registered by the client, so that it cannot be
+    // redirected to an arbitrary URL (RFC 6749, section 10.15).
+    let Some(redirect_uris) = config()
+        .authorization_clients
+        .iter()
+        .find(|client| client.client_id == client_id)
+        .map(|client| client.redirect_uris.clone())
+    else {
+        return (StatusCode::BAD_REQUEST, "invalid `client_id`").into_response();
+    };
+
+    let registered_redirect_uri = match (&redirect_uri, redirect_uris.as_slice()) {
+        (Some(redirect_uri), redirect_uris) if redirect_uris.contains(redirect_uri) => redirect_uri,
+        (Some(_), _) => return (StatusCode::BAD_REQUEST, "invalid `redirect_uri`").into_response(),
+        // The `redirect_uri` can only be omitted when the client registered a single one.
+        (None, [redirect_uri]) => redirect_uri,
+        (None, _) => return (StatusCode::BAD_REQUEST, "missing `redirect_uri`").into_response(),
+    };
+
+    let Ok(mut redirect_url) = Url::parse(registered_redirect_uri) else {
+        return (StatusCode::BAD_REQUEST, "invalid `redirect_uri`").into_response();
+    };
+
+    if response_type != "code" {
+        return (StatusCode::BAD_REQUEST, "unsupported_response_type").into_response();
+    }
+
+    // PKCE is required since Wallets are public clients. Only `S256` is supported, as advertised in the Authorization
+    // Server Metadata.
+    let Some(code_challenge) = code_challenge else {
+        return (StatusCode::BAD_REQUEST, "missing `code_challenge`").into_response();
+    };
+
+    if code_challenge_method != Some(CodeChallengeMethod::S256) {
+        return (StatusCode::BAD_REQUEST, "`code_challenge_method` must be `S256`").into_response();
+    }
+
+    // The `issuer_state` binds the Authorization Request to a Credential Offer that was created by the issuer. Since it
+    // is only known to the recipient of the Credential Offer, it takes the place of authenticating the End-User, together
+    // with the Transaction Code when the Credential Offer has one.
+    let Some(issuer_state) = issuer_state else {
+        return (StatusCode::BAD_REQUEST, "missing `issuer_state`").into_response();
+    };
+
+    // Use the `issuer_state` to get the `offer_id` from the `IssuerStateView`.
+    let offer_id = match query_handler(&issuer_state, &state.query.issuer_state).await {
+        Ok(Some(IssuerStateView { offer_id })) => offer_id,
+        Ok(None) => return (StatusCode::BAD_REQUEST, "invalid `issuer_state`").into_response(),
+        _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    // Every attempt to provide the Transaction Code is registered, so that the number of attempts is limited across both
+    // grants.
+    if tx_code.is_some() {
+        let register_attempt = OfferCommand::RegisterTransactionCodeAttempt {
+            offer_id: offer_id.clone(),
+        };
+
+        match command_handler(&offer_id, &state.command.offer, register_attempt).await {
+            Ok(_) => {}
+            Err(AggregateError::UserError(err)) => return (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        };
+    }
+
+    let command = OfferCommand::CreateAuthorizationCode {
+        offer_id: offer_id.clone(),
+        code_challenge,
+        code_challenge_method: CodeChallengeMethod::S256,
+        // The Token Request must contain the `redirect_uri` only when it was included in the Authorization Request.
+        redirect_uri,
+        tx_code,
+    };
+
+    // Create an Authorization Code using the `offer_id` and the PKCE parameters.
+    match command_handler(&offer_id, &state.command.offer, command).await {
+        Ok(_) => {}
+        // The Credential Offer has expired, has been cancelled or has already been redeemed, or the Transaction Code is
+        // invalid.
+        Err(AggregateError::UserError(err)) => return (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    // Use the `offer_id` to get the `authorization_code` from the `OfferView`.
+    let authorization_code = match query_handler(&offer_id, &state.query.offer).await {
+        Ok(Some(OfferView {
+            authorization_code: Some(authorization_code),
+            ..
+        })) => authorization_code,
+        _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    // Redirect the Wallet back to its `redirect_uri` including the `code` and the (optional) `state`.
+    {
+        let mut query_pairs = redirect_url.query_pairs_mut();
+        query_pairs.append_pair("code", &authorization_code);
+        if let Some(client_state) = client_state {
+            query_pairs.append_pair("state", &client_state);
+        }
+    }
+
+    (StatusCode::FOUND, [(header::LOCATION, redirect_url.to_string())]).into_response()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        issuance::{credentials::tests::credentials, router},
+        tests::{BASE_URL, OFFER_ID},
+    };
+    use agent_issuance::{
+        offer::aggregate::test_utils::{CODE_CHALLENGE, CODE_VERIFIER},
+        startup_commands::startup_commands,
+        state::initialize,
+    };
+    use agent_secret_manager::service::Service;
+    use agent_store::in_memory;
+    use axum::{
+        body::Body,
+        http::{self, Request},
+        Router,
+    };
+    use oid4vci::token_response::TokenResponse;
+    use rstest::rstest;
+    use serde_json::Value;
+    use tower::Service as _;
+
+    const REDIRECT_URI: &str = "https://wallet.example.org/callback";
+
+    pub async fn authorize(app: &mut Router, issuer_state: String) -> String {
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!(
+                        "/auth/authorize?response_type=code&client_id=wallet&redirect_uri={}&state=xyz&issuer_state={issuer_state}&code_challenge={CODE_CHALLENGE}&code_challenge_method=S256",
+                        url::form_urlencoded::byte_serialize(REDIRECT_URI.as_bytes()).collect::<String>()
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FOUND);
+
+        let location: Url = response
+            .headers()
+            .get(http::header::LOCATION)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        assert!(location.as_str().starts_with(REDIRECT_URI));
+        assert_eq!(location.query_pairs().find(|(key, _)| key == "state").unwrap().1, "xyz");
+
+        location
+            .query_pairs()
+            .find(|(key, _)| key == "code")
+            .map(|(_, code)| code.to_string())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_authorization_code_flow() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state.clone());
+
+        credentials(&mut app).await;
+
+        let issuer_state = match query_handler(OFFER_ID, &issuance_state.query.offer).await {
+            Ok(Some(OfferView { issuer_state, .. })) => issuer_state,
+            _ => unreachable!(),
+        };
+
+        let code = authorize(&mut app, issuer_state).await;
+
+        let response = token_request(&mut app, &code).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let token_response: TokenResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(token_response.token_type, "bearer");
+
+        // The Authorization Code can only be redeemed once.
+        let response = token_request(&mut app, &code).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap()["error"],
+            "invalid_grant"
+        );
+
+        let response = token_request(&mut app, "unknown-code").await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap()["error"],
+            "invalid_grant"
+        );
+    }
+
+    #[rstest]
+    #[case::unregistered_client_id("wallet-unknown", Some(REDIRECT_URI), "S256")]
+    #[case::unregistered_redirect_uri("wallet", Some("https://attacker.example.org/callback"), "S256")]
+    #[case::plain_code_challenge_method("wallet", Some(REDIRECT_URI), "plain")]
+    #[tokio::test]
+    async fn test_authorize_rejects_invalid_request(
+        #[case] client_id: &str,
+        #[case] redirect_uri: Option<&str>,
+        #[case] code_challenge_method: &str,
+    ) {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state.clone());
+
+        credentials(&mut app).await;
+
+        let issuer_state = match query_handler(OFFER_ID, &issuance_state.query.offer).await {
+            Ok(Some(OfferView { issuer_state, .. })) => issuer_state,
+            _ => unreachable!(),
+        };
+
+        let mut query = vec![
+            ("response_type", "code"),
+            ("client_id", client_id),
+            ("issuer_state", &issuer_state),
+            ("code_challenge", CODE_CHALLENGE),
+            ("code_challenge_method", code_challenge_method),
+        ];
+        query.extend(redirect_uri.map(|redirect_uri| ("redirect_uri", redirect_uri)));
+
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!(
+                        "/auth/authorize?{}",
+                        serde_urlencoded::to_string(query).unwrap()
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // The Wallet is never redirected when the request is invalid.
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(response.headers().get(http::header::LOCATION).is_none());
+    }
+
+    async fn token_request(app: &mut Router, code: &str) -> Response {
+        app.call(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/auth/token")
+                .header(
+                    http::header::CONTENT_TYPE,
+                    mime::APPLICATION_WWW_FORM_URLENCODED.as_ref(),
+                )
+                .body(Body::from(
+                    serde_urlencoded::to_string([
+                        ("grant_type", "authorization_code"),
+                        ("code", code),
+                        ("code_verifier", CODE_VERIFIER),
+                        ("redirect_uri", REDIRECT_URI),
+                    ])
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+}
@@ -0,0 +1,253 @@
+use agent_issuance::{
+    offer::{aggregate::C_NONCE_EXPIRES_IN, command::OfferCommand, queries::OfferView},
+    server_config::queries::ServerConfigView,
+    state::{IssuanceState, SERVER_CONFIG_ID},
+};
+use agent_shared::handlers::{command_handler, query_handler};
+use axum::{
+    extract::{rejection::JsonRejection, Json, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use oid4vci::{
+    credential_request::BatchCredentialRequest,
+    credential_response::{BatchCredentialResponse, CredentialResponseType},
+};
+use serde_json::json;
+use tracing::info;
+
+use super::credential::{
+    accepted_locales, invalid_credential_request_response, select_credential_id, sign_credential,
+    unsupported_credential_response, wait_for_credentials,
+};
+use super::dpop::AccessToken;
+use super::error::{ErrorCode, ErrorResponse};
+use super::nonce::invalid_proof_response;
+use super::token::offer_id_from_access_token;
+
+#[axum_macros::debug_handler]
+pub(crate) async fn batch_credential(
+    State(state): State<IssuanceState>,
+    access_token: AccessToken,
+    headers: HeaderMap,
+    batch_credential_request: Result<Json<BatchCredentialRequest>, JsonRejection>,
+) -> Response {
+    let Json(batch_credential_request) = match batch_credential_request {
+        Ok(batch_credential_request) => batch_credential_request,
+        Err(rejection) => return invalid_credential_request_response(rejection),
+    };
+
+    info!("Request Body: {}", json!(batch_credential_request));
+
+    let offer_id = match offer_id_from_access_token(&state, &access_token).await {
+        Ok(offer_id) => offer_id,
+        Err(response) => return response,
+    };
+
+    let credential_requests = batch_credential_request.credential_requests;
+    let locales = accepted_locales(&headers);
+
+    if credential_requests.is_empty() {
+        return ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidCredentialRequest)
+            .with_description("missing `credential_requests`")
+            .into_response();
+    }
+
+    // Get the `credential_issuer_metadata` and `authorization_server_metadata` from the `ServerConfigView`.
+    let (credential_issuer_metadata, authorization_server_metadata) =
+        match query_handler(SERVER_CONFIG_ID, &state.query.server_config).await {
+            Ok(Some(ServerConfigView {
+                credential_issuer_metadata: Some(credential_issuer_metadata),
+                authorization_server_metadata,
+                ..
+            })) => (
+                Box::new(credential_issuer_metadata),
+                Box::new(authorization_server_metadata),
+            ),
+            _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        };
+
+    // Use the `offer_id` to verify the `proof` inside each of the `CredentialRequest`s.
+    let command = OfferCommand::VerifyCredentialRequest {
+        offer_id: offer_id.clone(),
+        credential_issuer_metadata: credential_issuer_metadata.clone(),
+        authorization_server_metadata,
+        credential_requests: credential_requests.clone(),
+    };
+
+    if let Err(error) = command_handler(&offer_id, &state.command.offer, command).await {
+        return invalid_proof_response(&state, &offer_id, error).await;
+    };
+
+    // Use the `offer_id` to get the `credential_ids`, their `credential_configuration_ids` and the `subject_id` from the
+    // `OfferView`.
+    let (credential_ids, credential_configuration_ids, subject_id) = match wait_for_credentials(&state, &offer_id).await
+    {
+        Ok(Some(credentials)) => credentials,
+        // When the credentials are not available in time, the issuance of all credentials is deferred. Each of them can
+        // be obtained from the Deferred Credential Endpoint using the same `transaction_id`.
+        Ok(None) => {
+            let credential_count = credential_requests.len();
+
+            let command = OfferCommand::CreateDeferredCredentialResponse {
+                offer_id: offer_id.clone(),
+                credential_requests,
+            };
+
+            if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            };
+
+            return match query_handler(&offer_id, &state.query.offer).await {
+                Ok(Some(OfferView {
+                    transaction_id: Some(transaction_id),
+                    c_nonce,
+                    ..
+                })) => {
+                    let batch_credential_response = BatchCredentialResponse {
+                        credential_responses: (0..credential_count)
+                            .map(|_| CredentialResponseType::Deferred {
+                                transaction_id: transaction_id.clone(),
+                            })
+                            .collect(),
+                        c_nonce_expires_in: c_nonce.as_ref().map(|_| C_NONCE_EXPIRES_IN),
+                        c_nonce,
+                    };
+
+                    (StatusCode::OK, Json(batch_credential_response)).into_response()
+                }
+                _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            };
+        }
+        Err(status_code) => return status_code.into_response(),
+    };
+
+    // Select a distinct credential for each of the `CredentialRequest`s and sign it.
+    let mut selected_credential_ids = vec![];
+    let mut signed_credentials = vec![];
+    for credential_request in &credential_requests {
+        let Some(credential_id) = select_credential_id(
+            &credential_issuer_metadata,
+            &credential_configuration_ids,
+            &credential_ids,
+            credential_request,
+            &selected_credential_ids,
+        ) else {
+            return unsupported_credential_response(&credential_issuer_metadata, credential_request);
+        };
+
+        let signed_credential = match sign_credential(&state, &credential_id, &subject_id, &locales).await {
+            Ok(signed_credential) => signed_credential,
+            Err(response) => return response,
+        };
+
+        selected_credential_ids.push(credential_id);
+        signed_credentials.push(signed_credential);
+    }
+
+    let command = OfferCommand::CreateBatchCredentialResponse {
+        offer_id: offer_id.clone(),
+        signed_credentials,
+    };
+
+    // Use the `offer_id` to create a `BatchCredentialResponse` from the signed credentials.
+    if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+
+    // Use the `offer_id` to get the `batch_credential_response` from the `OfferView`.
+    match query_handler(&offer_id, &state.query.offer).await {
+        Ok(Some(OfferView {
+            batch_credential_response: Some(batch_credential_response),
+            ..
+        })) => (StatusCode::OK, Json(batch_credential_response)).into_response(),
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        issuance::{
+            credential_issuer::token::tests::token, credentials::tests::credentials, offers::tests::offers, router,
+        },
+        tests::BASE_URL,
+    };
+    use agent_issuance::{
+        offer::aggregate::test_utils::PROOF_JWT, startup_commands::startup_commands, state::initialize,
+    };
+    use agent_secret_manager::service::Service;
+    use agent_store::in_memory;
+    use axum::{
+        body::Body,
+        http::{self, Request},
+    };
+    use serde_json::Value;
+    use tower::Service as _;
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_batch_credential_endpoint() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        // Add two credentials to the same offer.
+        credentials(&mut app).await;
+        credentials(&mut app).await;
+
+        let pre_authorized_code = offers(&mut app).await;
+
+        let access_token = token(&mut app, pre_authorized_code).await;
+
+        let credential_request = json!({
+            "format": "jwt_vc_json",
+            "credential_definition": {
+                "type": [
+                    "VerifiableCredential",
+                    "OpenBadgeCredential"
+                ]
+            },
+            "proof": {
+                "proof_type": "jwt",
+                "jwt": PROOF_JWT
+            }
+        });
+
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/openid4vci/batch_credential")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", access_token))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "credential_requests": [credential_request.clone(), credential_request]
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let batch_credential_response: BatchCredentialResponse = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(batch_credential_response.credential_responses.len(), 2);
+        for credential_response in batch_credential_response.credential_responses {
+            assert!(matches!(
+                credential_response,
+                CredentialResponseType::Immediate {
+                    credential: Value::String(_),
+                    ..
+                }
+            ));
+        }
+    }
+}
@@ -1,11 +1,8 @@
-use std::time::{Duration, Instant};
+use std::{collections::HashMap, mem::discriminant, time::Duration};
 
 use agent_issuance::{
     credential::{command::CredentialCommand, queries::CredentialView},
-    offer::{
-        command::OfferCommand,
-        queries::{access_token::AccessTokenView, OfferView},
-    },
+    offer::{command::OfferCommand, queries::OfferView},
     server_config::queries::ServerConfigView,
     state::{IssuanceState, SERVER_CONFIG_ID},
 };
@@ -14,32 +11,44 @@ use agent_shared::{
     handlers::{command_handler, query_handler},
 };
 use axum::{
-    extract::{Json, State},
-    http::StatusCode,
+    extract::{rejection::JsonRejection, Json, State},
+    http::{header::ACCEPT_LANGUAGE, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
-use axum_auth::AuthBearer;
-use oid4vci::credential_request::CredentialRequest;
+use cqrs_es::AggregateError;
+use oid4vci::{
+    credential_format_profiles::{CredentialFormats, WithParameters},
+    credential_issuer::credential_issuer_metadata::CredentialIssuerMetadata,
+    credential_request::CredentialRequest,
+};
 use serde_json::json;
-use tokio::time::sleep;
-use tracing::{error, info};
+use tokio::time::{timeout_at, Instant};
+use tracing::info;
+
+use super::dpop::AccessToken;
+use super::error::{ErrorCode, ErrorResponse};
+use super::nonce::invalid_proof_response;
+use super::token::offer_id_from_access_token;
 
 const DEFAULT_EXTERNAL_SERVER_RESPONSE_TIMEOUT_MS: u64 = 1000;
-const POLLING_INTERVAL_MS: u64 = 100;
 
 #[axum_macros::debug_handler]
 pub(crate) async fn credential(
     State(state): State<IssuanceState>,
-    AuthBearer(access_token): AuthBearer,
-    Json(credential_request): Json<CredentialRequest>,
-    // TODO: implement official oid4vci error response. This TODO is also in the `token` endpoint.
+    access_token: AccessToken,
+    headers: HeaderMap,
+    credential_request: Result<Json<CredentialRequest>, JsonRejection>,
 ) -> Response {
+    let Json(credential_request) = match credential_request {
+        Ok(credential_request) => credential_request,
+        Err(rejection) => return invalid_credential_request_response(rejection),
+    };
+
     info!("Request Body: {}", json!(credential_request));
 
-    // Use the `access_token` to get the `offer_id` from the `AccessTokenView`.
-    let offer_id = match query_handler(&access_token, &state.query.access_token).await {
-        Ok(Some(AccessTokenView { offer_id })) => offer_id,
-        _ => return StatusCode::UNAUTHORIZED.into_response(),
+    let offer_id = match offer_id_from_access_token(&state, &access_token).await {
+        Ok(offer_id) => offer_id,
+        Err(response) => return response,
     };
 
     // Get the `credential_issuer_metadata` and `authorization_server_metadata` from the `ServerConfigView`.
@@ -48,6 +57,7 @@ pub(crate) async fn credential(
             Ok(Some(ServerConfigView {
                 credential_issuer_metadata: Some(credential_issuer_metadata),
                 authorization_server_metadata,
+                ..
             })) => (
                 Box::new(credential_issuer_metadata),
                 Box::new(authorization_server_metadata),
@@ -57,91 +67,267 @@ pub(crate) async fn credential(
 
     let command = OfferCommand::VerifyCredentialRequest {
         offer_id: offer_id.clone(),
-        credential_issuer_metadata,
+        credential_issuer_metadata: credential_issuer_metadata.clone(),
         authorization_server_metadata,
-        credential_request,
+        credential_requests: vec![credential_request.clone()],
     };
 
     // Use the `offer_id` to verify the `proof` inside the `CredentialRequest`.
+    if let Err(error) = command_handler(&offer_id, &state.command.offer, command).await {
+        return invalid_proof_response(&state, &offer_id, error).await;
+    };
+
+    // Use the `offer_id` to get the `credential_ids`, their `credential_configuration_ids` and the `subject_id` from the
+    // `OfferView`.
+    let (credential_ids, credential_configuration_ids, subject_id) = match wait_for_credentials(&state, &offer_id).await
+    {
+        Ok(Some(credentials)) => credentials,
+        // When the credentials are not available in time, the issuance of the credential is deferred.
+        Ok(None) => {
+            let command = OfferCommand::CreateDeferredCredentialResponse {
+                offer_id: offer_id.clone(),
+                credential_requests: vec![credential_request],
+            };
+
+            if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            };
+
+            return match query_handler(&offer_id, &state.query.offer).await {
+                Ok(Some(OfferView {
+                    credential_response: Some(credential_response),
+                    ..
+                })) => (StatusCode::OK, Json(credential_response)).into_response(),
+                _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            };
+        }
+        Err(status_code) => return status_code.into_response(),
+    };
+
+    // Select the credential that matches the `CredentialRequest`.
+    let Some(credential_id) = select_credential_id(
+        &credential_issuer_metadata,
+        &credential_configuration_ids,
+        &credential_ids,
+        &credential_request,
+        &[],
+    ) else {
+        return unsupported_credential_response(&credential_issuer_metadata, &credential_request);
+    };
+
+    // Use the `credential_id` and `subject_id` to sign the credential.
+    // The name of the credential is localized according to the `Accept-Language` of the Wallet.
+    let locales = accepted_locales(&headers);
+    let signed_credential = match sign_credential(&state, &credential_id, &subject_id, &locales).await {
+        Ok(signed_credential) => signed_credential,
+        Err(response) => return response,
+    };
+
+    let command = OfferCommand::CreateCredentialResponse {
+        offer_id: offer_id.clone(),
+        signed_credential,
+    };
+
+    // Use the `offer_id` to create a `CredentialResponse` from the `CredentialRequest` and `credentials`.
     if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
-        StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
     };
 
+    // Use the `offer_id` to get the `credential_response` from the `OfferView`.
+    match query_handler(&offer_id, &state.query.offer).await {
+        Ok(Some(OfferView {
+            credential_response: Some(credential_response),
+            ..
+        })) => (StatusCode::OK, Json(credential_response)).into_response(),
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// Waits until the Offer includes the `credential_ids` and `subject_id`. When the credentials are provided by an
+/// external server, this might take a while. Instead of polling the `OfferView`, the request handler is woken up by
+/// the `TxChannelRegistry` as soon as credentials are added to the Offer. Returns `None` when the credentials are not
+/// provided in time. The `credential_ids` are returned together with the id of the Credential Configuration of each
+/// credential.
+pub(crate) async fn wait_for_credentials(
+    state: &IssuanceState,
+    offer_id: &str,
+) -> Result<Option<(Vec<String>, HashMap<String, String>, String)>, StatusCode> {
     let timeout = config()
         .external_server_response_timeout_ms
         .unwrap_or(DEFAULT_EXTERNAL_SERVER_RESPONSE_TIMEOUT_MS);
-    let start_time = Instant::now();
+    let deadline = Instant::now() + Duration::from_millis(timeout);
+
+    // Subscribe before querying the `OfferView` so that credentials that are added in between are not missed.
+    let mut receiver = state.tx_channel_registry.subscribe(offer_id);
 
-    // TODO: replace this polling solution with a call to the `TxChannelRegistry` as described here: https://github.com/impierce/ssi-agent/issues/75
-    // Use the `offer_id` to get the `credential_ids` and `subject_id` from the `OfferView`.
-    let (credential_ids, subject_id) = loop {
-        match query_handler(&offer_id, &state.query.offer).await {
+    loop {
+        match query_handler(offer_id, &state.query.offer).await {
             // When the Offer does not include the credential id's yet, wait for the external server to provide them.
             Ok(Some(OfferView { credential_ids, .. })) if credential_ids.is_empty() => {
-                if start_time.elapsed().as_millis() <= timeout.into() {
-                    sleep(Duration::from_millis(POLLING_INTERVAL_MS)).await;
-                } else {
-                    error!("timeout failure");
-                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                if !matches!(timeout_at(deadline, receiver.changed()).await, Ok(Ok(()))) {
+                    info!("The credentials were not provided within {timeout}ms");
+                    return Ok(None);
                 }
             }
             Ok(Some(OfferView {
                 credential_ids,
+                credential_configuration_ids,
                 subject_id: Some(subject_id),
                 ..
-            })) => break (credential_ids, subject_id),
-            _ => {
-                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-            }
+            })) => return Ok(Some((credential_ids, credential_configuration_ids, subject_id))),
+            _ => return Err(StatusCode::INTERNAL_SERVER_ERROR),
         }
-    };
-
-    // Use the `credential_ids` and `subject_id` to sign all the credentials.
-    let mut signed_credentials = vec![];
-    for credential_id in credential_ids {
-        let command = CredentialCommand::SignCredential {
-            subject_id: subject_id.clone(),
-            overwrite: false,
-        };
+    }
+}
 
-        if command_handler(&credential_id, &state.command.credential, command)
-            .await
-            .is_err()
-        {
-            StatusCode::INTERNAL_SERVER_ERROR.into_response();
-        };
+/// Returns the id of the credential in `credential_ids` (excluding the ones in `skip`) whose Credential Configuration
+/// is the one that is requested by the `credential_format` of the `CredentialRequest`.
+pub(crate) fn select_credential_id(
+    credential_issuer_metadata: &CredentialIssuerMetadata,
+    credential_configuration_ids: &HashMap<String, String>,
+    credential_ids: &[String],
+    credential_request: &CredentialRequest,
+    skip: &[String],
+) -> Option<String> {
+    credential_ids
+        .iter()
+        .filter(|credential_id| !skip.contains(credential_id))
+        .find(|credential_id| {
+            credential_configuration_ids
+                .get(*credential_id)
+                .and_then(|credential_configuration_id| {
+                    credential_issuer_metadata
+                        .credential_configurations_supported
+                        .get(credential_configuration_id)
+                })
+                .is_some_and(|credential_configuration| {
+                    is_requested_credential_format(
+                        &credential_request.credential_format,
+                        &credential_configuration.credential_format,
+                    )
+                })
+        })
+        .cloned()
+}
 
-        let signed_credential = match query_handler(&credential_id, &state.query.credential).await {
-            Ok(Some(CredentialView {
-                signed: Some(signed_credential),
-                ..
-            })) => signed_credential,
-            _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-        };
+/// Returns `true` when the `requested` credential format refers to the `supported` credential format. Only the
+/// parameters that identify the type of the credential are compared, since a Credential Request does not need to
+/// repeat e.g. the claims of the Credential Configuration. A requested `type` may contain more types than the
+/// Credential Configuration, as long as it contains all of its types.
+fn is_requested_credential_format(
+    requested: &CredentialFormats<WithParameters>,
+    supported: &CredentialFormats<WithParameters>,
+) -> bool {
+    match (requested, supported) {
+        (CredentialFormats::JwtVcJson(requested), CredentialFormats::JwtVcJson(supported)) => supported
+            .parameters
+            .credential_definition
+            .type_
+            .iter()
+            .all(|type_| requested.parameters.credential_definition.type_.contains(type_)),
+        (CredentialFormats::LdpVc(requested), CredentialFormats::LdpVc(supported)) => supported
+            .parameters
+            .credential_definition
+            .type_
+            .iter()
+            .all(|type_| requested.parameters.credential_definition.type_.contains(type_)),
+        (CredentialFormats::SdJwtVc(requested), CredentialFormats::SdJwtVc(supported)) => {
+            requested.parameters.vct == supported.parameters.vct
+        }
+        (CredentialFormats::MsoMdoc(requested), CredentialFormats::MsoMdoc(supported)) => {
+            requested.parameters.doctype == supported.parameters.doctype
+        }
+        (requested, supported) => requested == supported,
+    }
+}
 
-        signed_credentials.push(signed_credential);
+/// Returned when none of the offered credentials matches the `CredentialRequest`. When the Credential Issuer does not
+/// support the requested format at all, the format is reported as unsupported instead of the type of the credential.
+pub(crate) fn unsupported_credential_response(
+    credential_issuer_metadata: &CredentialIssuerMetadata,
+    credential_request: &CredentialRequest,
+) -> Response {
+    let is_supported_format = credential_issuer_metadata
+        .credential_configurations_supported
+        .values()
+        .any(|credential_configuration| {
+            discriminant(&credential_configuration.credential_format)
+                == discriminant(&credential_request.credential_format)
+        });
+
+    if is_supported_format {
+        ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::UnsupportedCredentialType)
+            .with_description("None of the offered Credentials matches the requested Credential type")
+            .into_response()
+    } else {
+        ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::UnsupportedCredentialFormat)
+            .with_description("The requested Credential format is not supported")
+            .into_response()
     }
+}
 
-    let command = OfferCommand::CreateCredentialResponse {
-        offer_id: offer_id.clone(),
-        signed_credentials,
+/// Returned when the body of a (Batch) Credential Request cannot be parsed.
+pub(crate) fn invalid_credential_request_response(rejection: JsonRejection) -> Response {
+    ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidCredentialRequest)
+        .with_description(rejection.body_text())
+        .into_response()
+}
+
+/// Signs the credential with the given `credential_id` for the `subject_id` and returns the signed credential.
+pub(crate) async fn sign_credential(
+    state: &IssuanceState,
+    credential_id: &str,
+    subject_id: &str,
+    locales: &[String],
+) -> Result<serde_json::Value, Response> {
+    let command = CredentialCommand::SignCredential {
+        subject_id: subject_id.to_string(),
+        overwrite: false,
+        locales: locales.to_vec(),
     };
 
-    // Use the `offer_id` to create a `CredentialResponse` from the `CredentialRequest` and `credentials`.
-    if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
-        StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    match command_handler(credential_id, &state.command.credential, command).await {
+        Ok(_) => {}
+        Err(AggregateError::UserError(error)) => return Err(ErrorResponse::from(error).into_response()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
     };
 
-    // Use the `offer_id` to get the `credential_response` from the `OfferView`.
-    match query_handler(&offer_id, &state.query.offer).await {
-        Ok(Some(OfferView {
-            credential_response: Some(credential_response),
+    match query_handler(credential_id, &state.query.credential).await {
+        Ok(Some(CredentialView {
+            signed: Some(signed_credential),
             ..
-        })) => (StatusCode::OK, Json(credential_response)).into_response(),
-        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        })) => Ok(signed_credential),
+        _ => Err(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
     }
 }
 
+/// Returns the locales of the `Accept-Language` header of the Wallet, in order of preference.
+pub(crate) fn accepted_locales(headers: &HeaderMap) -> Vec<String> {
+    let mut locales: Vec<(String, f32)> = headers
+        .get_all(ACCEPT_LANGUAGE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|language_range| {
+            let mut parameters = language_range.split(';').map(str::trim);
+            let locale = parameters
+                .next()
+                .filter(|locale| !locale.is_empty() && *locale != "*")?;
+            let quality = match parameters.find_map(|parameter| parameter.strip_prefix("q=")) {
+                Some(quality) => quality.parse().ok()?,
+                None => 1.0,
+            };
+
+            (quality > 0.0).then(|| (locale.to_string(), quality))
+        })
+        .collect();
+
+    // The sort is stable, so locales with the same quality keep their order.
+    locales.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    locales.into_iter().map(|(locale, _)| locale).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,7 +341,18 @@ mod tests {
         tests::{BASE_URL, CREDENTIAL_CONFIGURATION_ID, OFFER_ID},
     };
     use agent_event_publisher_http::EventPublisherHttp;
-    use agent_issuance::{offer::event::OfferEvent, startup_commands::startup_commands, state::initialize};
+    use agent_issuance::{
+        credential::aggregate::test_utils::credential_jwt_signed_by_unicore,
+        offer::{
+            aggregate::{
+                test_utils::{c_nonce, notification_id, transaction_id, PROOF_JWT},
+                C_NONCE_EXPIRES_IN,
+            },
+            event::OfferEvent,
+        },
+        startup_commands::startup_commands,
+        state::initialize,
+    };
     use agent_secret_manager::service::Service;
     use agent_shared::config::{set_config, Events};
     use agent_store::{in_memory, EventPublisher};
@@ -174,6 +371,8 @@ mod tests {
         Mock, MockServer, ResponseTemplate,
     };
 
+    const POLLING_INTERVAL_MS: u64 = 100;
+
     const CREDENTIAL_JWT: &str = "eyJ0eXAiOiJKV1QiLCJhbGciOiJFZERTQSIsImtpZCI6ImRpZDprZXk6ejZNa2dFODROQ01wTWVBeDlqSzljZjVXNEc4Z2NaOXh1d0p2RzFlN3dOazhLQ2d0I3o2TWtnRTg0TkNNcE1lQXg5aks5Y2Y1VzRHOGdjWjl4dXdKdkcxZTd3Tms4S0NndCJ9.eyJpc3MiOiJkaWQ6a2V5Ono2TWtnRTg0TkNNcE1lQXg5aks5Y2Y1VzRHOGdjWjl4dXdKdkcxZTd3Tms4S0NndCIsInN1YiI6ImRpZDprZXk6ejZNa2lpZXlvTE1TVnNKQVp2N0pqZTV3V1NrREV5bVVna3lGOGtiY3JqWnBYM3FkIiwiZXhwIjo5OTk5OTk5OTk5LCJpYXQiOjAsInZjIjp7IkBjb250ZXh0IjoiaHR0cHM6Ly93d3cudzMub3JnLzIwMTgvY3JlZGVudGlhbHMvdjEiLCJ0eXBlIjpbIlZlcmlmaWFibGVDcmVkZW50aWFsIl0sImNyZWRlbnRpYWxTdWJqZWN0Ijp7ImlkIjoiZGlkOmtleTp6Nk1raWlleW9MTVNWc0pBWnY3SmplNXdXU2tERXltVWdreUY4a2JjcmpacFgzcWQiLCJmaXJzdF9uYW1lIjoiRmVycmlzIiwibGFzdF9uYW1lIjoiUnVzdGFjZWFuIn0sImlzc3VlciI6ImRpZDprZXk6ejZNa2dFODROQ01wTWVBeDlqSzljZjVXNEc4Z2NaOXh1d0p2RzFlN3dOazhLQ2d0IiwiaXNzdWFuY2VEYXRlIjoiMjAxMC0wMS0wMVQwMDowMDowMFoifX0.d4QN73vDtZu79RP6GldHObu6rGsjidkLYp0XMRQNbNPY75LJoSv2iXk2Rz5M-VMBZGSU3YPZHytlrKBjxr1IBQ";
 
     trait CredentialEventTrigger {
@@ -217,6 +416,8 @@ mod tests {
                                         credential: json!(CREDENTIAL_JWT),
                                         is_signed: true,
                                         credential_configuration_id: CREDENTIAL_CONFIGURATION_ID.to_string(),
+                                        valid_from: None,
+                                        valid_until: None,
                                     }
                                 } else {
                                     // ...or else, submitting the data that will be signed inside `UniCore`.
@@ -231,6 +432,8 @@ mod tests {
                                         }),
                                         is_signed: false,
                                         credential_configuration_id: CREDENTIAL_CONFIGURATION_ID.to_string(),
+                                        valid_from: None,
+                                        valid_until: None,
                                     }
                                 };
 
@@ -263,12 +466,51 @@ mod tests {
         }
     }
 
+    async fn deferred_credential_request(app: Router, access_token: &str, transaction_id: &str) -> (StatusCode, Value) {
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/openid4vci/deferred_credential")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", access_token))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({ "transaction_id": transaction_id })).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn deferred_credential(app: Router, access_token: &str, transaction_id: &str) -> Value {
+        for _ in 0..10 {
+            let (status, body) = deferred_credential_request(app.clone(), access_token, transaction_id).await;
+
+            if status == StatusCode::OK {
+                return body;
+            }
+
+            // The credential is not available yet.
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body, json!({ "error": "issuance_pending" }));
+
+            tokio::time::sleep(Duration::from_millis(POLLING_INTERVAL_MS)).await;
+        }
+
+        panic!("The deferred credential was not issued in time");
+    }
+
     #[rstest]
     #[case::without_external_server(false, false, 0)]
     #[case::with_external_server(true, false, 0)]
     #[case::with_external_server_and_self_signed_credential(true, true, 0)]
-    #[should_panic(expected = "assertion `left == right` failed\n  left: 500\n right: 200")]
-    #[case::should_panic_due_to_timout(true, false, DEFAULT_EXTERNAL_SERVER_RESPONSE_TIMEOUT_MS + 100)]
+    #[case::deferred_due_to_timeout(true, false, DEFAULT_EXTERNAL_SERVER_RESPONSE_TIMEOUT_MS + 100)]
     #[serial_test::serial]
     #[tokio::test(flavor = "multi_thread")]
     #[tracing_test::traced_test]
@@ -300,7 +542,7 @@ mod tests {
         let issuance_state = in_memory::issuance_state(Service::default(), issuance_event_publishers).await;
         initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
 
-        let mut app = router(issuance_state);
+        let mut app = router(issuance_state.clone());
 
         if let Some(external_server) = &external_server {
             external_server
@@ -320,6 +562,7 @@ mod tests {
         let access_token = token(&mut app, pre_authorized_code).await;
 
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
                     .method(http::Method::POST)
@@ -337,15 +580,7 @@ mod tests {
                             },
                             "proof": {
                                 "proof_type": "jwt",
-                                "jwt": "eyJ0eXAiOiJvcGVuaWQ0dmNpLXByb29mK2p3dCIsImFsZyI6IkVkRFNBIiwia2lk\
-                                        IjoiZGlkOmtleTp6Nk1raWlleW9MTVNWc0pBWnY3SmplNXdXU2tERXltVWdreUY4\
-                                        a2JjcmpacFgzcWQjejZNa2lpZXlvTE1TVnNKQVp2N0pqZTV3V1NrREV5bVVna3lG\
-                                        OGtiY3JqWnBYM3FkIn0.eyJpc3MiOiJkaWQ6a2V5Ono2TWtpaWV5b0xNU1ZzSkFa\
-                                        djdKamU1d1dTa0RFeW1VZ2t5RjhrYmNyalpwWDNxZCIsImF1ZCI6Imh0dHBzOi8v\
-                                        ZXhhbXBsZS5jb20vIiwiZXhwIjo5OTk5OTk5OTk5LCJpYXQiOjE1NzEzMjQ4MDAs\
-                                        Im5vbmNlIjoiN2UwM2FkM2Y3NmNiMzMzOGMzYTU2NDJmZTc2MzQ0NzZhYTNhZDkz\
-                                        ZmExZDU4NDAxMWJhMjE1MGQ5ZGE0NzEzMyJ9.bDxmEWTGwKJJC8J5N16JHAR2ZBY\
-                                        tgWlhM_o_voJdXLnw_ScZMwGjZwNH6aQWKlgIaFWKonF88KNRFX2UAOAuBQ"
+                                "jwt": PROOF_JWT
                             }
                         }))
                         .unwrap(),
@@ -360,10 +595,54 @@ mod tests {
 
         let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
         let body: Value = serde_json::from_slice(&body).unwrap();
+
+        // When the external server does not provide the credential in time, the issuance is deferred.
+        let body = if delay > DEFAULT_EXTERNAL_SERVER_RESPONSE_TIMEOUT_MS {
+            let transaction_id = transaction_id().await;
+            assert_eq!(
+                body,
+                json!({
+                    "transaction_id": transaction_id,
+                    "c_nonce": c_nonce().await,
+                    "c_nonce_expires_in": C_NONCE_EXPIRES_IN
+                })
+            );
+
+            let body = deferred_credential(app.clone(), &access_token, &transaction_id).await;
+
+            // The `transaction_id` is invalidated once its credential has been issued.
+            let (status, error_response) = deferred_credential_request(app, &access_token, &transaction_id).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(error_response["error"], "invalid_transaction_id");
+
+            body
+        } else {
+            body
+        };
+
+        // Only the credentials that are signed by UniCore itself contain an `id` and a `credentialStatus`.
+        let credential_jwt = if is_self_signed {
+            CREDENTIAL_JWT.to_string()
+        } else {
+            let credential_id = query_handler("all_credentials", &issuance_state.query.all_credentials)
+                .await
+                .unwrap()
+                .unwrap()
+                .credentials
+                .into_keys()
+                .next()
+                .unwrap();
+
+            credential_jwt_signed_by_unicore(CREDENTIAL_JWT, &format!("urn:uuid:{credential_id}")).await
+        };
+
         assert_eq!(
             body,
             json!({
-                    "credential": CREDENTIAL_JWT
+                    "credential": credential_jwt,
+                    "notification_id": notification_id().await,
+                    "c_nonce": c_nonce().await,
+                    "c_nonce_expires_in": C_NONCE_EXPIRES_IN
                 }
             )
         );
@@ -373,4 +652,132 @@ mod tests {
             assert!(external_server.received_requests().await.unwrap().len() == 1);
         }
     }
+
+    #[rstest]
+    #[case::unsupported_credential_type(
+        json!({ "format": "jwt_vc_json", "credential_definition": { "type": ["UniversityDegreeCredential"] } }),
+        "unsupported_credential_type"
+    )]
+    #[case::unsupported_credential_format(
+        json!({ "format": "mso_mdoc", "doctype": "org.iso.18013.5.1.mDL" }),
+        "unsupported_credential_format"
+    )]
+    #[serial_test::serial]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_credential_endpoint_with_unsupported_credential(
+        #[case] mut credential_request: Value,
+        #[case] expected_error: &str,
+    ) {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        credentials(&mut app).await;
+        let pre_authorized_code = offers(&mut app).await;
+        let access_token = token(&mut app, pre_authorized_code).await;
+
+        credential_request["proof"] = json!({
+            "proof_type": "jwt",
+            "jwt": PROOF_JWT
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/openid4vci/credential")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", access_token))
+                    .body(Body::from(serde_json::to_vec(&credential_request).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["error"], expected_error);
+    }
+
+    #[test]
+    fn test_select_credential_id() {
+        let credential_issuer_metadata: CredentialIssuerMetadata = serde_json::from_value(json!({
+            "credential_issuer": "https://example.com/",
+            "credential_endpoint": "https://example.com/openid4vci/credential",
+            "credential_configurations_supported": {
+                "badge": {
+                    "format": "jwt_vc_json",
+                    "credential_definition": { "type": ["VerifiableCredential", "OpenBadgeCredential"] }
+                },
+                "mdl": {
+                    "format": "mso_mdoc",
+                    "doctype": "org.iso.18013.5.1.mDL"
+                }
+            }
+        }))
+        .unwrap();
+        let credential_configuration_ids = HashMap::from([
+            ("badge-id".to_string(), "badge".to_string()),
+            ("mdl-id".to_string(), "mdl".to_string()),
+        ]);
+        let credential_ids = vec!["badge-id".to_string(), "mdl-id".to_string()];
+
+        let credential_request =
+            |credential_format: Value| -> CredentialRequest { serde_json::from_value(credential_format).unwrap() };
+
+        let mdl_request = credential_request(json!({ "format": "mso_mdoc", "doctype": "org.iso.18013.5.1.mDL" }));
+
+        // The credential whose Credential Configuration matches is selected, even when it is not the first one.
+        assert_eq!(
+            select_credential_id(
+                &credential_issuer_metadata,
+                &credential_configuration_ids,
+                &credential_ids,
+                &mdl_request,
+                &[]
+            ),
+            Some("mdl-id".to_string())
+        );
+
+        // No other credential is selected instead when the matching credential has already been selected.
+        assert_eq!(
+            select_credential_id(
+                &credential_issuer_metadata,
+                &credential_configuration_ids,
+                &credential_ids,
+                &mdl_request,
+                &["mdl-id".to_string()]
+            ),
+            None
+        );
+
+        // No credential is selected when none of them matches the Credential Request.
+        assert_eq!(
+            select_credential_id(
+                &credential_issuer_metadata,
+                &credential_configuration_ids,
+                &credential_ids,
+                &credential_request(json!({ "format": "vc+sd-jwt", "vct": "https://example.com/identity_credential" })),
+                &[]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_accepted_locales() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            ACCEPT_LANGUAGE,
+            "fr;q=0.5, de-CH, en;q=0.8, *;q=0.1, nl;q=0".parse().unwrap(),
+        );
+
+        assert_eq!(accepted_locales(&headers), vec!["de-CH", "en", "fr"]);
+        assert!(accepted_locales(&HeaderMap::new()).is_empty());
+    }
 }
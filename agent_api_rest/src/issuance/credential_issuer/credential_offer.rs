@@ -0,0 +1,149 @@
+use agent_issuance::{
+    offer::queries::{credential_offer_uri::CredentialOfferUriView, OfferView},
+    state::IssuanceState,
+};
+use agent_shared::handlers::query_handler;
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use oid4vci::credential_offer::CredentialOffer;
+
+/// Serves the Credential Offer that is referenced by the `credential_offer_uri` parameter. The Credential Offer is
+/// looked up by the random id in its `credential_offer_uri`, so only Credential Offers that were created by reference can
+/// be retrieved.
+#[axum_macros::debug_handler]
+pub(crate) async fn credential_offer(
+    State(state): State<IssuanceState>,
+    Path(credential_offer_uri_id): Path<String>,
+) -> Response {
+    // Use the `credential_offer_uri_id` to get the `offer_id` from the `CredentialOfferUriView`.
+    let offer_id = match query_handler(&credential_offer_uri_id, &state.query.credential_offer_uri).await {
+        Ok(Some(CredentialOfferUriView { offer_id })) => offer_id,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    match query_handler(&offer_id, &state.query.offer).await {
+        Ok(Some(OfferView {
+            credential_offer: Some(CredentialOffer::CredentialOffer(credential_offer)),
+            ..
+        })) => (StatusCode::OK, Json(credential_offer)).into_response(),
+        Ok(_) => StatusCode::NOT_FOUND.into_response(),
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        issuance::{credentials::tests::credentials, offers::tests::offers, router},
+        tests::{BASE_URL, OFFER_ID},
+        API_VERSION,
+    };
+    use agent_issuance::{startup_commands::startup_commands, state::initialize};
+    use agent_secret_manager::service::Service;
+    use agent_store::in_memory;
+    use axum::{
+        body::Body,
+        http::{self, Request},
+    };
+    use oid4vci::credential_offer::CredentialOfferParameters;
+    use serde_json::json;
+    use std::str::FromStr;
+    use tower::Service as _;
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_credential_offer_by_reference() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        credentials(&mut app).await;
+
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(&format!("{API_VERSION}/offers"))
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "offerId": OFFER_ID,
+                            "byReference": true
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: String = String::from_utf8(body.to_vec()).unwrap();
+
+        let CredentialOffer::CredentialOfferUri(credential_offer_uri) = CredentialOffer::from_str(&body).unwrap()
+        else {
+            unreachable!()
+        };
+
+        // The `credential_offer_uri` does not reveal the `offer_id`.
+        assert!(credential_offer_uri
+            .as_str()
+            .starts_with("https://example.com/openid4vci/credential_offer/"));
+        assert!(!credential_offer_uri.as_str().contains(OFFER_ID));
+
+        // The Wallet retrieves the Credential Offer using the `credential_offer_uri`.
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(credential_offer_uri.path())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let credential_offer: CredentialOfferParameters = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(credential_offer.credential_issuer, BASE_URL.clone());
+        assert_eq!(credential_offer.credential_configuration_ids, vec!["badge".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_credential_offer_not_found() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        // A Credential Offer that is not passed by reference cannot be retrieved using its `offer_id`.
+        credentials(&mut app).await;
+        offers(&mut app).await;
+
+        for credential_offer_uri_id in ["unknown-offer-id", OFFER_ID] {
+            let response = app
+                .call(
+                    Request::builder()
+                        .method(http::Method::GET)
+                        .uri(format!("/openid4vci/credential_offer/{credential_offer_uri_id}"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use agent_issuance::{
+    offer::{command::OfferCommand, queries::OfferView},
+    server_config::queries::ServerConfigView,
+    state::{IssuanceState, SERVER_CONFIG_ID},
+};
+use agent_shared::handlers::{command_handler, query_handler};
+use axum::{
+    extract::{rejection::JsonRejection, Json, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use cqrs_es::AggregateError;
+use oid4vci::credential_request::CredentialRequest;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::info;
+
+use super::credential::{accepted_locales, select_credential_id, sign_credential, unsupported_credential_response};
+use super::dpop::AccessToken;
+use super::error::{ErrorCode, ErrorResponse};
+use super::token::offer_id_from_access_token;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DeferredCredentialRequest {
+    pub transaction_id: String,
+}
+
+#[axum_macros::debug_handler]
+pub(crate) async fn deferred_credential(
+    State(state): State<IssuanceState>,
+    access_token: AccessToken,
+    headers: HeaderMap,
+    deferred_credential_request: Result<Json<DeferredCredentialRequest>, JsonRejection>,
+) -> Response {
+    let Json(deferred_credential_request) = match deferred_credential_request {
+        Ok(deferred_credential_request) => deferred_credential_request,
+        Err(rejection) => {
+            return ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidRequest)
+                .with_description(rejection.body_text())
+                .into_response()
+        }
+    };
+
+    info!("Request Body: {}", json!(deferred_credential_request));
+
+    let offer_id = match offer_id_from_access_token(&state, &access_token).await {
+        Ok(offer_id) => offer_id,
+        Err(response) => return response,
+    };
+
+    // Get the `credential_issuer_metadata` from the `ServerConfigView`.
+    let credential_issuer_metadata = match query_handler(SERVER_CONFIG_ID, &state.query.server_config).await {
+        Ok(Some(ServerConfigView {
+            credential_issuer_metadata: Some(credential_issuer_metadata),
+            ..
+        })) => credential_issuer_metadata,
+        _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    // Use the `offer_id` to get the deferred Credential Requests that belong to the `transaction_id`, the credentials
+    // that have already been issued using it, and the `credential_ids` and `subject_id` from the `OfferView`.
+    let offer_view = match query_handler(&offer_id, &state.query.offer).await {
+        Ok(Some(offer_view)) => offer_view,
+        _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    if offer_view.transaction_id.as_ref() != Some(&deferred_credential_request.transaction_id) {
+        return ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidTransactionId).into_response();
+    }
+
+    // The deferred Credential Requests are issued in the order in which they were received.
+    let Some(credential_request) = offer_view
+        .deferred_credential_requests
+        .first()
+        .and_then(|credential_request| serde_json::from_value::<CredentialRequest>(credential_request.clone()).ok())
+    else {
+        return ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidTransactionId).into_response();
+    };
+
+    let OfferView {
+        credential_ids,
+        credential_configuration_ids,
+        deferred_credential_ids,
+        subject_id,
+        ..
+    } = offer_view;
+
+    // The credentials have not been provided yet, so the Wallet needs to try again later.
+    let Some(subject_id) = subject_id.filter(|_| !credential_ids.is_empty()) else {
+        return ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::IssuancePending).into_response();
+    };
+
+    // Select the credential that matches the deferred Credential Request, excluding the ones that have already been
+    // issued using the same `transaction_id`.
+    let Some(credential_id) = select_credential_id(
+        &credential_issuer_metadata,
+        &credential_configuration_ids,
+        &credential_ids,
+        &credential_request,
+        &deferred_credential_ids,
+    ) else {
+        return unsupported_credential_response(&credential_issuer_metadata, &credential_request);
+    };
+
+    // Use the `credential_id` and `subject_id` to sign the credential.
+    // The name of the credential is localized according to the `Accept-Language` of the Wallet.
+    let locales = accepted_locales(&headers);
+    let signed_credential = match sign_credential(&state, &credential_id, &subject_id, &locales).await {
+        Ok(signed_credential) => signed_credential,
+        Err(response) => return response,
+    };
+
+    let command = OfferCommand::RedeemTransactionId {
+        offer_id: offer_id.clone(),
+        transaction_id: deferred_credential_request.transaction_id,
+        credential_id: credential_id.clone(),
+        signed_credential,
+    };
+
+    // Redeem the `transaction_id` for the selected credential and create the `CredentialResponse` for it. The
+    // `transaction_id` is invalidated once all of its credentials have been issued.
+    match command_handler(&offer_id, &state.command.offer, command).await {
+        Ok(_) => {}
+        Err(AggregateError::UserError(error)) => return ErrorResponse::from(error).into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    // Use the `offer_id` to get the `credential_response` from the `OfferView`.
+    match query_handler(&offer_id, &state.query.offer).await {
+        Ok(Some(OfferView {
+            credential_response: Some(credential_response),
+            ..
+        })) => (StatusCode::OK, Json(credential_response)).into_response(),
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
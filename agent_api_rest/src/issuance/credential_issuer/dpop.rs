@@ -0,0 +1,111 @@
+use agent_issuance::state::IssuanceState;
+use agent_shared::{
+    config::{config, get_all_enabled_signing_algorithms},
+    dpop::{self, DpopError, DPOP},
+    url_utils::UrlAppendHelpers,
+};
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+
+use super::error::{ErrorCode, ErrorResponse};
+
+/// Validates the `DPoP` proof of a request and returns the JWK Thumbprint of the key it was signed with. Returns `None`
+/// when the request does not contain a proof or when DPoP is not enabled. The `access_token` must be provided for
+/// requests to the protected endpoints of the Credential Issuer.
+///
+/// The `uri` is relative to the public URL of the Credential Issuer, so that the `htu` of the proof can be compared with
+/// the public URL of the request, even when UniCore runs behind a reverse proxy.
+pub(crate) fn dpop_jkt(
+    state: &IssuanceState,
+    headers: &HeaderMap,
+    method: &Method,
+    uri: &Uri,
+    access_token: Option<&str>,
+) -> Result<Option<String>, DpopError> {
+    if !config().dpop_enabled {
+        return Ok(None);
+    }
+
+    let mut proofs = headers.get_all(DPOP).iter();
+
+    let proof = match (proofs.next(), proofs.next()) {
+        (None, _) => return Ok(None),
+        (Some(proof), None) => proof
+            .to_str()
+            .map_err(|e| DpopError::InvalidProofError(e.to_string()))?,
+        (Some(_), Some(_)) => {
+            return Err(DpopError::InvalidProofError(
+                "only a single DPoP proof is allowed".to_string(),
+            ))
+        }
+    };
+
+    let htu = state
+        .url
+        .parse::<url::Url>()
+        .map_err(|e| DpopError::InvalidProofError(e.to_string()))?
+        .append_path_segment(uri.path());
+
+    dpop::verify_proof(
+        proof,
+        &get_all_enabled_signing_algorithms(),
+        method.as_str(),
+        &htu,
+        access_token,
+        &state.dpop_jti_cache,
+    )
+    .map(Some)
+}
+
+/// The Access Token of a request to one of the protected endpoints of the Credential Issuer. The Access Token can be
+/// provided with the `Bearer` authentication scheme or, when it is bound to a key, with the `DPoP` authentication scheme
+/// together with a `DPoP` proof.
+pub(crate) struct AccessToken {
+    pub(crate) access_token: String,
+    /// The JWK Thumbprint of the key that signed the `DPoP` proof of the request.
+    pub(crate) dpop_jkt: Option<String>,
+}
+
+#[async_trait]
+impl FromRequestParts<IssuanceState> for AccessToken {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &IssuanceState) -> Result<Self, Self::Rejection> {
+        let authorization = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|authorization| authorization.to_str().ok())
+            .and_then(|authorization| authorization.split_once(' '));
+
+        match authorization {
+            Some((scheme, access_token)) if scheme.eq_ignore_ascii_case("Bearer") => Ok(AccessToken {
+                access_token: access_token.to_string(),
+                dpop_jkt: None,
+            }),
+            Some((scheme, access_token)) if scheme.eq_ignore_ascii_case(DPOP) && config().dpop_enabled => {
+                match dpop_jkt(state, &parts.headers, &parts.method, &parts.uri, Some(access_token)) {
+                    Ok(Some(dpop_jkt)) => Ok(AccessToken {
+                        access_token: access_token.to_string(),
+                        dpop_jkt: Some(dpop_jkt),
+                    }),
+                    Ok(None) => Err(invalid_dpop_proof_response("Missing `DPoP` proof")),
+                    Err(error) => Err(invalid_dpop_proof_response(error)),
+                }
+            }
+            _ => Err(ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidRequest)
+                .with_description("The `Authorization` header must contain a `Bearer` or `DPoP` Access Token")
+                .into_response()),
+        }
+    }
+}
+
+/// Returned when the `DPoP` proof of a request to one of the protected endpoints is missing or invalid.
+pub(crate) fn invalid_dpop_proof_response(error_description: impl ToString) -> Response {
+    ErrorResponse::new(StatusCode::UNAUTHORIZED, ErrorCode::InvalidDpopProof)
+        .with_description(error_description)
+        .into_response()
+}
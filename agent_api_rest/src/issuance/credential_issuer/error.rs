@@ -0,0 +1,216 @@
+use agent_issuance::{
+    credential::error::CredentialError,
+    offer::{aggregate::C_NONCE_EXPIRES_IN, error::OfferError},
+};
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+/// The error codes defined by OAuth 2.0 (RFC 6749, section 5.2), Bearer Token Usage (RFC 6750, section 3.1), DPoP
+/// (RFC 9449, section 12.2) and OpenID4VCI (sections 7.3.1.2, 9.3 and 10.3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ErrorCode {
+    InvalidRequest,
+    InvalidGrant,
+    InvalidToken,
+    InvalidDpopProof,
+    InvalidCredentialRequest,
+    UnsupportedCredentialType,
+    UnsupportedCredentialFormat,
+    InvalidProof,
+    InvalidNonce,
+    CredentialRequestDenied,
+    IssuancePending,
+    InvalidTransactionId,
+    InvalidNotificationId,
+    InvalidNotificationRequest,
+    ServerError,
+}
+
+/// An error response of the Token Endpoint or of one of the Credential Issuer's endpoints.
+#[derive(Debug, Serialize)]
+pub(crate) struct ErrorResponse {
+    #[serde(skip)]
+    status_code: StatusCode,
+    error: ErrorCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    c_nonce: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    c_nonce_expires_in: Option<u64>,
+}
+
+impl ErrorResponse {
+    pub(crate) fn new(status_code: StatusCode, error: ErrorCode) -> Self {
+        Self {
+            status_code,
+            error,
+            error_description: None,
+            c_nonce: None,
+            c_nonce_expires_in: None,
+        }
+    }
+
+    pub(crate) fn with_description(mut self, error_description: impl ToString) -> Self {
+        self.error_description.replace(error_description.to_string());
+        self
+    }
+
+    /// Adds a fresh `c_nonce` so that the Wallet can retry the Credential Request with a new `proof`.
+    pub(crate) fn with_c_nonce(mut self, c_nonce: String) -> Self {
+        self.c_nonce.replace(c_nonce);
+        self.c_nonce_expires_in.replace(C_NONCE_EXPIRES_IN);
+        self
+    }
+
+    pub(crate) fn error(&self) -> ErrorCode {
+        self.error
+    }
+}
+
+impl IntoResponse for ErrorResponse {
+    fn into_response(self) -> Response {
+        let status_code = self.status_code;
+
+        // An invalid Access Token must be reported in the `WWW-Authenticate` header as well (RFC 6750, section 3). The
+        // same applies to an invalid DPoP proof at one of the protected endpoints (RFC 9449, section 7.1).
+        let www_authenticate = match (self.error, status_code) {
+            (ErrorCode::InvalidToken, _) => Some(r#"Bearer error="invalid_token""#),
+            (ErrorCode::InvalidDpopProof, StatusCode::UNAUTHORIZED) => Some(r#"DPoP error="invalid_dpop_proof""#),
+            _ => None,
+        };
+
+        if let Some(www_authenticate) = www_authenticate {
+            return (
+                status_code,
+                [
+                    (header::CACHE_CONTROL, "no-store"),
+                    (header::WWW_AUTHENTICATE, www_authenticate),
+                ],
+                Json(self),
+            )
+                .into_response();
+        }
+
+        (status_code, [(header::CACHE_CONTROL, "no-store")], Json(self)).into_response()
+    }
+}
+
+impl From<OfferError> for ErrorResponse {
+    fn from(error: OfferError) -> Self {
+        use OfferError::*;
+
+        let (status_code, error_code) = match &error {
+            PreAuthorizedCodeExpiredError
+            | PreAuthorizedCodeAlreadyRedeemedError
+            | TransactionCodeAttemptsExceededError
+            | InvalidTransactionCodeError
+            | InvalidRefreshTokenError
+            | RefreshTokenExpiredError
+            | MissingAuthorizationCodeError
+            | InvalidAuthorizationCodeError
+            | AuthorizationCodeAlreadyRedeemedError
+            | OfferAlreadyRedeemedError
+            | RedirectUriMismatchError
+            | InvalidCodeVerifierError
+            | OfferExpiredError
+            | OfferCancelledError => (StatusCode::BAD_REQUEST, ErrorCode::InvalidGrant),
+            RevokedAccessTokenError => (StatusCode::UNAUTHORIZED, ErrorCode::InvalidToken),
+            DpopKeyMismatchError => (StatusCode::BAD_REQUEST, ErrorCode::InvalidDpopProof),
+            MissingTransactionCodeError | MissingCodeVerifierError | InvalidTransactionCodeLengthError => {
+                (StatusCode::BAD_REQUEST, ErrorCode::InvalidRequest)
+            }
+            MissingProofError | InvalidProofError(_) | MissingProofIssuerError => {
+                (StatusCode::BAD_REQUEST, ErrorCode::InvalidProof)
+            }
+            MissingNonceError | InvalidNonceError | ExpiredNonceError => {
+                (StatusCode::BAD_REQUEST, ErrorCode::InvalidNonce)
+            }
+            CredentialConfigurationNotFoundError(_) => (StatusCode::BAD_REQUEST, ErrorCode::UnsupportedCredentialType),
+            MissingCredentialError => (StatusCode::BAD_REQUEST, ErrorCode::InvalidCredentialRequest),
+            InvalidTransactionIdError => (StatusCode::BAD_REQUEST, ErrorCode::InvalidTransactionId),
+            InvalidNotificationIdError => (StatusCode::BAD_REQUEST, ErrorCode::InvalidNotificationId),
+            MissingCredentialOfferError | SendCredentialOfferError(_) | OfferNotCancellableError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::ServerError)
+            }
+        };
+
+        ErrorResponse::new(status_code, error_code).with_description(error)
+    }
+}
+
+impl From<CredentialError> for ErrorResponse {
+    fn from(error: CredentialError) -> Self {
+        use CredentialError::*;
+
+        let (status_code, error_code) = match &error {
+            UnsupportedCredentialFormat | UnsupportedContextError(_) => {
+                (StatusCode::BAD_REQUEST, ErrorCode::UnsupportedCredentialFormat)
+            }
+            // The key that is bound to the `proof` cannot be used for this Credential.
+            InvalidDeviceKeyError(_) | InvalidHolderKeyError(_) => (StatusCode::BAD_REQUEST, ErrorCode::InvalidProof),
+            // The data of the Credential is provided by the Credential Issuer, so the Wallet cannot resolve these.
+            InvalidCredentialError
+            | MissingCredentialSubjectError
+            | InvalidCredentialSubjectError(_)
+            | InvalidVerifiableCredentialError(_)
+            | InvalidValidityError(_)
+            | InvalidSelectivelyDisclosableClaimError(_)
+            | CredentialSchemaViolationError(_)
+            | MissingTemplateFieldError(_)
+            | InvalidTemplateFieldError(_) => (StatusCode::BAD_REQUEST, ErrorCode::CredentialRequestDenied),
+            MissingCredentialDataError
+            | CredentialSigningError(_)
+            | CanonicalizationError(_)
+            | MissingCertificateChainError
+            | InvalidCredentialSchemaError(_)
+            | InvalidCredentialTemplateError(_) => (StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::ServerError),
+        };
+
+        ErrorResponse::new(status_code, error_code).with_description(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    async fn body(response: Response) -> Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_error_response() {
+        let response = ErrorResponse::from(OfferError::ExpiredNonceError)
+            .with_c_nonce("nonce".to_string())
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers().get(header::CACHE_CONTROL).unwrap(), "no-store");
+        assert_eq!(
+            body(response).await,
+            json!({
+                "error": "invalid_nonce",
+                "error_description": "The `c_nonce` has expired",
+                "c_nonce": "nonce",
+                "c_nonce_expires_in": C_NONCE_EXPIRES_IN
+            })
+        );
+
+        let response = ErrorResponse::new(StatusCode::UNAUTHORIZED, ErrorCode::InvalidToken).into_response();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+            r#"Bearer error="invalid_token""#
+        );
+        assert_eq!(body(response).await, json!({ "error": "invalid_token" }));
+    }
+}
@@ -1,3 +1,11 @@
+pub mod authorize;
+pub mod batch_credential;
 pub mod credential;
+pub mod credential_offer;
+pub mod deferred_credential;
+pub mod dpop;
+pub mod error;
+pub mod nonce;
+pub mod notification;
 pub mod token;
 pub mod well_known;
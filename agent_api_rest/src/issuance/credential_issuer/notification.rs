@@ -0,0 +1,207 @@
+use agent_issuance::{offer::command::OfferCommand, state::IssuanceState};
+use agent_shared::{handlers::command_handler, notification::NotificationRequest};
+use axum::{
+    extract::{rejection::JsonRejection, Json, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use cqrs_es::AggregateError;
+use serde_json::json;
+use tracing::info;
+
+use super::dpop::AccessToken;
+use super::error::{ErrorCode, ErrorResponse};
+use super::token::offer_id_from_access_token;
+
+#[axum_macros::debug_handler]
+pub(crate) async fn notification(
+    State(state): State<IssuanceState>,
+    access_token: AccessToken,
+    notification_request: Result<Json<NotificationRequest>, JsonRejection>,
+) -> Response {
+    let Json(notification_request) = match notification_request {
+        Ok(notification_request) => notification_request,
+        Err(rejection) => {
+            return ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidNotificationRequest)
+                .with_description(rejection.body_text())
+                .into_response()
+        }
+    };
+
+    info!("Request Body: {}", json!(notification_request));
+
+    let offer_id = match offer_id_from_access_token(&state, &access_token).await {
+        Ok(offer_id) => offer_id,
+        Err(response) => return response,
+    };
+
+    let command = OfferCommand::HandleNotification {
+        offer_id: offer_id.clone(),
+        notification_request,
+    };
+
+    match command_handler(&offer_id, &state.command.offer, command).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(AggregateError::UserError(error)) => ErrorResponse::from(error).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        issuance::{
+            credential_issuer::token::tests::token, credentials::tests::credentials, offers::tests::offers, router,
+        },
+        tests::BASE_URL,
+    };
+    use agent_issuance::{
+        offer::aggregate::test_utils::{notification_id, PROOF_JWT},
+        startup_commands::startup_commands,
+        state::initialize,
+    };
+    use agent_secret_manager::service::Service;
+    use agent_store::in_memory;
+    use axum::{
+        body::Body,
+        http::{self, Request},
+        Router,
+    };
+    use rstest::rstest;
+    use serde_json::Value;
+    use tower::Service as _;
+
+    async fn credential(app: &mut Router, access_token: &str) {
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/openid4vci/credential")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", access_token))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "format": "jwt_vc_json",
+                            "credential_definition": {
+                                "type": [
+                                    "VerifiableCredential",
+                                    "OpenBadgeCredential"
+                                ]
+                            },
+                            "proof": {
+                                "proof_type": "jwt",
+                                "jwt": PROOF_JWT
+                            }
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[rstest]
+    #[case::valid_notification_id(None, StatusCode::NO_CONTENT, None)]
+    #[case::invalid_notification_id(
+        Some("invalid-notification-id"),
+        StatusCode::BAD_REQUEST,
+        Some(json!({
+            "error": "invalid_notification_id",
+            "error_description": "The `notification_id` does not belong to a Credential that was issued for this Credential Offer"
+        }))
+    )]
+    #[serial_test::serial]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_notification_endpoint(
+        #[case] notification_id_override: Option<&str>,
+        #[case] expected_status_code: StatusCode,
+        #[case] expected_body: Option<Value>,
+    ) {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        credentials(&mut app).await;
+        let pre_authorized_code = offers(&mut app).await;
+        let access_token = token(&mut app, pre_authorized_code).await;
+        credential(&mut app, &access_token).await;
+
+        let notification_id = match notification_id_override {
+            Some(notification_id) => notification_id.to_string(),
+            None => notification_id().await,
+        };
+
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/openid4vci/notification")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", access_token))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "notification_id": notification_id,
+                            "event": "credential_accepted"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), expected_status_code);
+
+        if let Some(expected_body) = expected_body {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body, expected_body);
+        }
+    }
+
+    #[serial_test::serial]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_notification_endpoint_rejects_invalid_notification_request() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        credentials(&mut app).await;
+        let pre_authorized_code = offers(&mut app).await;
+        let access_token = token(&mut app, pre_authorized_code).await;
+        credential(&mut app, &access_token).await;
+
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/openid4vci/notification")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", access_token))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "notification_id": notification_id().await,
+                            "event": "credential_unknown"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "invalid_notification_request");
+    }
+}
@@ -1,51 +1,151 @@
 use agent_issuance::{
     offer::{
         command::OfferCommand,
-        queries::{pre_authorized_code::PreAuthorizedCodeView, OfferView},
+        queries::{
+            authorization_code::AuthorizationCodeView, pre_authorized_code::PreAuthorizedCodeView,
+            refresh_token::RefreshTokenView, OfferView,
+        },
     },
     state::IssuanceState,
 };
 use agent_shared::handlers::{command_handler, query_handler};
 use axum::{
-    extract::{Json, State},
-    http::StatusCode,
+    extract::{rejection::FormRejection, Json, State},
+    http::{HeaderMap, Method, StatusCode, Uri},
     response::{IntoResponse, Response},
     Form,
 };
+use cqrs_es::AggregateError;
 use oid4vci::token_request::TokenRequest;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tracing::info;
 
+use super::dpop::{dpop_jkt, invalid_dpop_proof_response, AccessToken};
+use super::error::{ErrorCode, ErrorResponse};
+
+/// The Token Request as accepted by the Token Endpoint. Next to the grant types defined by OpenID4VCI, an Access Token
+/// can be refreshed using the `refresh_token` grant type (RFC 6749, section 6).
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum TokenEndpointRequest {
+    Oid4vci(TokenRequest),
+    Refresh(RefreshTokenRequest),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "grant_type")]
+pub(crate) enum RefreshTokenRequest {
+    #[serde(rename = "refresh_token")]
+    RefreshToken { refresh_token: String },
+}
+
 #[axum_macros::debug_handler]
 pub(crate) async fn token(
     State(state): State<IssuanceState>,
-    Form(token_request): Form<TokenRequest>,
-    // TODO: implement official oid4vci error response. This TODO is also in the `credential` endpoint.
+    uri: Uri,
+    headers: HeaderMap,
+    token_request: Result<Form<TokenEndpointRequest>, FormRejection>,
 ) -> Response {
+    let Form(token_request) = match token_request {
+        Ok(token_request) => token_request,
+        Err(rejection) => {
+            return ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidRequest)
+                .with_description(rejection.body_text())
+                .into_response()
+        }
+    };
+
     info!("Request Body: {}", json!(token_request));
 
-    // Get the `pre_authorized_code` from the `TokenRequest`.
-    let pre_authorized_code = match &token_request {
-        TokenRequest::PreAuthorizedCode {
-            pre_authorized_code, ..
-        } => pre_authorized_code,
-        _ => return StatusCode::BAD_REQUEST.into_response(),
+    // When the Token Request contains a valid `DPoP` proof, the Access Token is bound to its key.
+    let dpop_jkt = match dpop_jkt(&state, &headers, &Method::POST, &uri, None) {
+        Ok(dpop_jkt) => dpop_jkt,
+        Err(error) => {
+            return ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidDpopProof)
+                .with_description(error)
+                .into_response()
+        }
     };
 
-    // Use the `pre_authorized_code` to get the `offer_id` from the `PreAuthorizedCodeView`.
-    let offer_id = match query_handler(pre_authorized_code, &state.query.pre_authorized_code).await {
-        Ok(Some(PreAuthorizedCodeView { offer_id })) => offer_id,
-        _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
+    let (offer_id, command) = match token_request {
+        TokenEndpointRequest::Oid4vci(token_request) => {
+            let offer_id = match &token_request {
+                // Use the `pre_authorized_code` to get the `offer_id` from the `PreAuthorizedCodeView`.
+                TokenRequest::PreAuthorizedCode {
+                    pre_authorized_code, ..
+                } => match query_handler(pre_authorized_code, &state.query.pre_authorized_code).await {
+                    Ok(Some(pre_authorized_code_view)) if pre_authorized_code_view.is_unusable() => {
+                        return invalid_grant_response(
+                            "The Pre-Authorized Code has expired or has already been redeemed",
+                        )
+                    }
+                    Ok(Some(PreAuthorizedCodeView { offer_id, .. })) => offer_id,
+                    Ok(None) => return invalid_grant_response("Unknown Pre-Authorized Code"),
+                    Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+                },
+                // Use the `code` to get the `offer_id` from the `AuthorizationCodeView`.
+                TokenRequest::AuthorizationCode { code, .. } => {
+                    match query_handler(code, &state.query.authorization_code).await {
+                        Ok(Some(AuthorizationCodeView { redeemed: true, .. })) => {
+                            return invalid_grant_response("The Authorization Code has already been redeemed")
+                        }
+                        Ok(Some(AuthorizationCodeView { offer_id, .. })) => offer_id,
+                        Ok(None) => return invalid_grant_response("Unknown Authorization Code"),
+                        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+                    }
+                }
+            };
 
-    let command = OfferCommand::CreateTokenResponse {
-        offer_id: offer_id.clone(),
-        token_request,
+            (
+                offer_id.clone(),
+                OfferCommand::CreateTokenResponse {
+                    offer_id,
+                    token_request,
+                    dpop_jkt,
+                },
+            )
+        }
+        // Use the `refresh_token` to get the `offer_id` from the `RefreshTokenView`.
+        TokenEndpointRequest::Refresh(RefreshTokenRequest::RefreshToken { refresh_token }) => {
+            match query_handler(&refresh_token, &state.query.refresh_token).await {
+                Ok(Some(RefreshTokenView { offer_id })) => (
+                    offer_id.clone(),
+                    OfferCommand::RefreshAccessToken {
+                        offer_id,
+                        refresh_token,
+                        dpop_jkt,
+                    },
+                ),
+                Ok(None) => return invalid_grant_response("Unknown Refresh Token"),
+                Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            }
+        }
     };
 
+    // Every attempt to provide a Transaction Code is registered before it is verified, so that the number of attempts is
+    // limited even for concurrent Token Requests.
+    if let OfferCommand::CreateTokenResponse {
+        token_request: TokenRequest::PreAuthorizedCode { tx_code: Some(_), .. },
+        ..
+    } = &command
+    {
+        let register_attempt = OfferCommand::RegisterTransactionCodeAttempt {
+            offer_id: offer_id.clone(),
+        };
+
+        match command_handler(&offer_id, &state.command.offer, register_attempt).await {
+            Ok(_) => {}
+            Err(AggregateError::UserError(error)) => return ErrorResponse::from(error).into_response(),
+            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        };
+    }
+
     // Create a `TokenResponse` using the `offer_id` and `token_request`.
-    if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    match command_handler(&offer_id, &state.command.offer, command).await {
+        Ok(_) => {}
+        Err(AggregateError::UserError(error)) => return ErrorResponse::from(error).into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     };
 
     // Use the `offer_id` to get the `token_response` from the `OfferView`.
@@ -58,15 +158,71 @@ pub(crate) async fn token(
     }
 }
 
+/// Returned when the grant in the Token Request cannot be used to obtain an Access Token.
+fn invalid_grant_response(error_description: &str) -> Response {
+    ErrorResponse::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidGrant)
+        .with_description(error_description)
+        .into_response()
+}
+
+/// Uses the `access_token` to get the `offer_id` from the `AccessTokenView`. Unknown and expired Access Tokens are
+/// rejected with a `401 Unauthorized` response, just like Access Tokens that are used without the `DPoP` proof of the
+/// key they are bound to.
+pub(crate) async fn offer_id_from_access_token(
+    state: &IssuanceState,
+    AccessToken { access_token, dpop_jkt }: &AccessToken,
+) -> Result<String, Response> {
+    match query_handler(access_token, &state.query.access_token).await {
+        Ok(Some(access_token_view)) if access_token_view.is_expired() => {
+            Err(ErrorResponse::new(StatusCode::UNAUTHORIZED, ErrorCode::InvalidToken)
+                .with_description("The Access Token has expired")
+                .into_response())
+        }
+        Ok(Some(access_token_view)) if access_token_view.revoked => {
+            Err(ErrorResponse::new(StatusCode::UNAUTHORIZED, ErrorCode::InvalidToken)
+                .with_description("The Access Token has been revoked")
+                .into_response())
+        }
+        Ok(Some(access_token_view)) => match (&access_token_view.dpop_jkt, dpop_jkt) {
+            (None, None) => Ok(access_token_view.offer_id),
+            (Some(bound_jkt), Some(dpop_jkt)) if bound_jkt == dpop_jkt => Ok(access_token_view.offer_id),
+            (Some(_), Some(_)) => Err(invalid_dpop_proof_response(
+                "The DPoP proof is not signed by the key the Access Token is bound to",
+            )),
+            (Some(_), None) => Err(ErrorResponse::new(StatusCode::UNAUTHORIZED, ErrorCode::InvalidToken)
+                .with_description("The Access Token is bound to a key and must be used with the `DPoP` scheme")
+                .into_response()),
+            (None, Some(_)) => Err(ErrorResponse::new(StatusCode::UNAUTHORIZED, ErrorCode::InvalidToken)
+                .with_description("The Access Token is not bound to a key and must be used with the `Bearer` scheme")
+                .into_response()),
+        },
+        Ok(None) => Err(ErrorResponse::new(StatusCode::UNAUTHORIZED, ErrorCode::InvalidToken).into_response()),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use crate::{
-        issuance::{credentials::tests::credentials, offers::tests::offers, router},
-        tests::BASE_URL,
+        issuance::{
+            credentials::tests::credentials,
+            offers::tests::{cancel, offers, offers_with_tx_code},
+            router,
+        },
+        tests::{BASE_URL, OFFER_ID},
+    };
+    use agent_issuance::{
+        offer::aggregate::{test_utils::SUBJECT_KEY_DID, DEFAULT_ACCESS_TOKEN_EXPIRES_IN},
+        startup_commands::startup_commands,
+        state::initialize,
     };
-    use agent_issuance::{startup_commands::startup_commands, state::initialize};
     use agent_secret_manager::service::Service;
+    use agent_shared::{
+        config::{config, get_preferred_did_method, get_preferred_signing_algorithm, set_config},
+        dpop::{create_proof, DPOP},
+        UrlAppendHelpers,
+    };
     use agent_store::in_memory;
     use axum::{
         body::Body,
@@ -118,4 +274,386 @@ pub mod tests {
 
         let _access_token = token(&mut app, pre_authorized_code).await;
     }
+
+    async fn token_request(app: &mut Router, body: String) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/auth/token")
+                    .header(
+                        http::header::CONTENT_TYPE,
+                        mime::APPLICATION_WWW_FORM_URLENCODED.as_ref(),
+                    )
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status_code = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status_code, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_token_endpoint_with_redeemed_pre_authorized_code() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        credentials(&mut app).await;
+        let pre_authorized_code = offers(&mut app).await;
+
+        let _access_token = token(&mut app, pre_authorized_code.clone()).await;
+
+        // The Pre-Authorized Code can only be redeemed once.
+        let (status_code, body) = token_request(
+            &mut app,
+            format!(
+                "grant_type=urn:ietf:params:oauth:grant-type:pre-authorized_code&pre-authorized_code={}",
+                pre_authorized_code
+            ),
+        )
+        .await;
+
+        assert_eq!(status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            json!({
+                "error": "invalid_grant",
+                "error_description": "The Pre-Authorized Code has expired or has already been redeemed"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_token_endpoint_with_cancelled_offer() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        credentials(&mut app).await;
+        let pre_authorized_code = offers(&mut app).await;
+
+        assert_eq!(cancel(&mut app, OFFER_ID).await, StatusCode::NO_CONTENT);
+
+        let (status_code, body) = token_request(
+            &mut app,
+            format!(
+                "grant_type=urn:ietf:params:oauth:grant-type:pre-authorized_code&pre-authorized_code={}",
+                pre_authorized_code
+            ),
+        )
+        .await;
+
+        assert_eq!(status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            json!({
+                "error": "invalid_grant",
+                "error_description": "The Credential Offer has been cancelled"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_token_endpoint_with_invalid_token_request() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        // An unknown Pre-Authorized Code is rejected.
+        let (status_code, body) = token_request(
+            &mut app,
+            "grant_type=urn:ietf:params:oauth:grant-type:pre-authorized_code&pre-authorized_code=unknown".to_string(),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "invalid_grant");
+
+        // A malformed Token Request is rejected.
+        let (status_code, body) = token_request(&mut app, "grant_type=password".to_string()).await;
+        assert_eq!(status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "invalid_request");
+    }
+
+    #[tokio::test]
+    async fn test_token_endpoint_with_refresh_token() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        credentials(&mut app).await;
+        let pre_authorized_code = offers(&mut app).await;
+
+        let (status_code, body) = token_request(
+            &mut app,
+            format!(
+                "grant_type=urn:ietf:params:oauth:grant-type:pre-authorized_code&pre-authorized_code={}",
+                pre_authorized_code
+            ),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::OK);
+
+        let token_response: TokenResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(token_response.expires_in, Some(DEFAULT_ACCESS_TOKEN_EXPIRES_IN));
+        let refresh_token = token_response.refresh_token.unwrap();
+
+        // A new Access Token can be obtained using the Refresh Token.
+        let (status_code, body) = token_request(
+            &mut app,
+            format!("grant_type=refresh_token&refresh_token={}", refresh_token),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::OK);
+
+        let token_response: TokenResponse = serde_json::from_value(body).unwrap();
+        assert!(token_response.refresh_token.is_some());
+
+        // An unknown Refresh Token is rejected.
+        let (status_code, body) =
+            token_request(&mut app, "grant_type=refresh_token&refresh_token=invalid".to_string()).await;
+        assert_eq!(status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            json!({
+                "error": "invalid_grant",
+                "error_description": "Unknown Refresh Token"
+            })
+        );
+    }
+
+    async fn token_request_with_tx_code(app: &mut Router, pre_authorized_code: &str, tx_code: &str) -> StatusCode {
+        app.call(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/auth/token")
+                .header(
+                    http::header::CONTENT_TYPE,
+                    mime::APPLICATION_WWW_FORM_URLENCODED.as_ref(),
+                )
+                .body(Body::from(format!(
+                    "grant_type=urn:ietf:params:oauth:grant-type:pre-authorized_code&pre-authorized_code={}&tx_code={}",
+                    pre_authorized_code, tx_code
+                )))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+    }
+
+    #[tokio::test]
+    async fn test_token_endpoint_with_tx_code() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        credentials(&mut app).await;
+        let (pre_authorized_code, tx_code) = offers_with_tx_code(&mut app).await;
+
+        // An invalid Transaction Code is rejected.
+        assert_eq!(
+            token_request_with_tx_code(&mut app, &pre_authorized_code, "invalid").await,
+            StatusCode::BAD_REQUEST
+        );
+
+        // The valid Transaction Code is accepted.
+        assert_eq!(
+            token_request_with_tx_code(&mut app, &pre_authorized_code, &tx_code).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_token_endpoint_with_tx_code_attempts_exceeded() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        credentials(&mut app).await;
+        let (pre_authorized_code, tx_code) = offers_with_tx_code(&mut app).await;
+
+        for _ in 0..agent_issuance::offer::aggregate::MAX_TRANSACTION_CODE_ATTEMPTS {
+            assert_eq!(
+                token_request_with_tx_code(&mut app, &pre_authorized_code, "invalid").await,
+                StatusCode::BAD_REQUEST
+            );
+        }
+
+        // After too many failed attempts, even the valid Transaction Code is rejected.
+        assert_eq!(
+            token_request_with_tx_code(&mut app, &pre_authorized_code, &tx_code).await,
+            StatusCode::BAD_REQUEST
+        );
+
+        // Creating the Credential Offer again does not reset the number of attempts.
+        let (pre_authorized_code, tx_code) = offers_with_tx_code(&mut app).await;
+
+        assert_eq!(
+            token_request_with_tx_code(&mut app, &pre_authorized_code, &tx_code).await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    /// Creates a `DPoP` proof of the holder for a request to `path`, relative to the public URL of the Credential Issuer.
+    async fn dpop_proof(path: &str, access_token: Option<&str>) -> String {
+        create_proof(
+            SUBJECT_KEY_DID.clone(),
+            &get_preferred_did_method().to_string(),
+            get_preferred_signing_algorithm(),
+            "POST",
+            &config().url.parse::<url::Url>().unwrap().append_path_segment(path),
+            access_token,
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Sends a request with the `DPoP` `proof` of the holder and returns the response.
+    async fn dpop_request(
+        app: &mut Router,
+        path: &str,
+        authorization: Option<String>,
+        proof: String,
+        body: String,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder()
+            .method(http::Method::POST)
+            .uri(path)
+            .header(
+                http::header::CONTENT_TYPE,
+                mime::APPLICATION_WWW_FORM_URLENCODED.as_ref(),
+            )
+            .header(DPOP, proof);
+        if let Some(authorization) = authorization {
+            request = request.header(http::header::AUTHORIZATION, authorization);
+        }
+
+        let response = app.call(request.body(Body::from(body)).unwrap()).await.unwrap();
+
+        let status_code = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status_code, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_token_endpoint_with_dpop() {
+        set_config().dpop_enabled = true;
+
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        let response = app
+            .call(
+                Request::builder()
+                    .uri("/.well-known/oauth-authorization-server")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let authorization_server_metadata: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            authorization_server_metadata["dpop_signing_alg_values_supported"],
+            json!(["EdDSA"])
+        );
+
+        credentials(&mut app).await;
+        let pre_authorized_code = offers(&mut app).await;
+
+        // The Access Token is bound to the key of the `DPoP` proof.
+        let (status_code, body) = dpop_request(
+            &mut app,
+            "/auth/token",
+            None,
+            dpop_proof("/auth/token", None).await,
+            format!(
+                "grant_type=urn:ietf:params:oauth:grant-type:pre-authorized_code&pre-authorized_code={}",
+                pre_authorized_code
+            ),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::OK);
+
+        let token_response: TokenResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(token_response.token_type, "DPoP");
+        let access_token = token_response.access_token;
+
+        // A bound Access Token cannot be used as a Bearer token.
+        let (status_code, body) = dpop_request(
+            &mut app,
+            "/openid4vci/nonce",
+            Some(format!("Bearer {access_token}")),
+            dpop_proof("/openid4vci/nonce", Some(&access_token)).await,
+            String::new(),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "invalid_token");
+
+        // The `DPoP` proof must contain the hash of the Access Token.
+        let (status_code, body) = dpop_request(
+            &mut app,
+            "/openid4vci/nonce",
+            Some(format!("DPoP {access_token}")),
+            dpop_proof("/openid4vci/nonce", None).await,
+            String::new(),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "invalid_dpop_proof");
+
+        let proof = dpop_proof("/openid4vci/nonce", Some(&access_token)).await;
+        let (status_code, body) = dpop_request(
+            &mut app,
+            "/openid4vci/nonce",
+            Some(format!("DPoP {access_token}")),
+            proof.clone(),
+            String::new(),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::OK);
+        assert!(body["c_nonce"].is_string());
+
+        // A `DPoP` proof cannot be replayed.
+        let (status_code, body) = dpop_request(
+            &mut app,
+            "/openid4vci/nonce",
+            Some(format!("DPoP {access_token}")),
+            proof,
+            String::new(),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "invalid_dpop_proof");
+
+        // The `htu` of the `DPoP` proof must be the public URL of the request.
+        let (status_code, body) = dpop_request(
+            &mut app,
+            "/openid4vci/nonce",
+            Some(format!("DPoP {access_token}")),
+            dpop_proof("/acme/openid4vci/nonce", Some(&access_token)).await,
+            String::new(),
+        )
+        .await;
+        assert_eq!(status_code, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "invalid_dpop_proof");
+
+        set_config().dpop_enabled = false;
+    }
 }
@@ -2,12 +2,16 @@ use agent_issuance::{
     server_config::queries::ServerConfigView,
     state::{IssuanceState, SERVER_CONFIG_ID},
 };
-use agent_shared::handlers::query_handler;
+use agent_shared::{
+    config::{config, get_all_enabled_signing_algorithms},
+    handlers::query_handler,
+};
 use axum::{
     extract::{Json, State},
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use serde_json::json;
 
 #[axum_macros::debug_handler]
 pub(crate) async fn oauth_authorization_server(State(state): State<IssuanceState>) -> Response {
@@ -15,7 +19,15 @@ pub(crate) async fn oauth_authorization_server(State(state): State<IssuanceState
         Ok(Some(ServerConfigView {
             authorization_server_metadata,
             ..
-        })) => (StatusCode::OK, Json(authorization_server_metadata)).into_response(),
+        })) => {
+            let mut authorization_server_metadata = json!(authorization_server_metadata);
+            if config().dpop_enabled {
+                authorization_server_metadata["dpop_signing_alg_values_supported"] =
+                    json!(get_all_enabled_signing_algorithms());
+            }
+
+            (StatusCode::OK, Json(authorization_server_metadata)).into_response()
+        }
         Ok(None) => StatusCode::NOT_FOUND.into_response(),
         _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     }
@@ -59,7 +71,10 @@ mod tests {
             authorization_server_metadata,
             AuthorizationServerMetadata {
                 issuer: "https://example.com/".parse().unwrap(),
+                authorization_endpoint: Some("https://example.com/auth/authorize".parse().unwrap()),
                 token_endpoint: Some("https://example.com/auth/token".parse().unwrap()),
+                response_types_supported: Some(vec!["code".to_string()]),
+                code_challenge_methods_supported: Some(vec!["S256".to_string()]),
                 ..Default::default()
             }
         );
@@ -68,6 +83,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[serial_test::serial]
     async fn test_oauth_authorization_server_endpoint() {
         let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
         initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
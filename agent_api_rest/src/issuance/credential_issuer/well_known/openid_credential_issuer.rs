@@ -8,14 +8,23 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use serde_json::json;
 
 #[axum_macros::debug_handler]
 pub(crate) async fn openid_credential_issuer(State(state): State<IssuanceState>) -> Response {
     match query_handler(SERVER_CONFIG_ID, &state.query.server_config).await {
         Ok(Some(ServerConfigView {
             credential_issuer_metadata: Some(credential_issuer_metadata),
+            signed_metadata,
             ..
-        })) => (StatusCode::OK, Json(credential_issuer_metadata)).into_response(),
+        })) => {
+            let mut credential_issuer_metadata = json!(credential_issuer_metadata);
+            if let Some(signed_metadata) = signed_metadata {
+                credential_issuer_metadata["signed_metadata"] = json!(signed_metadata);
+            }
+
+            (StatusCode::OK, Json(credential_issuer_metadata)).into_response()
+        }
         Ok(None) => StatusCode::NOT_FOUND.into_response(),
         _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     }
@@ -34,6 +43,7 @@ mod tests {
         http::{self, Request},
         Router,
     };
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
     use jsonwebtoken::Algorithm;
     use oid4vci::{
         credential_format_profiles::{
@@ -46,7 +56,6 @@ mod tests {
         proof::KeyProofMetadata,
         ProofType,
     };
-    use serde_json::json;
     use std::collections::HashMap;
     use tower::Service as _;
 
@@ -67,13 +76,33 @@ mod tests {
         assert_eq!(response.headers().get("Content-Type").unwrap(), "application/json");
 
         let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
-        let credential_issuer_metadata: CredentialIssuerMetadata = serde_json::from_slice(&body).unwrap();
+        let mut credential_issuer_metadata: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        // The `signed_metadata` contains the Credential Issuer Metadata as the claims of a JWT.
+        let signed_metadata = credential_issuer_metadata
+            .as_object_mut()
+            .unwrap()
+            .remove("signed_metadata")
+            .unwrap();
+        let payload = signed_metadata.as_str().unwrap().split('.').nth(1).unwrap();
+        let claims: serde_json::Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap();
+        assert_eq!(claims["sub"], json!(BASE_URL.clone()));
+        assert_eq!(
+            claims["credential_configurations_supported"],
+            credential_issuer_metadata["credential_configurations_supported"]
+        );
+
+        let credential_issuer_metadata: CredentialIssuerMetadata =
+            serde_json::from_value(credential_issuer_metadata).unwrap();
 
         assert_eq!(
             credential_issuer_metadata,
             CredentialIssuerMetadata {
                 credential_issuer: BASE_URL.clone(),
                 credential_endpoint: BASE_URL.append_path_segment("openid4vci/credential"),
+                batch_credential_endpoint: Some(BASE_URL.append_path_segment("openid4vci/batch_credential")),
+                deferred_credential_endpoint: Some(BASE_URL.append_path_segment("openid4vci/deferred_credential")),
+                notification_endpoint: Some(BASE_URL.append_path_segment("openid4vci/notification")),
                 credential_configurations_supported: vec![(
                     "badge".to_string(),
                     CredentialConfigurationsSupportedObject {
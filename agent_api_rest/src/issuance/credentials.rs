@@ -1,9 +1,17 @@
 use crate::API_VERSION;
 use agent_issuance::{
-    credential::{command::CredentialCommand, entity::Data, queries::CredentialView},
+    credential::{
+        command::CredentialCommand,
+        entity::{Data, Validity},
+        error::CredentialError,
+        queries::CredentialView,
+    },
     offer::command::OfferCommand,
     server_config::queries::ServerConfigView,
-    state::{IssuanceState, SERVER_CONFIG_ID},
+    state::{IssuanceState, SERVER_CONFIG_ID, STATUS_LIST_ID},
+    status_list::{
+        aggregate::StatusPurpose, command::StatusListCommand, error::StatusListError, queries::StatusListView,
+    },
 };
 use agent_shared::handlers::{command_handler, query_handler};
 use axum::{
@@ -11,7 +19,9 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use cqrs_es::AggregateError;
 use hyper::header;
+use oid4vci::credential_format_profiles::CredentialFormats;
 use oid4vci::credential_issuer::credential_issuer_metadata::CredentialIssuerMetadata;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -40,6 +50,15 @@ pub struct CredentialsEndpointRequest {
     #[serde(default)]
     pub is_signed: bool,
     pub credential_configuration_id: String,
+    /// Overrides the moment from which the Credential is valid (RFC 3339).
+    #[serde(default)]
+    pub valid_from: Option<String>,
+    /// Overrides the moment until which the Credential is valid (RFC 3339).
+    #[serde(default)]
+    pub valid_until: Option<String>,
+    /// The id of the Credential Template that maps the `credential` (a flat input record) onto the Credential.
+    #[serde(default)]
+    pub template_id: Option<String>,
 }
 
 #[axum_macros::debug_handler]
@@ -54,6 +73,9 @@ pub(crate) async fn credentials(
         credential: data,
         is_signed,
         credential_configuration_id,
+        valid_from,
+        valid_until,
+        template_id,
     }) = serde_json::from_value(payload)
     else {
         return (StatusCode::BAD_REQUEST, "invalid payload").into_response();
@@ -65,28 +87,105 @@ pub(crate) async fn credentials(
 
     let credential_id = uuid::Uuid::new_v4().to_string();
 
-    let credential_configuration = match query_handler(SERVER_CONFIG_ID, &state.query.server_config).await {
-        Ok(Some(ServerConfigView {
-            credential_issuer_metadata:
-                Some(CredentialIssuerMetadata {
-                    credential_configurations_supported,
-                    ..
-                }),
-            ..
-        })) => {
-            if let Some(credential_configuration) =
-                credential_configurations_supported.get(&credential_configuration_id)
+    let (credential_configuration, selectively_disclosable_claims, validity_period, credential_schema, templates) =
+        match query_handler(SERVER_CONFIG_ID, &state.query.server_config).await {
+            Ok(Some(ServerConfigView {
+                credential_issuer_metadata:
+                    Some(CredentialIssuerMetadata {
+                        credential_configurations_supported,
+                        ..
+                    }),
+                mut credential_configurations,
+                ..
+            })) => {
+                if let Some(credential_configuration) =
+                    credential_configurations_supported.get(&credential_configuration_id)
+                {
+                    // Get the claims that need to be selectively disclosable, the default validity period, the JSON
+                    // Schema and the templates from the Credential Configuration.
+                    let (selectively_disclosable_claims, validity_period, credential_schema, templates) =
+                        credential_configurations
+                            .remove(&credential_configuration_id)
+                            .map(|credential_configuration| {
+                                (
+                                    credential_configuration.selectively_disclosable_claims,
+                                    credential_configuration.validity_period,
+                                    credential_configuration.credential_schema,
+                                    credential_configuration.templates,
+                                )
+                            })
+                            .unwrap_or_default();
+
+                    (
+                        credential_configuration.clone(),
+                        selectively_disclosable_claims,
+                        validity_period,
+                        credential_schema,
+                        templates,
+                    )
+                } else {
+                    return (
+                        StatusCode::NOT_FOUND,
+                        format!("No Credential Configuration found with id: `{credential_configuration_id}`"),
+                    )
+                        .into_response();
+                }
+            }
+            _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        };
+
+    let credential_template = match template_id {
+        Some(template_id) if !is_signed => {
+            match templates
+                .into_iter()
+                .find(|template| template.template_id == template_id)
             {
-                credential_configuration.clone()
-            } else {
-                return (
-                    StatusCode::NOT_FOUND,
-                    format!("No Credential Configuration found with id: `{credential_configuration_id}`"),
-                )
-                    .into_response();
+                Some(template) => Some(template),
+                None => {
+                    return (
+                        StatusCode::NOT_FOUND,
+                        format!("No Credential Template found with id: `{template_id}`"),
+                    )
+                        .into_response()
+                }
             }
         }
-        _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        Some(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                "a signed credential cannot be created from a template",
+            )
+                .into_response()
+        }
+        None => None,
+    };
+
+    // Allocate a status list index so that the credential can be revoked or suspended later on. As of now, only
+    // `jwt_vc_json` credentials contain a `credentialStatus`.
+    let status_list_index = if !is_signed
+        && matches!(
+            credential_configuration.credential_format,
+            CredentialFormats::JwtVcJson(_)
+        ) {
+        let command = StatusListCommand::AllocateStatusListIndex {
+            credential_id: credential_id.clone(),
+        };
+
+        if command_handler(STATUS_LIST_ID, &state.command.status_list, command)
+            .await
+            .is_err()
+        {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+
+        match query_handler(STATUS_LIST_ID, &state.query.status_list).await {
+            Ok(Some(StatusListView {
+                status_list_indices, ..
+            })) => status_list_indices.get(&credential_id).copied(),
+            _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    } else {
+        None
     };
 
     let command = if is_signed {
@@ -95,17 +194,55 @@ pub(crate) async fn credentials(
         }
     } else {
         CredentialCommand::CreateUnsignedCredential {
+            credential_id: credential_id.clone(),
             data: Data { raw: data },
             credential_configuration,
+            selectively_disclosable_claims,
+            status_list_index,
+            validity: Validity {
+                valid_from,
+                valid_until,
+                validity_period,
+            },
+            credential_schema,
+            credential_template,
         }
     };
 
     // Create an unsigned/signed credential.
-    if command_handler(&credential_id, &state.command.credential, command)
-        .await
-        .is_err()
-    {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let result = command_handler(&credential_id, &state.command.credential, command).await;
+
+    // The status list index is released again when the credential could not be created, so that it does not leak.
+    if result.is_err() && status_list_index.is_some() {
+        let command = StatusListCommand::ReleaseStatusListIndex {
+            credential_id: credential_id.clone(),
+        };
+
+        if command_handler(STATUS_LIST_ID, &state.command.status_list, command)
+            .await
+            .is_err()
+        {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    match result {
+        Ok(_) => {}
+        // Report every field of the `credentialSubject` that does not conform to the JSON Schema.
+        Err(AggregateError::UserError(CredentialError::CredentialSchemaViolationError(violations))) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({
+                    "error": "The `credentialSubject` does not conform to the JSON Schema",
+                    "violations": violations,
+                })),
+            )
+                .into_response();
+        }
+        Err(AggregateError::UserError(err)) => {
+            return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+        }
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     }
 
     // Get the `CredentialIssuerMetadata` from the `ServerConfigView`.
@@ -127,6 +264,7 @@ pub(crate) async fn credentials(
                 OfferCommand::CreateCredentialOffer {
                     offer_id: offer_id.clone(),
                     credential_issuer_metadata,
+                    credential_configuration_ids: vec![credential_configuration_id.clone()],
                 },
             )
             .await
@@ -140,6 +278,7 @@ pub(crate) async fn credentials(
     let command = OfferCommand::AddCredentials {
         offer_id: offer_id.clone(),
         credential_ids: vec![credential_id.clone()],
+        credential_configuration_id,
     };
 
     // Add the credential to the offer.
@@ -162,6 +301,39 @@ pub(crate) async fn credentials(
     }
 }
 
+#[axum_macros::debug_handler]
+pub(crate) async fn revoke(State(state): State<IssuanceState>, Path(credential_id): Path<String>) -> Response {
+    update_credential_status(state, credential_id, StatusPurpose::Revocation).await
+}
+
+#[axum_macros::debug_handler]
+pub(crate) async fn suspend(State(state): State<IssuanceState>, Path(credential_id): Path<String>) -> Response {
+    update_credential_status(state, credential_id, StatusPurpose::Suspension).await
+}
+
+/// Sets the status of the credential in the status list with the given `status_purpose`, which will republish the
+/// corresponding status list credential.
+async fn update_credential_status(
+    state: IssuanceState,
+    credential_id: String,
+    status_purpose: StatusPurpose,
+) -> Response {
+    let command = StatusListCommand::UpdateCredentialStatus {
+        credential_id,
+        status_purpose,
+        status: true,
+    };
+
+    match command_handler(STATUS_LIST_ID, &state.command.status_list, command).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(AggregateError::UserError(err @ StatusListError::UnknownCredentialError(_))) => {
+            (StatusCode::NOT_FOUND, err.to_string()).into_response()
+        }
+        Err(AggregateError::UserError(err)) => (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
 #[axum_macros::debug_handler]
 pub(crate) async fn all_credentials(State(state): State<IssuanceState>) -> Response {
     match query_handler("all_credentials", &state.query.all_credentials).await {
@@ -177,7 +349,9 @@ pub mod tests {
     use crate::issuance::router;
     use crate::tests::{BASE_URL, CREDENTIAL_CONFIGURATION_ID, OFFER_ID};
     use crate::API_VERSION;
-    use agent_issuance::{startup_commands::startup_commands, state::initialize};
+    use agent_issuance::{
+        server_config::command::ServerConfigCommand, startup_commands::startup_commands, state::initialize,
+    };
     use agent_secret_manager::service::Service;
     use agent_store::in_memory;
     use axum::{
@@ -244,9 +418,14 @@ pub mod tests {
             .unwrap()
             .to_string();
 
+        // Every credential is identified by a unique `urn:uuid:` id.
+        let credential_id = get_credentials_endpoint.rsplit('/').next().unwrap();
+        let mut credential = CREDENTIAL.clone();
+        credential["id"] = json!(format!("urn:uuid:{credential_id}"));
+
         let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
         let body: Value = serde_json::from_slice(&body).unwrap();
-        assert_eq!(body, CREDENTIAL.clone());
+        assert_eq!(body, credential);
 
         let response = app
             .call(
@@ -265,7 +444,7 @@ pub mod tests {
 
         let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
         let body: Value = serde_json::from_slice(&body).unwrap();
-        assert_eq!(body, CREDENTIAL.clone());
+        assert_eq!(body, credential);
     }
 
     #[tokio::test]
@@ -277,4 +456,162 @@ pub mod tests {
         let mut app = router(issuance_state);
         credentials(&mut app).await;
     }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_credentials_endpoint_with_credential_schema_violation() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let command = ServerConfigCommand::AddCredentialConfiguration {
+            credential_configuration: serde_json::from_value(json!({
+                "credential_configuration_id": "diploma",
+                "format": "jwt_vc_json",
+                "credential_definition": {
+                    "type": ["VerifiableCredential"]
+                },
+                "credential_schema": {
+                    "id": "https://example.com/schemas/diploma.json",
+                    "schema": {
+                        "type": "object",
+                        "properties": {
+                            "first_name": { "type": "string" },
+                            "last_name": { "type": "string" }
+                        },
+                        "required": ["first_name", "last_name"]
+                    }
+                }
+            }))
+            .unwrap(),
+        };
+        command_handler(SERVER_CONFIG_ID, &issuance_state.command.server_config, command)
+            .await
+            .unwrap();
+
+        let mut app = router(issuance_state.clone());
+
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(&format!("{API_VERSION}/credentials"))
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "offerId": OFFER_ID,
+                            "credential": {
+                                "credentialSubject": {
+                                    "first_name": 42
+                                }
+                            },
+                            "credentialConfigurationId": "diploma"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["error"],
+            "The `credentialSubject` does not conform to the JSON Schema"
+        );
+
+        let mut instance_paths: Vec<_> = body["violations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|violation| violation["instancePath"].as_str().unwrap())
+            .collect();
+        instance_paths.sort();
+        assert_eq!(instance_paths, vec!["", "/first_name"]);
+
+        // The status list index that was allocated for the credential is released again.
+        let status_list_view = query_handler(STATUS_LIST_ID, &issuance_state.query.status_list)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(status_list_view.status_list_indices.is_empty());
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_credentials_endpoint_with_credential_template() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let command = ServerConfigCommand::AddCredentialConfiguration {
+            credential_configuration: serde_json::from_value(json!({
+                "credential_configuration_id": "employee",
+                "format": "jwt_vc_json",
+                "credential_definition": {
+                    "type": ["VerifiableCredential"]
+                },
+                "templates": [{
+                    "template_id": "hr_export",
+                    "credential_subject": {
+                        "first_name": { "field": "GIVEN_NAME", "transform": "trim" },
+                        "last_name": { "field": "SURNAME" },
+                        "employment.department": { "field": "DEPT", "default": "Engineering" },
+                        "employment.employee_number": { "field": "EMP_NO", "transform": "number" }
+                    },
+                    "properties": {
+                        "name": { "value": "Employee Credential" }
+                    }
+                }]
+            }))
+            .unwrap(),
+        };
+        command_handler(SERVER_CONFIG_ID, &issuance_state.command.server_config, command)
+            .await
+            .unwrap();
+
+        let mut app = router(issuance_state);
+
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(&format!("{API_VERSION}/credentials"))
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "offerId": OFFER_ID,
+                            "credential": {
+                                "GIVEN_NAME": " Ferris ",
+                                "SURNAME": "Rustacean",
+                                "EMP_NO": "42"
+                            },
+                            "credentialConfigurationId": "employee",
+                            "templateId": "hr_export"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["name"], "Employee Credential");
+        assert_eq!(
+            body["credentialSubject"],
+            json!({
+                "first_name": "Ferris",
+                "last_name": "Rustacean",
+                "employment": {
+                    "department": "Engineering",
+                    "employee_number": 42
+                }
+            })
+        );
+    }
 }
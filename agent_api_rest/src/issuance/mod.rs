@@ -1,20 +1,28 @@
+pub mod credential_configurations;
 pub mod credential_issuer;
 pub mod credentials;
 pub mod offers;
+pub mod status_lists;
 
 use agent_issuance::state::IssuanceState;
-use axum::routing::get;
+use axum::routing::{get, put};
 use axum::{routing::post, Router};
 use credentials::all_credentials;
 use offers::all_offers;
 
 use crate::issuance::{
+    credential_configurations::{
+        credential_configurations, delete_credential_configuration, update_credential_configuration,
+    },
     credential_issuer::{
-        credential::credential, token::token, well_known::oauth_authorization_server::oauth_authorization_server,
+        authorize::authorize, batch_credential::batch_credential, credential::credential,
+        credential_offer::credential_offer, deferred_credential::deferred_credential, nonce::nonce,
+        notification::notification, token::token, well_known::oauth_authorization_server::oauth_authorization_server,
         well_known::openid_credential_issuer::openid_credential_issuer,
     },
-    credentials::{credentials, get_credentials},
-    offers::{offers, send::send},
+    credentials::{credentials, get_credentials, revoke, suspend},
+    offers::{cancel, offer_qr_code, offers, send::send},
+    status_lists::{numbered_status_lists, status_lists},
 };
 use crate::API_VERSION;
 
@@ -23,17 +31,34 @@ pub fn router(issuance_state: IssuanceState) -> Router {
         .nest(
             API_VERSION,
             Router::new()
+                .route("/credential-configurations", post(credential_configurations))
+                .route(
+                    "/credential-configurations/:credential_configuration_id",
+                    put(update_credential_configuration).delete(delete_credential_configuration),
+                )
                 .route("/credentials", post(credentials).get(all_credentials))
                 .route("/credentials/:credential_id", get(get_credentials))
+                .route("/credentials/:credential_id/revoke", post(revoke))
+                .route("/credentials/:credential_id/suspend", post(suspend))
                 .route("/offers", post(offers).get(all_offers))
-                .route("/offers/send", post(send)),
+                .route("/offers/send", post(send))
+                .route("/offers/:offer_id/qr", get(offer_qr_code))
+                .route("/offers/:offer_id/cancel", post(cancel)),
         )
         .route(
             "/.well-known/oauth-authorization-server",
             get(oauth_authorization_server),
         )
         .route("/.well-known/openid-credential-issuer", get(openid_credential_issuer))
+        .route("/auth/authorize", get(authorize))
         .route("/auth/token", post(token))
+        .route("/openid4vci/credential_offer/:reference_id", get(credential_offer))
         .route("/openid4vci/credential", post(credential))
+        .route("/openid4vci/batch_credential", post(batch_credential))
+        .route("/openid4vci/deferred_credential", post(deferred_credential))
+        .route("/openid4vci/nonce", post(nonce))
+        .route("/openid4vci/notification", post(notification))
+        .route("/status-lists/:status_purpose", get(status_lists))
+        .route("/status-lists/:status_purpose/:status_list", get(numbered_status_lists))
         .with_state(issuance_state)
 }
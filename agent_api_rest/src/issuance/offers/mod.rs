@@ -1,17 +1,20 @@
 pub mod send;
 
+use crate::qr_code::{qr_code_response, QrCodeParams};
 use agent_issuance::{
-    offer::{command::OfferCommand, queries::OfferView},
+    offer::{aggregate::generate_transaction_code, command::OfferCommand, error::OfferError, queries::OfferView},
     server_config::queries::ServerConfigView,
     state::{IssuanceState, SERVER_CONFIG_ID},
 };
 use agent_shared::handlers::{command_handler, query_handler};
 use axum::{
-    extract::{Json, State},
+    extract::{Json, Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use cqrs_es::AggregateError;
 use hyper::header;
+use oid4vci::credential_offer::TransactionCode;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde_json::Value;
@@ -21,13 +24,29 @@ use tracing::info;
 #[serde(rename_all = "camelCase")]
 pub struct OffersEndpointRequest {
     pub offer_id: String,
+    /// When present, the Wallet will be required to provide a Transaction Code in the Token Request. The generated
+    /// code is returned in the response so that it can be delivered to the holder via a separate channel.
+    pub tx_code: Option<TransactionCode>,
+    /// When `true`, the Credential Offer is passed by reference using the `credential_offer_uri` parameter.
+    #[serde(default)]
+    pub by_reference: bool,
+    /// The Credential Configurations to offer when the offer does not exist yet, e.g. because its credentials will be
+    /// provided by an external server later on. When empty, all supported Credential Configurations are offered.
+    #[serde(default)]
+    pub credential_configuration_ids: Vec<String>,
 }
 
 #[axum_macros::debug_handler]
 pub(crate) async fn offers(State(state): State<IssuanceState>, Json(payload): Json<Value>) -> Response {
     info!("Request Body: {}", payload);
 
-    let Ok(OffersEndpointRequest { offer_id }) = serde_json::from_value(payload) else {
+    let Ok(OffersEndpointRequest {
+        offer_id,
+        tx_code,
+        by_reference,
+        credential_configuration_ids,
+    }) = serde_json::from_value(payload)
+    else {
         return (StatusCode::BAD_REQUEST, "invalid payload").into_response();
     };
 
@@ -44,35 +63,86 @@ pub(crate) async fn offers(State(state): State<IssuanceState>, Json(payload): Js
     match query_handler(&offer_id, &state.query.offer).await {
         Ok(Some(_)) => {}
         _ => {
-            if command_handler(
-                &offer_id,
-                &state.command.offer,
-                OfferCommand::CreateCredentialOffer {
-                    offer_id: offer_id.clone(),
-                    credential_issuer_metadata,
-                },
-            )
-            .await
-            .is_err()
-            {
-                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            let command = OfferCommand::CreateCredentialOffer {
+                offer_id: offer_id.clone(),
+                credential_issuer_metadata,
+                credential_configuration_ids,
+            };
+
+            match command_handler(&offer_id, &state.command.offer, command).await {
+                Ok(_) => {}
+                Err(AggregateError::UserError(err)) => {
+                    return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+                }
+                Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            }
+        }
+    };
+
+    // Only the hash of the Transaction Code is stored, so it is returned in this response only.
+    let transaction_code = match tx_code {
+        Some(tx_code) => {
+            let transaction_code = match generate_transaction_code(&tx_code) {
+                Ok(transaction_code) => transaction_code,
+                Err(err) => return (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+            };
+
+            let command = OfferCommand::CreateTransactionCode {
+                offer_id: offer_id.clone(),
+                tx_code,
+                transaction_code: transaction_code.clone(),
+            };
+
+            match command_handler(&offer_id, &state.command.offer, command).await {
+                Ok(_) => {}
+                Err(AggregateError::UserError(err)) => {
+                    return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+                }
+                Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
             }
+
+            Some(transaction_code)
         }
+        None => None,
     };
 
     let command = OfferCommand::CreateFormUrlEncodedCredentialOffer {
         offer_id: offer_id.clone(),
+        by_reference,
     };
 
-    if command_handler(&offer_id, &state.command.offer, command).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    // Expired and cancelled offers can no longer be handed out to a Wallet.
+    match command_handler(&offer_id, &state.command.offer, command).await {
+        Ok(_) => {}
+        Err(AggregateError::UserError(err)) => {
+            return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+        }
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     }
 
-    match query_handler(&offer_id, &state.query.offer).await {
-        Ok(Some(OfferView {
-            form_url_encoded_credential_offer,
-            ..
-        })) => (
+    match (query_handler(&offer_id, &state.query.offer).await, transaction_code) {
+        // When a Transaction Code is required, it is returned alongside the Credential Offer.
+        (
+            Ok(Some(OfferView {
+                form_url_encoded_credential_offer,
+                ..
+            })),
+            Some(transaction_code),
+        ) => (
+            StatusCode::OK,
+            Json(json!({
+                "credentialOffer": form_url_encoded_credential_offer,
+                "txCode": transaction_code
+            })),
+        )
+            .into_response(),
+        (
+            Ok(Some(OfferView {
+                form_url_encoded_credential_offer,
+                ..
+            })),
+            None,
+        ) => (
             StatusCode::OK,
             [(header::CONTENT_TYPE, "application/x-www-form-urlencoded")],
             form_url_encoded_credential_offer,
@@ -82,10 +152,51 @@ pub(crate) async fn offers(State(state): State<IssuanceState>, Json(payload): Js
     }
 }
 
+/// Renders the form-url-encoded Credential Offer as a QR code that can be scanned by a Wallet.
+#[axum_macros::debug_handler]
+pub(crate) async fn offer_qr_code(
+    State(state): State<IssuanceState>,
+    Path(offer_id): Path<String>,
+    Query(params): Query<QrCodeParams>,
+) -> Response {
+    match query_handler(&offer_id, &state.query.offer).await {
+        // The form-url-encoded Credential Offer is only available after it has been created at the `/v0/offers`
+        // endpoint.
+        Ok(Some(OfferView {
+            form_url_encoded_credential_offer,
+            ..
+        })) if !form_url_encoded_credential_offer.is_empty() => {
+            qr_code_response(&form_url_encoded_credential_offer, &params)
+        }
+        Ok(_) => StatusCode::NOT_FOUND.into_response(),
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[axum_macros::debug_handler]
+pub(crate) async fn cancel(State(state): State<IssuanceState>, Path(offer_id): Path<String>) -> Response {
+    let command = OfferCommand::CancelOffer {
+        offer_id: offer_id.clone(),
+    };
+
+    match command_handler(&offer_id, &state.command.offer, command).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(AggregateError::UserError(err @ OfferError::MissingCredentialOfferError)) => {
+            (StatusCode::NOT_FOUND, err.to_string()).into_response()
+        }
+        Err(AggregateError::UserError(err)) => (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
 #[axum_macros::debug_handler]
 pub(crate) async fn all_offers(State(state): State<IssuanceState>) -> Response {
     match query_handler("all_offers", &state.query.all_offers).await {
-        Ok(Some(all_offers_view)) => (StatusCode::OK, Json(all_offers_view)).into_response(),
+        Ok(Some(mut all_offers_view)) => {
+            all_offers_view.offers.values_mut().for_each(OfferView::refresh_status);
+
+            (StatusCode::OK, Json(all_offers_view)).into_response()
+        }
         Ok(None) => (StatusCode::OK, Json(json!({}))).into_response(),
         _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     }
@@ -160,6 +271,99 @@ pub mod tests {
         }
     }
 
+    pub async fn offers_with_tx_code(app: &mut Router) -> (String, String) {
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(&format!("{API_VERSION}/offers"))
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "offerId": OFFER_ID,
+                            "txCode": {
+                                "input_mode": "numeric",
+                                "length": 6,
+                                "description": "Please provide the one-time code that was sent to you"
+                            }
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "application/json");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+
+        let tx_code = body["txCode"].as_str().unwrap().to_string();
+        assert_eq!(tx_code.len(), 6);
+
+        let CredentialOffer::CredentialOffer(credential_offer) =
+            CredentialOffer::from_str(body["credentialOffer"].as_str().unwrap()).unwrap()
+        else {
+            unreachable!()
+        };
+
+        let CredentialOfferParameters {
+            grants:
+                Some(Grants {
+                    pre_authorized_code:
+                        Some(PreAuthorizedCode {
+                            pre_authorized_code,
+                            tx_code: Some(_),
+                            ..
+                        }),
+                    ..
+                }),
+            ..
+        } = *credential_offer
+        else {
+            unreachable!()
+        };
+
+        (pre_authorized_code, tx_code)
+    }
+
+    pub async fn cancel(app: &mut Router, offer_id: &str) -> StatusCode {
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(&format!("{API_VERSION}/offers/{offer_id}/cancel"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        response.status()
+    }
+
+    async fn offer_status(app: &mut Router) -> Value {
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(&format!("{API_VERSION}/offers"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+
+        body[OFFER_ID]["status"].clone()
+    }
+
     #[tokio::test]
     #[tracing_test::traced_test]
     async fn test_offers_endpoint() {
@@ -171,4 +375,111 @@ pub mod tests {
         credentials(&mut app).await;
         let _pre_authorized_code = offers(&mut app).await;
     }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_offers_endpoint_with_unknown_credential_configuration_id() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(&format!("{API_VERSION}/offers"))
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "offerId": OFFER_ID,
+                            "credentialConfigurationIds": ["unknown"]
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_offer_qr_code_endpoint() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        credentials(&mut app).await;
+
+        let qr_code_request = |query: &str| {
+            Request::builder()
+                .method(http::Method::GET)
+                .uri(&format!("{API_VERSION}/offers/{OFFER_ID}/qr{query}"))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        // The Credential Offer has not been created yet.
+        let response = app.call(qr_code_request("")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let _pre_authorized_code = offers(&mut app).await;
+
+        let response = app.call(qr_code_request("")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "image/png");
+
+        let response = app
+            .call(qr_code_request("?format=svg&size=512&errorCorrection=H"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "image/svg+xml");
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_cancel_offer_endpoint() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state);
+
+        // The Credential Offer does not exist yet.
+        assert_eq!(cancel(&mut app, OFFER_ID).await, StatusCode::NOT_FOUND);
+
+        credentials(&mut app).await;
+        let _pre_authorized_code = offers(&mut app).await;
+        assert_eq!(offer_status(&mut app).await, json!("created"));
+
+        assert_eq!(cancel(&mut app, OFFER_ID).await, StatusCode::NO_CONTENT);
+        assert_eq!(offer_status(&mut app).await, json!("cancelled"));
+
+        // Cancelling is idempotent.
+        assert_eq!(cancel(&mut app, OFFER_ID).await, StatusCode::NO_CONTENT);
+
+        // A cancelled Credential Offer can no longer be handed out to a Wallet.
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(&format!("{API_VERSION}/offers"))
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "offerId": OFFER_ID
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
@@ -0,0 +1,157 @@
+use agent_issuance::{
+    state::{IssuanceState, STATUS_LIST_ID},
+    status_list::{
+        aggregate::{status_list_path, StatusPurpose},
+        queries::StatusListView,
+    },
+};
+use agent_shared::handlers::query_handler;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use hyper::header;
+
+/// Serves the signed status list credential that is referenced by the `statusListCredential` property in the
+/// `credentialStatus` of the issued credentials.
+#[axum_macros::debug_handler]
+pub(crate) async fn status_lists(
+    State(state): State<IssuanceState>,
+    Path(status_purpose): Path<StatusPurpose>,
+) -> Response {
+    status_list_credential(state, status_list_path(0, status_purpose)).await
+}
+
+/// Serves the signed status list credentials of the status lists that were started once the first one was full.
+#[axum_macros::debug_handler]
+pub(crate) async fn numbered_status_lists(
+    State(state): State<IssuanceState>,
+    Path((status_purpose, status_list)): Path<(StatusPurpose, usize)>,
+) -> Response {
+    status_list_credential(state, status_list_path(status_list, status_purpose)).await
+}
+
+async fn status_list_credential(state: IssuanceState, path: String) -> Response {
+    match query_handler(STATUS_LIST_ID, &state.query.status_list).await {
+        Ok(Some(StatusListView {
+            status_list_credentials,
+            ..
+        })) => match status_list_credentials.get(&path) {
+            Some(status_list_credential) => (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, "application/vc+jwt")],
+                status_list_credential.clone(),
+            )
+                .into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        issuance::{credentials::tests::credentials, router},
+        tests::BASE_URL,
+        API_VERSION,
+    };
+    use agent_issuance::{startup_commands::startup_commands, state::initialize};
+    use agent_secret_manager::service::Service;
+    use agent_store::in_memory;
+    use axum::{
+        body::Body,
+        http::{self, Request},
+        Router,
+    };
+    use tower::Service as _;
+
+    async fn status_list_credential(app: &mut Router, status_purpose: &str) -> String {
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!("/status-lists/{status_purpose}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "application/vc+jwt");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    async fn update_credential_status(app: &mut Router, credential_id: &str, action: &str) -> StatusCode {
+        app.call(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri(format!("{API_VERSION}/credentials/{credential_id}/{action}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_revoke_and_suspend_credential() {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(BASE_URL.clone())).await;
+
+        let mut app = router(issuance_state.clone());
+
+        // The status lists are published once the first credential is created.
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri("/status-lists/revocation")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        credentials(&mut app).await;
+
+        let credential_id = query_handler(STATUS_LIST_ID, &issuance_state.query.status_list)
+            .await
+            .unwrap()
+            .unwrap()
+            .status_list_indices
+            .into_keys()
+            .next()
+            .unwrap();
+
+        let revocation_list = status_list_credential(&mut app, "revocation").await;
+        let suspension_list = status_list_credential(&mut app, "suspension").await;
+
+        assert_eq!(
+            update_credential_status(&mut app, &credential_id, "suspend").await,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(status_list_credential(&mut app, "revocation").await, revocation_list);
+        assert_ne!(status_list_credential(&mut app, "suspension").await, suspension_list);
+
+        assert_eq!(
+            update_credential_status(&mut app, &credential_id, "revoke").await,
+            StatusCode::NO_CONTENT
+        );
+        assert_ne!(status_list_credential(&mut app, "revocation").await, revocation_list);
+
+        assert_eq!(
+            update_credential_status(&mut app, "unknown-credential", "revoke").await,
+            StatusCode::NOT_FOUND
+        );
+    }
+}
@@ -1,12 +1,25 @@
 pub mod holder;
 pub mod issuance;
+pub mod qr_code;
 pub mod verification;
 
 use agent_holder::state::HolderState;
 use agent_issuance::state::IssuanceState;
-use agent_shared::{config::config, ConfigError};
+use agent_shared::{
+    config::{config, TenantRoute},
+    ConfigError,
+};
 use agent_verification::state::VerificationState;
-use axum::{body::Bytes, extract::MatchedPath, http::Request, response::Response, Router};
+use axum::{
+    body::{Body, Bytes},
+    extract::MatchedPath,
+    http::{header::HOST, Request},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use std::{collections::HashMap, sync::Arc};
+use tower::ServiceExt;
 use tower_http::trace::TraceLayer;
 use tracing::{info_span, Span};
 
@@ -19,21 +32,52 @@ pub struct ApplicationState {
     pub verification_state: Option<VerificationState>,
 }
 
-pub fn app(
-    ApplicationState {
-        issuance_state,
-        holder_state,
-        verification_state,
-    }: ApplicationState,
-) -> Router {
-    Router::new()
-        .nest(
-            &get_base_path().unwrap_or_default(),
-            Router::new()
-                .merge(issuance_state.map(issuance::router).unwrap_or_default())
-                .merge(holder_state.map(holder::router).unwrap_or_default())
-                .merge(verification_state.map(verification::router).unwrap_or_default()),
-        )
+/// A tenant that is served next to the default tenant. Requests are routed to the tenant either by their `Host`
+/// header or by a path prefix.
+pub struct Tenant {
+    pub route: TenantRoute,
+    pub state: ApplicationState,
+}
+
+pub fn app(state: ApplicationState) -> Router {
+    app_with_tenants(state, vec![])
+}
+
+pub fn app_with_tenants(state: ApplicationState, tenants: Vec<Tenant>) -> Router {
+    let mut router = routes(state);
+    let mut hosts = HashMap::new();
+
+    for Tenant { route, state } in tenants {
+        match route {
+            TenantRoute::Host(host) => {
+                hosts.insert(host.to_lowercase(), with_base_path(routes(state)));
+            }
+            TenantRoute::BasePath(base_path) => {
+                router = router.nest(&normalize_base_path(base_path), routes(state));
+            }
+        }
+    }
+
+    let mut router = with_base_path(router);
+
+    if !hosts.is_empty() {
+        let hosts = Arc::new(hosts);
+        router = router.layer(middleware::from_fn(move |request: Request<Body>, next: Next| {
+            let hosts = hosts.clone();
+            async move {
+                match request_host(&request).and_then(|host| hosts.get(&host)) {
+                    Some(tenant_router) => tenant_router
+                        .clone()
+                        .oneshot(request)
+                        .await
+                        .unwrap_or_else(|error| match error {}),
+                    None => next.run(request).await,
+                }
+            }
+        }));
+    }
+
+    router
         // Trace layer
         .layer(
             TraceLayer::new_for_http()
@@ -59,42 +103,77 @@ pub fn app(
         )
 }
 
+fn routes(
+    ApplicationState {
+        issuance_state,
+        holder_state,
+        verification_state,
+    }: ApplicationState,
+) -> Router {
+    Router::new()
+        .merge(issuance_state.map(issuance::router).unwrap_or_default())
+        .merge(holder_state.map(holder::router).unwrap_or_default())
+        .merge(verification_state.map(verification::router).unwrap_or_default())
+}
+
+fn with_base_path(router: Router) -> Router {
+    Router::new().nest(&get_base_path().unwrap_or_default(), router)
+}
+
+/// Returns the lowercased host of the request without its port.
+fn request_host(request: &Request<Body>) -> Option<String> {
+    request
+        .headers()
+        .get(HOST)
+        .and_then(|host| host.to_str().ok())
+        .or_else(|| request.uri().host())
+        .map(|host| host.split(':').next().unwrap_or(host).to_lowercase())
+}
+
 fn get_base_path() -> Result<String, ConfigError> {
     config()
         .base_path
         .clone()
         .ok_or_else(|| ConfigError::NotFound("No configuration for `base_path` found".to_string()))
-        .map(|mut base_path| {
-            if base_path.starts_with('/') {
-                base_path.remove(0);
-            }
-
-            if base_path.ends_with('/') {
-                base_path.pop();
-            }
-
-            if base_path.is_empty() {
-                panic!("UNICORE__BASE_PATH can't be empty, remove or set path");
-            }
+        .map(|base_path| {
+            let base_path = normalize_base_path(base_path);
 
             tracing::info!("Base path: {:?}", base_path);
 
-            format!("/{}", base_path)
+            base_path
         })
 }
 
+fn normalize_base_path(mut base_path: String) -> String {
+    if base_path.starts_with('/') {
+        base_path.remove(0);
+    }
+
+    if base_path.ends_with('/') {
+        base_path.pop();
+    }
+
+    if base_path.is_empty() {
+        panic!("UNICORE__BASE_PATH can't be empty, remove or set path");
+    }
+
+    format!("/{}", base_path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use agent_issuance::startup_commands::{initialize, startup_commands};
     use agent_secret_manager::service::Service;
     use agent_store::in_memory;
+    use axum::http::{self, StatusCode};
     use axum::routing::post;
     use oid4vci::credential_issuer::{
         credential_configurations_supported::CredentialConfigurationsSupportedObject,
         credential_issuer_metadata::CredentialIssuerMetadata,
     };
-    use serde_json::json;
-    use std::collections::HashMap;
+    use serde_json::{json, Value};
+    use tower::Service as _;
 
     pub const CREDENTIAL_CONFIGURATION_ID: &str = "badge";
     pub const OFFER_ID: &str = "00000000-0000-0000-0000-000000000000";
@@ -150,4 +229,67 @@ mod tests {
 
         let _ = router.route("/auth/token", post(handler));
     }
+
+    async fn issuance_state(url: &str) -> ApplicationState {
+        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        initialize(&issuance_state, startup_commands(url.parse().unwrap())).await;
+
+        ApplicationState {
+            issuance_state: Some(issuance_state),
+            ..Default::default()
+        }
+    }
+
+    async fn credential_issuer(app: &mut Router, host: &str, path: &str) -> Value {
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(path)
+                    .header(http::header::HOST, host)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let credential_issuer_metadata: Value = serde_json::from_slice(&body).unwrap();
+
+        credential_issuer_metadata["credential_issuer"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_tenant_routes() {
+        let mut app = app_with_tenants(
+            issuance_state("https://example.com/").await,
+            vec![
+                Tenant {
+                    route: TenantRoute::BasePath("acme".to_string()),
+                    state: issuance_state("https://example.com/acme/").await,
+                },
+                Tenant {
+                    route: TenantRoute::Host("Globex.example.com".to_string()),
+                    state: issuance_state("https://globex.example.com/").await,
+                },
+            ],
+        );
+
+        let path = "/.well-known/openid-credential-issuer";
+
+        assert_eq!(
+            credential_issuer(&mut app, "example.com", path).await,
+            "https://example.com/"
+        );
+        assert_eq!(
+            credential_issuer(&mut app, "example.com", &format!("/acme{path}")).await,
+            "https://example.com/acme/"
+        );
+        assert_eq!(
+            credential_issuer(&mut app, "globex.example.com:3033", path).await,
+            "https://globex.example.com/"
+        );
+    }
 }
@@ -0,0 +1,147 @@
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use image::{DynamicImage, ImageFormat, Luma};
+use qrcode::{render::svg, EcLevel, QrCode};
+use serde::Deserialize;
+use std::io::Cursor;
+use tracing::error;
+
+const DEFAULT_SIZE: u32 = 256;
+const MAX_SIZE: u32 = 2048;
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QrCodeFormat {
+    #[default]
+    Png,
+    Svg,
+}
+
+/// The error correction level of the QR code, i.e. the share of the code that can be restored when it is damaged.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCorrection {
+    /// 7%
+    L,
+    /// 15%
+    #[default]
+    M,
+    /// 25%
+    Q,
+    /// 30%
+    H,
+}
+
+impl From<ErrorCorrection> for EcLevel {
+    fn from(error_correction: ErrorCorrection) -> Self {
+        match error_correction {
+            ErrorCorrection::L => EcLevel::L,
+            ErrorCorrection::M => EcLevel::M,
+            ErrorCorrection::Q => EcLevel::Q,
+            ErrorCorrection::H => EcLevel::H,
+        }
+    }
+}
+
+/// The query parameters of the QR code endpoints.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrCodeParams {
+    #[serde(default)]
+    pub format: QrCodeFormat,
+    /// The minimum width and height of the QR code in pixels.
+    pub size: Option<u32>,
+    #[serde(default)]
+    pub error_correction: ErrorCorrection,
+}
+
+/// Renders the `data` as a QR code in the format and size that are requested in the `params`.
+pub(crate) fn qr_code_response(data: &str, params: &QrCodeParams) -> Response {
+    let size = params.size.unwrap_or(DEFAULT_SIZE);
+    if size == 0 || size > MAX_SIZE {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("`size` must be between 1 and {MAX_SIZE}"),
+        )
+            .into_response();
+    }
+
+    let qr_code = match QrCode::with_error_correction_level(data.as_bytes(), params.error_correction.into()) {
+        Ok(qr_code) => qr_code,
+        // The data does not fit in a QR code with the requested error correction level.
+        Err(err) => return (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+    };
+
+    match params.format {
+        QrCodeFormat::Png => {
+            let image = qr_code.render::<Luma<u8>>().min_dimensions(size, size).build();
+
+            let mut png = Vec::new();
+            if let Err(err) = DynamicImage::ImageLuma8(image).write_to(&mut Cursor::new(&mut png), ImageFormat::Png) {
+                error!("Failed to encode the QR code as PNG: {err}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+
+            (StatusCode::OK, [(header::CONTENT_TYPE, "image/png")], png).into_response()
+        }
+        QrCodeFormat::Svg => {
+            let svg = qr_code.render::<svg::Color>().min_dimensions(size, size).build();
+
+            (StatusCode::OK, [(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    async fn body(response: Response) -> Vec<u8> {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_qr_code_response() {
+        let data = "openid-credential-offer://?credential_offer_uri=https%3A%2F%2Fmy-domain.example.org";
+
+        let response = qr_code_response(data, &QrCodeParams::default());
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(header::CONTENT_TYPE).unwrap(), "image/png");
+
+        let png = body(response).await;
+        assert!(png.starts_with(&PNG_SIGNATURE));
+
+        // The PNG can be decoded and has at least the default size.
+        let image = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+        assert!(image.width() >= DEFAULT_SIZE && image.height() >= DEFAULT_SIZE);
+
+        let response = qr_code_response(
+            data,
+            &QrCodeParams {
+                format: QrCodeFormat::Svg,
+                size: Some(512),
+                error_correction: ErrorCorrection::H,
+            },
+        );
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(header::CONTENT_TYPE).unwrap(), "image/svg+xml");
+
+        let svg = String::from_utf8(body(response).await).unwrap();
+        assert!(svg.contains("<svg"));
+
+        let response = qr_code_response(
+            data,
+            &QrCodeParams {
+                size: Some(MAX_SIZE + 1),
+                ..Default::default()
+            },
+        );
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::qr_code::{qr_code_response, QrCodeParams};
 use crate::API_VERSION;
 use agent_shared::{
     generate_random_string,
@@ -8,7 +9,7 @@ use agent_verification::{
     state::VerificationState,
 };
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
@@ -35,6 +36,23 @@ pub(crate) async fn get_authorization_requests(
     }
 }
 
+/// Renders the form-url-encoded Authorization Request as a QR code that can be scanned by a Wallet.
+#[axum_macros::debug_handler]
+pub(crate) async fn authorization_request_qr_code(
+    State(state): State<VerificationState>,
+    Path(authorization_request_id): Path<String>,
+    Query(params): Query<QrCodeParams>,
+) -> Response {
+    match query_handler(&authorization_request_id, &state.query.authorization_request).await {
+        Ok(Some(AuthorizationRequestView {
+            form_url_encoded_authorization_request: Some(form_url_encoded_authorization_request),
+            ..
+        })) => qr_code_response(&form_url_encoded_authorization_request, &params),
+        Ok(_) => StatusCode::NOT_FOUND.into_response(),
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct AuthorizationRequestsEndpointRequest {
     pub nonce: String,
@@ -226,4 +244,29 @@ pub mod tests {
 
         authorization_requests(&mut app, by_value).await;
     }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_authorization_request_qr_code_endpoint() {
+        let verification_state = in_memory::verification_state(Service::default(), Default::default()).await;
+
+        let mut app = router(verification_state);
+
+        let form_url_encoded_authorization_request = authorization_requests(&mut app, true).await;
+        let state = form_url_encoded_authorization_request.rsplit("%2F").next().unwrap();
+
+        let response = app
+            .call(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(&format!("{API_VERSION}/authorization_requests/{state}/qr?format=svg"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "image/svg+xml");
+    }
 }
@@ -6,8 +6,9 @@ use axum::routing::get;
 use axum::{routing::post, Router};
 
 use crate::verification::{
-    authorization_requests::authorization_requests, authorization_requests::get_authorization_requests,
-    relying_party::redirect::redirect, relying_party::request::request,
+    authorization_requests::authorization_request_qr_code, authorization_requests::authorization_requests,
+    authorization_requests::get_authorization_requests, relying_party::redirect::redirect,
+    relying_party::request::request,
 };
 use crate::API_VERSION;
 
@@ -20,6 +21,10 @@ pub fn router(verification_state: VerificationState) -> Router {
                 .route(
                     "/authorization_requests/:authorization_request_id",
                     get(get_authorization_requests),
+                )
+                .route(
+                    "/authorization_requests/:authorization_request_id/qr",
+                    get(authorization_request_qr_code),
                 ),
         )
         .route("/request/:request_id", get(request))
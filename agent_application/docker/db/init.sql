@@ -1,3 +1,8 @@
+-- The tables below are created in the default schema. Every tenant (see `tenants` in `config.yaml`) requires the same
+-- tables in its own schema, which can be created by prepending the following statements:
+-- CREATE SCHEMA <tenant_id>;
+-- SET search_path TO <tenant_id>;
+
 CREATE TABLE events
 (
     aggregate_type text                         NOT NULL,
@@ -42,6 +47,38 @@ CREATE TABLE access_token
     PRIMARY KEY (view_id)
 );
 
+CREATE TABLE issuer_state
+(
+    view_id           text                        NOT NULL,
+    version           bigint CHECK (version >= 0) NOT NULL,
+    payload           json                        NOT NULL,
+    PRIMARY KEY (view_id)
+);
+
+CREATE TABLE authorization_code
+(
+    view_id           text                        NOT NULL,
+    version           bigint CHECK (version >= 0) NOT NULL,
+    payload           json                        NOT NULL,
+    PRIMARY KEY (view_id)
+);
+
+CREATE TABLE refresh_token
+(
+    view_id           text                        NOT NULL,
+    version           bigint CHECK (version >= 0) NOT NULL,
+    payload           json                        NOT NULL,
+    PRIMARY KEY (view_id)
+);
+
+CREATE TABLE credential_offer_uri
+(
+    view_id           text                        NOT NULL,
+    version           bigint CHECK (version >= 0) NOT NULL,
+    payload           json                        NOT NULL,
+    PRIMARY KEY (view_id)
+);
+
 CREATE TABLE credential
 (
     view_id           text                        NOT NULL,
@@ -66,6 +103,14 @@ CREATE TABLE server_config
     PRIMARY KEY (view_id)
 );
 
+CREATE TABLE status_list
+(
+    view_id           text                        NOT NULL,
+    version           bigint CHECK (version >= 0) NOT NULL,
+    payload           json                        NOT NULL,
+    PRIMARY KEY (view_id)
+);
+
 CREATE TABLE received_offer
 (
     view_id           text                        NOT NULL,
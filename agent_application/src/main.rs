@@ -1,12 +1,16 @@
 #![allow(clippy::await_holding_lock)]
 
-use agent_api_rest::{app, ApplicationState};
+use agent_api_rest::{app_with_tenants, ApplicationState, Tenant};
 use agent_event_publisher_http::EventPublisherHttp;
 use agent_holder::services::HolderServices;
-use agent_issuance::{services::IssuanceServices, startup_commands::startup_commands, state::initialize};
-use agent_secret_manager::{secret_manager, service::Service as _, subject::Subject};
+use agent_issuance::{
+    services::IssuanceServices,
+    startup_commands::{startup_commands, tenant_startup_commands},
+    state::initialize,
+};
+use agent_secret_manager::{secret_manager, secret_manager_from_config, service::Service as _, subject::Subject};
 use agent_shared::{
-    config::{config, LogFormat, SupportedDidMethod, ToggleOptions},
+    config::{config, LogFormat, SupportedDidMethod, TenantConfiguration, ToggleOptions},
     domain_linkage::create_did_configuration_resource,
     from_jsonwebtoken_algorithm_to_jwsalgorithm,
 };
@@ -70,11 +74,27 @@ async fn main() -> io::Result<()> {
 
     initialize(&issuance_state, startup_commands(url.clone())).await;
 
-    let mut app = app(ApplicationState {
-        issuance_state: Some(issuance_state),
-        holder_state: Some(holder_state),
-        verification_state: Some(verification_state),
-    });
+    let tenants = config().tenants.clone();
+    let mut tenant_states = vec![];
+    for tenant in tenants {
+        info!("Tenant `{}` url: {:?}", tenant.tenant_id, tenant.url);
+
+        tenant_states.push(Tenant {
+            route: tenant
+                .route()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+            state: tenant_state(&tenant).await,
+        });
+    }
+
+    let mut app = app_with_tenants(
+        ApplicationState {
+            issuance_state: Some(issuance_state),
+            holder_state: Some(holder_state),
+            verification_state: Some(verification_state),
+        },
+        tenant_states,
+    );
 
     // CORS
     if config().cors_enabled.unwrap_or(false) {
@@ -167,3 +187,43 @@ async fn main() -> io::Result<()> {
 
     Ok(())
 }
+
+/// Initializes the issuer, holder and verifier of a tenant with its own keys, event publishers and event store.
+async fn tenant_state(tenant: &TenantConfiguration) -> ApplicationState {
+    let subject = Arc::new(Subject {
+        secret_manager: Arc::new(tokio::sync::Mutex::new(
+            secret_manager_from_config(tenant.secret_manager.clone()).await,
+        )),
+    });
+
+    let issuance_services = Arc::new(IssuanceServices::for_tenant(subject.clone(), tenant));
+    let holder_services = Arc::new(HolderServices::new(subject.clone()));
+    let verification_services = Arc::new(VerificationServices::for_tenant(subject, tenant));
+
+    let event_publishers = || -> Vec<Box<dyn EventPublisher>> {
+        vec![Box::new(
+            EventPublisherHttp::from_config(tenant.event_publishers.clone()).unwrap(),
+        )]
+    };
+
+    let (issuance_state, holder_state, verification_state) = match config().event_store.type_ {
+        agent_shared::config::EventStoreType::Postgres => (
+            postgres::tenant_issuance_state(issuance_services, event_publishers(), &tenant.tenant_id).await,
+            postgres::tenant_holder_state(holder_services, event_publishers(), &tenant.tenant_id).await,
+            postgres::tenant_verification_state(verification_services, event_publishers(), &tenant.tenant_id).await,
+        ),
+        agent_shared::config::EventStoreType::InMemory => (
+            in_memory::issuance_state(issuance_services, event_publishers()).await,
+            in_memory::holder_state(holder_services, event_publishers()).await,
+            in_memory::verification_state(verification_services, event_publishers()).await,
+        ),
+    };
+
+    initialize(&issuance_state, tenant_startup_commands(tenant)).await;
+
+    ApplicationState {
+        issuance_state: Some(issuance_state),
+        holder_state: Some(holder_state),
+        verification_state: Some(verification_state),
+    }
+}
@@ -1,7 +1,7 @@
 use agent_issuance::{
     credential::aggregate::Credential, offer::aggregate::Offer, server_config::aggregate::ServerConfig,
 };
-use agent_shared::config::config;
+use agent_shared::config::{config, EventPublishers};
 use agent_store::{
     AuthorizationRequestEventPublisher, ConnectionEventPublisher, CredentialEventPublisher, EventPublisher,
     HolderCredentialEventPublisher, OfferEventPublisher, ReceivedOfferEventPublisher, ServerConfigEventPublisher,
@@ -33,7 +33,17 @@ pub struct EventPublisherHttp {
 
 impl EventPublisherHttp {
     pub fn load() -> anyhow::Result<Self> {
-        let event_publisher_http = config().event_publishers.clone().unwrap().http.unwrap();
+        let event_publishers = config().event_publishers.clone();
+
+        Self::from_config(event_publishers)
+    }
+
+    /// Loads the event publisher from the given `event_publishers` configuration, e.g. the one of a tenant. Returns an
+    /// empty event publisher when no HTTP event publisher is configured.
+    pub fn from_config(event_publishers: Option<EventPublishers>) -> anyhow::Result<Self> {
+        let Some(event_publisher_http) = event_publishers.and_then(|event_publishers| event_publishers.http) else {
+            return Ok(EventPublisherHttp::default());
+        };
 
         // If it's not enabled, return an empty event publisher.
         if !event_publisher_http.enabled {
@@ -281,6 +291,7 @@ mod tests {
         let offer_event = OfferEvent::FormUrlEncodedCredentialOfferCreated {
             offer_id: Default::default(),
             form_url_encoded_credential_offer: "form_url_encoded_credential_offer".to_string(),
+            credential_offer_uri_id: None,
         };
 
         let events = [EventEnvelope::<Offer> {
@@ -306,6 +317,7 @@ mod tests {
         let offer_event = OfferEvent::CredentialsAdded {
             offer_id: Default::default(),
             credential_ids: vec!["credential-0001".to_string()],
+            credential_configuration_id: Some("badge".to_string()),
         };
 
         let events = [EventEnvelope::<Offer> {
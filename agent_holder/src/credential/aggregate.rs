@@ -46,6 +46,14 @@ impl Aggregate for Credential {
                 offer_id,
                 credential,
             }]),
+            DeleteCredential { credential_id } => {
+                // Nothing to delete when the Credential does not exist (anymore).
+                if self.credential.is_none() {
+                    return Ok(vec![]);
+                }
+
+                Ok(vec![CredentialDeleted { credential_id }])
+            }
         }
     }
 
@@ -64,6 +72,9 @@ impl Aggregate for Credential {
                 self.offer_id = Some(offer_id);
                 self.credential = Some(credential);
             }
+            CredentialDeleted { .. } => {
+                self.credential.take();
+            }
         }
     }
 }
@@ -99,6 +110,36 @@ pub mod credential_tests {
                 credential: json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT),
             }])
     }
+
+    #[rstest]
+    #[serial_test::serial]
+    fn test_delete_credential(credential_id: String, offer_id: String) {
+        let credential_added = CredentialEvent::CredentialAdded {
+            credential_id: credential_id.clone(),
+            offer_id,
+            credential: json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT),
+        };
+
+        CredentialTestFramework::with(Service::default())
+            .given(vec![credential_added.clone()])
+            .when(CredentialCommand::DeleteCredential {
+                credential_id: credential_id.clone(),
+            })
+            .then_expect_events(vec![CredentialEvent::CredentialDeleted {
+                credential_id: credential_id.clone(),
+            }]);
+
+        // A Credential can only be deleted once.
+        CredentialTestFramework::with(Service::default())
+            .given(vec![
+                credential_added,
+                CredentialEvent::CredentialDeleted {
+                    credential_id: credential_id.clone(),
+                },
+            ])
+            .when(CredentialCommand::DeleteCredential { credential_id })
+            .then_expect_events(vec![]);
+    }
 }
 
 #[cfg(feature = "test_utils")]
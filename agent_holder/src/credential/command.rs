@@ -8,4 +8,7 @@ pub enum CredentialCommand {
         offer_id: String,
         credential: serde_json::Value,
     },
+    DeleteCredential {
+        credential_id: String,
+    },
 }
@@ -8,6 +8,9 @@ pub enum CredentialEvent {
         offer_id: String,
         credential: serde_json::Value,
     },
+    CredentialDeleted {
+        credential_id: String,
+    },
 }
 
 impl DomainEvent for CredentialEvent {
@@ -16,6 +19,7 @@ impl DomainEvent for CredentialEvent {
 
         let event_type: &str = match self {
             CredentialAdded { .. } => "CredentialAdded",
+            CredentialDeleted { .. } => "CredentialDeleted",
         };
         event_type.to_string()
     }
@@ -1,4 +1,5 @@
 use super::HolderCredentialView;
+use crate::credential::event::CredentialEvent;
 use crate::credential::queries::Credential;
 use cqrs_es::{EventEnvelope, View};
 use serde::{Deserialize, Serialize};
@@ -12,6 +13,12 @@ pub struct AllHolderCredentialsView {
 
 impl View<Credential> for AllHolderCredentialsView {
     fn update(&mut self, event: &EventEnvelope<Credential>) {
+        // Deleted Credentials are no longer listed.
+        if let CredentialEvent::CredentialDeleted { .. } = &event.payload {
+            self.credentials.remove(&event.aggregate_id);
+            return;
+        }
+
         self.credentials
             // Get the entry for the aggregate_id
             .entry(event.aggregate_id.clone())
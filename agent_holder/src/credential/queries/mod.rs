@@ -26,6 +26,9 @@ impl View<Credential> for HolderCredentialView {
                 self.offer_id.replace(offer_id.clone());
                 self.credential.replace(credential.clone());
             }
+            CredentialDeleted { .. } => {
+                self.credential.take();
+            }
         }
     }
 }
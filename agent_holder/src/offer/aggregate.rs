@@ -2,17 +2,30 @@ use crate::offer::command::OfferCommand;
 use crate::offer::error::OfferError;
 use crate::offer::event::OfferEvent;
 use crate::services::HolderServices;
+use agent_shared::config::{get_preferred_did_method, get_preferred_signing_algorithm};
+use agent_shared::dpop::{create_proof, DPOP};
+use agent_shared::notification::{NotificationEvent, NotificationRequest};
+use agent_shared::url_utils::UrlAppendHelpers;
 use async_trait::async_trait;
 use cqrs_es::Aggregate;
+use oid4vc_core::Subject;
+use oid4vci::credential_issuer::authorization_server_metadata::AuthorizationServerMetadata;
 use oid4vci::credential_issuer::credential_configurations_supported::CredentialConfigurationsSupportedObject;
+use oid4vci::credential_issuer::credential_issuer_metadata::CredentialIssuerMetadata;
 use oid4vci::credential_offer::{CredentialOffer, CredentialOfferParameters, Grants};
-use oid4vci::credential_response::CredentialResponseType;
+use oid4vci::credential_request::CredentialRequest;
+use oid4vci::credential_response::{BatchCredentialResponse, CredentialResponse, CredentialResponseType};
 use oid4vci::token_request::TokenRequest;
 use oid4vci::token_response::TokenResponse;
+use oid4vci::{KeyProofType, ProofType};
+use reqwest::{header::AUTHORIZATION, RequestBuilder};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{info, warn};
+use url::Url;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub enum Status {
@@ -33,6 +46,9 @@ pub struct Offer {
     // `CredentialResponseReceived` event and then trigger the `CredentialCommand::AddCredential` command. We can do
     // this once we have a mechanism implemented that can both listen to events as well as trigger commands.
     pub credentials: Vec<serde_json::Value>,
+    pub notification_ids: Vec<String>,
+    /// The `transaction_id`s of the credentials whose issuance is deferred by the Credential Issuer.
+    pub transaction_ids: Vec<String>,
 }
 
 #[async_trait]
@@ -108,10 +124,8 @@ impl Aggregate for Offer {
                 let credential_issuer_url = credential_offer.credential_issuer.clone();
 
                 // Get the authorization server metadata.
-                let authorization_server_metadata = wallet
-                    .get_authorization_server_metadata(credential_issuer_url.clone())
-                    .await
-                    .map_err(|_| AuthorizationServerMetadataRetrievalError)?;
+                let (authorization_server_metadata, dpop_supported) =
+                    authorization_server_metadata(services, &credential_issuer_url).await?;
 
                 // Create a token request with grant_type `pre_authorized_code`.
                 let token_request = match credential_offer.grants.clone() {
@@ -127,16 +141,20 @@ impl Aggregate for Offer {
 
                 info!("token_request: {:?}", token_request);
 
-                // Get an access token.
-                let token_response = wallet
-                    .get_access_token(
-                        authorization_server_metadata
-                            .token_endpoint
-                            .ok_or(MissingTokenEndpointError)?,
-                        token_request,
-                    )
-                    .await
-                    .map_err(|_| TokenResponseError)?;
+                let token_endpoint = authorization_server_metadata
+                    .token_endpoint
+                    .ok_or(MissingTokenEndpointError)?;
+
+                // Get an access token. When the Authorization Server supports DPoP, the access token is bound to the
+                // key of the Holder.
+                let token_response = if dpop_supported {
+                    dpop_token_request(services, &token_endpoint, &token_request).await?
+                } else {
+                    wallet
+                        .get_access_token(token_endpoint, token_request)
+                        .await
+                        .map_err(|_| TokenResponseError)?
+                };
 
                 info!("token_response: {:?}", token_response);
 
@@ -178,7 +196,9 @@ impl Aggregate for Offer {
                     .as_ref()
                     .ok_or(MissingCredentialConfigurationsError)?;
 
-                let credentials: Vec<serde_json::Value> = match credential_configuration_ids.len() {
+                let deferred_credential_endpoint = credential_issuer_metadata.deferred_credential_endpoint.clone();
+
+                let credential_responses: Vec<CredentialResponseType> = match credential_configuration_ids.len() {
                     0 => vec![],
                     1 => {
                         let credential_configuration_id = &credential_configuration_ids[0];
@@ -188,36 +208,195 @@ impl Aggregate for Offer {
                             .ok_or(MissingCredentialConfigurationError)?;
 
                         // Get the credential.
-                        let credential_response = wallet
-                            .get_credential(credential_issuer_metadata, &token_response, credential_configuration)
-                            .await
-                            .map_err(|_| CredentialResponseError)?;
+                        let credential_response = if is_dpop_bound(&token_response) {
+                            let credential_request = credential_request(
+                                &services.holder,
+                                &credential_issuer_metadata,
+                                &token_response,
+                                credential_configuration,
+                            )
+                            .await?;
 
-                        let credential = match credential_response.credential {
-                            CredentialResponseType::Immediate { credential, .. } => credential,
-                            CredentialResponseType::Deferred { .. } => {
-                                return Err(UnsupportedDeferredCredentialResponseError)
-                            }
+                            send_dpop_credential_request(
+                                services,
+                                &credential_issuer_metadata.credential_endpoint,
+                                &token_response,
+                                json!(credential_request),
+                            )
+                            .await
+                            .map_err(|_| CredentialResponseError)?
+                        } else {
+                            wallet
+                                .get_credential(credential_issuer_metadata, &token_response, credential_configuration)
+                                .await
+                                .map_err(|_| CredentialResponseError)?
                         };
 
-                        vec![credential]
+                        vec![credential_response.credential]
                     }
                     _batch => {
-                        return Err(BatchCredentialRequestError);
+                        let credential_configurations = credential_configuration_ids
+                            .iter()
+                            .map(|credential_configuration_id| {
+                                credential_configurations
+                                    .get(credential_configuration_id)
+                                    .cloned()
+                                    .ok_or(MissingCredentialConfigurationError)
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+
+                        // Get the credentials.
+                        let batch_credential_response: BatchCredentialResponse = if is_dpop_bound(&token_response) {
+                            let batch_credential_endpoint = credential_issuer_metadata
+                                .batch_credential_endpoint
+                                .clone()
+                                .ok_or(BatchCredentialResponseError)?;
+
+                            let mut credential_requests = vec![];
+                            for credential_configuration in &credential_configurations {
+                                credential_requests.push(
+                                    credential_request(
+                                        &services.holder,
+                                        &credential_issuer_metadata,
+                                        &token_response,
+                                        credential_configuration,
+                                    )
+                                    .await?,
+                                );
+                            }
+
+                            send_dpop_credential_request(
+                                services,
+                                &batch_credential_endpoint,
+                                &token_response,
+                                json!({ "credential_requests": credential_requests }),
+                            )
+                            .await
+                            .map_err(|_| BatchCredentialResponseError)?
+                        } else {
+                            wallet
+                                .get_batch_credential(
+                                    credential_issuer_metadata,
+                                    &token_response,
+                                    &credential_configurations,
+                                )
+                                .await
+                                .map_err(|_| BatchCredentialResponseError)?
+                        };
+
+                        batch_credential_response.credential_responses
                     }
                 };
 
+                let mut credentials: Vec<serde_json::Value> = vec![];
+                let mut notification_ids: Vec<String> = vec![];
+                let mut transaction_ids: Vec<String> = vec![];
+                for credential_response in credential_responses {
+                    match credential_response {
+                        CredentialResponseType::Immediate {
+                            credential,
+                            notification_id,
+                        } => {
+                            credentials.push(credential);
+                            // Credentials from the same (Batch) Credential Response can share the same
+                            // `notification_id`.
+                            if let Some(notification_id) = notification_id.filter(|id| !notification_ids.contains(id)) {
+                                notification_ids.push(notification_id);
+                            }
+                        }
+                        // The issuance of the credential is deferred, so the Deferred Credential Endpoint needs to be
+                        // polled using `PollDeferredCredentials` until the credential is available.
+                        CredentialResponseType::Deferred { transaction_id } => {
+                            if deferred_credential_endpoint.is_none() {
+                                return Err(MissingDeferredCredentialEndpointError);
+                            }
+                            transaction_ids.push(transaction_id);
+                        }
+                    }
+                }
+
                 info!("credentials: {:?}", credentials);
 
+                if !transaction_ids.is_empty() {
+                    return Ok(vec![CredentialIssuanceDeferred {
+                        offer_id,
+                        credentials,
+                        notification_ids,
+                        transaction_ids,
+                    }]);
+                }
+
                 Ok(vec![CredentialResponseReceived {
                     offer_id,
                     status: Status::Received,
                     credentials,
+                    notification_ids,
                 }])
             }
+            PollDeferredCredentials { offer_id } => {
+                if self.transaction_ids.is_empty() {
+                    return Err(MissingTransactionIdsError);
+                }
+
+                let credential_offer = self.credential_offer.as_ref().ok_or(MissingCredentialOfferError)?;
+                let token_response = self.token_response.as_ref().ok_or(MissingTokenResponseError)?;
+
+                let credential_issuer_metadata = services
+                    .wallet
+                    .get_credential_issuer_metadata(credential_offer.credential_issuer.clone())
+                    .await
+                    .map_err(|_| CredentialIssuerMetadataRetrievalError)?;
+
+                let deferred_credential_endpoint = credential_issuer_metadata
+                    .deferred_credential_endpoint
+                    .ok_or(MissingDeferredCredentialEndpointError)?;
+
+                let mut credentials = self.credentials.clone();
+                let mut notification_ids = self.notification_ids.clone();
+                let mut events = vec![];
+                for transaction_id in &self.transaction_ids {
+                    let Some((credential, notification_id)) = request_deferred_credential(
+                        services,
+                        &deferred_credential_endpoint,
+                        token_response,
+                        transaction_id,
+                    )
+                    .await?
+                    else {
+                        continue;
+                    };
+
+                    credentials.push(credential.clone());
+                    if let Some(notification_id) = notification_id.as_ref().filter(|id| !notification_ids.contains(id))
+                    {
+                        notification_ids.push(notification_id.clone());
+                    }
+
+                    events.push(DeferredCredentialReceived {
+                        offer_id: offer_id.clone(),
+                        transaction_id: transaction_id.clone(),
+                        credential,
+                        notification_id,
+                    });
+                }
+
+                // All credentials of the Credential Offer are received once none of them is deferred anymore.
+                if events.len() == self.transaction_ids.len() {
+                    events.push(CredentialResponseReceived {
+                        offer_id,
+                        status: Status::Received,
+                        credentials,
+                        notification_ids,
+                    });
+                }
+
+                Ok(events)
+            }
             RejectCredentialOffer { offer_id } => {
+                // An Offer can be rejected before it is accepted or after its Credentials have been received, in which
+                // case the Credentials are discarded.
                 // TODO: should we 'do nothing' or log a `warn!` message instead of returning an error?
-                if self.status != Status::Pending {
+                if !matches!(self.status, Status::Pending | Status::Received) {
                     return Err(CredentialOfferStatusNotPendingError);
                 }
 
@@ -226,6 +405,42 @@ impl Aggregate for Offer {
                     status: Status::Rejected,
                 }])
             }
+            SendCredentialNotifications {
+                offer_id,
+                notification_event,
+            } => {
+                // Nothing to notify when the Credential Issuer did not return any `notification_id`s.
+                if self.notification_ids.is_empty() {
+                    return Ok(vec![]);
+                }
+
+                let credential_offer = self.credential_offer.as_ref().ok_or(MissingCredentialOfferError)?;
+                let token_response = self.token_response.as_ref().ok_or(MissingTokenResponseError)?;
+
+                let Some(notification_endpoint) = services
+                    .wallet
+                    .get_credential_issuer_metadata(credential_offer.credential_issuer.clone())
+                    .await
+                    .map_err(|_| CredentialIssuerMetadataRetrievalError)?
+                    .notification_endpoint
+                else {
+                    return Ok(vec![]);
+                };
+
+                send_notifications(
+                    services,
+                    &notification_endpoint,
+                    token_response,
+                    &self.notification_ids,
+                    notification_event.clone(),
+                )
+                .await;
+
+                Ok(vec![CredentialNotificationSent {
+                    offer_id,
+                    notification_event,
+                }])
+            }
         }
     }
 
@@ -250,34 +465,321 @@ impl Aggregate for Offer {
                 self.token_response.replace(token_response);
             }
             CredentialResponseReceived {
-                status, credentials, ..
+                status,
+                credentials,
+                notification_ids,
+                ..
             } => {
                 self.status = status;
                 self.credentials = credentials;
+                self.notification_ids = notification_ids;
+            }
+            CredentialIssuanceDeferred {
+                credentials,
+                notification_ids,
+                transaction_ids,
+                ..
+            } => {
+                self.credentials = credentials;
+                self.notification_ids = notification_ids;
+                self.transaction_ids = transaction_ids;
+            }
+            DeferredCredentialReceived {
+                transaction_id,
+                credential,
+                notification_id,
+                ..
+            } => {
+                // Multiple credentials of a Batch Credential Response can be deferred using the same `transaction_id`.
+                if let Some(index) = self.transaction_ids.iter().position(|id| *id == transaction_id) {
+                    self.transaction_ids.remove(index);
+                }
+                self.credentials.push(credential);
+                if let Some(notification_id) = notification_id.filter(|id| !self.notification_ids.contains(id)) {
+                    self.notification_ids.push(notification_id);
+                }
             }
             CredentialOfferRejected { status, .. } => {
                 self.status = status;
             }
+            CredentialNotificationSent { .. } => {}
         }
     }
 }
 
+/// Sends a Notification Request for each of the `notification_ids` to the Notification Endpoint of the Credential
+/// Issuer. Notifications are informational only, so a failure is logged rather than returned.
+async fn send_notifications(
+    services: &HolderServices,
+    notification_endpoint: &Url,
+    token_response: &TokenResponse,
+    notification_ids: &[String],
+    event: NotificationEvent,
+) {
+    for notification_id in notification_ids {
+        let notification_request = NotificationRequest {
+            notification_id: notification_id.clone(),
+            event: event.clone(),
+            event_description: None,
+        };
+
+        let request = match authorize(
+            services.client.post(notification_endpoint.clone()),
+            &services.holder,
+            notification_endpoint,
+            token_response,
+        )
+        .await
+        {
+            Ok(request) => request,
+            Err(err) => {
+                warn!("Failed to send the Notification Request for `notification_id` {notification_id}: {err}");
+                continue;
+            }
+        };
+
+        let result = request
+            .json(&notification_request)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        if let Err(err) = result {
+            warn!("Failed to send the Notification Request for `notification_id` {notification_id}: {err}");
+        }
+    }
+}
+
+/// Sends a Deferred Credential Request for the `transaction_id` to the Deferred Credential Endpoint of the Credential
+/// Issuer. Returns the credential together with its optional `notification_id`, or `None` when its issuance is still
+/// pending.
+async fn request_deferred_credential(
+    services: &HolderServices,
+    deferred_credential_endpoint: &Url,
+    token_response: &TokenResponse,
+    transaction_id: &str,
+) -> Result<Option<(serde_json::Value, Option<String>)>, OfferError> {
+    use OfferError::*;
+
+    let request = authorize(
+        services.client.post(deferred_credential_endpoint.clone()),
+        &services.holder,
+        deferred_credential_endpoint,
+        token_response,
+    )
+    .await?;
+
+    let response = request
+        .json(&json!({ "transaction_id": transaction_id }))
+        .send()
+        .await
+        .map_err(|_| DeferredCredentialResponseError)?;
+
+    if response.status().is_success() {
+        let credential_response: CredentialResponse =
+            response.json().await.map_err(|_| DeferredCredentialResponseError)?;
+
+        return match credential_response.credential {
+            CredentialResponseType::Immediate {
+                credential,
+                notification_id,
+            } => Ok(Some((credential, notification_id))),
+            CredentialResponseType::Deferred { .. } => Ok(None),
+        };
+    }
+
+    let error_response: serde_json::Value = response.json().await.map_err(|_| DeferredCredentialResponseError)?;
+
+    // Any error other than `issuance_pending` means that the credential will not be issued.
+    if error_response["error"] != "issuance_pending" {
+        return Err(DeferredCredentialResponseError);
+    }
+
+    info!("Issuance of the credential with `transaction_id` {transaction_id} is still pending");
+
+    Ok(None)
+}
+
+/// Retrieves the Authorization Server Metadata of the Credential Issuer together with whether the Authorization Server
+/// accepts DPoP proofs that are signed with the preferred signing algorithm. The `Wallet` does not expose the
+/// `dpop_signing_alg_values_supported` parameter of the Authorization Server Metadata, so the metadata is retrieved
+/// directly instead.
+async fn authorization_server_metadata(
+    services: &HolderServices,
+    credential_issuer_url: &Url,
+) -> Result<(AuthorizationServerMetadata, bool), OfferError> {
+    let url = credential_issuer_url.append_path_segment(".well-known/oauth-authorization-server");
+
+    let authorization_server_metadata: serde_json::Value = services
+        .client
+        .get(url)
+        .send()
+        .await
+        .map_err(|_| OfferError::AuthorizationServerMetadataRetrievalError)?
+        .json()
+        .await
+        .map_err(|_| OfferError::AuthorizationServerMetadataRetrievalError)?;
+
+    let dpop_supported = authorization_server_metadata["dpop_signing_alg_values_supported"]
+        .as_array()
+        .is_some_and(|algorithms| algorithms.contains(&json!(get_preferred_signing_algorithm())));
+
+    let authorization_server_metadata = serde_json::from_value(authorization_server_metadata)
+        .map_err(|_| OfferError::AuthorizationServerMetadataRetrievalError)?;
+
+    Ok((authorization_server_metadata, dpop_supported))
+}
+
+/// Returns `true` when the Access Token is bound to the key of the Holder using DPoP.
+fn is_dpop_bound(token_response: &TokenResponse) -> bool {
+    token_response.token_type.eq_ignore_ascii_case(DPOP)
+}
+
+/// Creates a `DPoP` proof of the Holder for a `POST` request to `url`.
+async fn dpop_proof(holder: &Arc<dyn Subject>, url: &Url, access_token: Option<&str>) -> Result<String, OfferError> {
+    create_proof(
+        holder.clone(),
+        &get_preferred_did_method().to_string(),
+        get_preferred_signing_algorithm(),
+        "POST",
+        url,
+        access_token,
+    )
+    .await
+    .map_err(|e| OfferError::DpopProofError(e.to_string()))
+}
+
+/// Sends the Token Request together with a `DPoP` proof of the Holder, so that the Access Token is bound to its key.
+async fn dpop_token_request(
+    services: &HolderServices,
+    token_endpoint: &Url,
+    token_request: &TokenRequest,
+) -> Result<TokenResponse, OfferError> {
+    let proof = dpop_proof(&services.holder, token_endpoint, None).await?;
+
+    services
+        .client
+        .post(token_endpoint.clone())
+        .header(DPOP, proof)
+        .form(token_request)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|_| OfferError::TokenResponseError)?
+        .json()
+        .await
+        .map_err(|_| OfferError::TokenResponseError)
+}
+
+/// Adds the Access Token to a request to one of the protected endpoints of the Credential Issuer. A DPoP-bound Access
+/// Token is sent using the `DPoP` authentication scheme together with a fresh `DPoP` proof.
+async fn authorize(
+    request: RequestBuilder,
+    holder: &Arc<dyn Subject>,
+    url: &Url,
+    token_response: &TokenResponse,
+) -> Result<RequestBuilder, OfferError> {
+    if !is_dpop_bound(token_response) {
+        return Ok(request.bearer_auth(&token_response.access_token));
+    }
+
+    let proof = dpop_proof(holder, url, Some(&token_response.access_token)).await?;
+
+    Ok(request
+        .header(AUTHORIZATION, format!("{DPOP} {}", token_response.access_token))
+        .header(DPOP, proof))
+}
+
+/// Creates a Credential Request for the `credential_configuration` with a `proof` of possession of the Holder's key.
+/// Used instead of the `Wallet` when the Access Token is DPoP-bound, since the `Wallet` only sends Bearer tokens.
+async fn credential_request(
+    holder: &Arc<dyn Subject>,
+    credential_issuer_metadata: &CredentialIssuerMetadata,
+    token_response: &TokenResponse,
+    credential_configuration: &CredentialConfigurationsSupportedObject,
+) -> Result<CredentialRequest, OfferError> {
+    let subject_syntax_type = get_preferred_did_method().to_string();
+    let algorithm = get_preferred_signing_algorithm();
+
+    let iat = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+
+    let proof = KeyProofType::builder()
+        .proof_type(ProofType::Jwt)
+        .algorithm(algorithm)
+        .signer(holder.clone())
+        .iss(
+            holder
+                .identifier(&subject_syntax_type, algorithm)
+                .await
+                .map_err(|_| OfferError::CredentialResponseError)?,
+        )
+        .aud(credential_issuer_metadata.credential_issuer.to_string())
+        .iat(iat)
+        .nonce(
+            token_response
+                .c_nonce
+                .clone()
+                .ok_or(OfferError::CredentialResponseError)?,
+        )
+        .subject_syntax_type(subject_syntax_type)
+        .build()
+        .await
+        .map_err(|_| OfferError::CredentialResponseError)?;
+
+    Ok(CredentialRequest {
+        credential_format: credential_configuration.credential_format.clone(),
+        proof: Some(proof),
+    })
+}
+
+/// Sends a (Batch) Credential Request using a DPoP-bound Access Token.
+async fn send_dpop_credential_request<T: serde::de::DeserializeOwned>(
+    services: &HolderServices,
+    credential_endpoint: &Url,
+    token_response: &TokenResponse,
+    credential_request: serde_json::Value,
+) -> Result<T, OfferError> {
+    let request = authorize(
+        services.client.post(credential_endpoint.clone()),
+        &services.holder,
+        credential_endpoint,
+        token_response,
+    )
+    .await?;
+
+    request
+        .json(&credential_request)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|_| OfferError::CredentialResponseError)?
+        .json()
+        .await
+        .map_err(|_| OfferError::CredentialResponseError)
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::test_utils::*;
     use super::*;
     use agent_api_rest::issuance;
     use agent_api_rest::API_VERSION;
-    use agent_issuance::offer::aggregate::test_utils::token_response;
+    use agent_issuance::credential::aggregate::test_utils::credential_jwt_signed_by_unicore;
+    use agent_issuance::offer::aggregate::test_utils::{notification_id, token_response};
     use agent_issuance::server_config::aggregate::test_utils::credential_configurations_supported;
     use agent_issuance::{startup_commands::startup_commands, state::initialize};
     use agent_secret_manager::service::Service;
+    use agent_shared::config::set_config;
     use agent_shared::generate_random_string;
     use agent_store::in_memory;
     use axum::{
         body::Body,
         http::{self, Request},
     };
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
     use cqrs_es::test::TestFramework;
     use oid4vci::credential_offer::CredentialOffer;
     use rstest::{fixture, rstest};
@@ -287,13 +789,18 @@ pub mod tests {
 
     type OfferTestFramework = TestFramework<Offer>;
 
+    const CREDENTIAL_JWT: &str = "eyJ0eXAiOiJKV1QiLCJhbGciOiJFZERTQSIsImtpZCI6ImRpZDprZXk6ejZNa2dFODROQ01wTWVBeDlqSzljZjVXNEc4Z2NaOXh1d0p2RzFlN3dOazhLQ2d0I3o2TWtnRTg0TkNNcE1lQXg5aks5Y2Y1VzRHOGdjWjl4dXdKdkcxZTd3Tms4S0NndCJ9.eyJpc3MiOiJkaWQ6a2V5Ono2TWtnRTg0TkNNcE1lQXg5aks5Y2Y1VzRHOGdjWjl4dXdKdkcxZTd3Tms4S0NndCIsInN1YiI6ImRpZDprZXk6ejZNa2dFODROQ01wTWVBeDlqSzljZjVXNEc4Z2NaOXh1d0p2RzFlN3dOazhLQ2d0IiwiZXhwIjo5OTk5OTk5OTk5LCJpYXQiOjAsInZjIjp7IkBjb250ZXh0IjoiaHR0cHM6Ly93d3cudzMub3JnLzIwMTgvY3JlZGVudGlhbHMvdjEiLCJ0eXBlIjpbIlZlcmlmaWFibGVDcmVkZW50aWFsIl0sImNyZWRlbnRpYWxTdWJqZWN0Ijp7ImlkIjoiZGlkOmtleTp6Nk1rZ0U4NE5DTXBNZUF4OWpLOWNmNVc0RzhnY1o5eHV3SnZHMWU3d05rOEtDZ3QiLCJkZWdyZWUiOnsidHlwZSI6Ik1hc3RlckRlZ3JlZSIsIm5hbWUiOiJNYXN0ZXIgb2YgT2NlYW5vZ3JhcGh5In0sImZpcnN0X25hbWUiOiJGZXJyaXMiLCJsYXN0X25hbWUiOiJSdXN0YWNlYW4ifSwiaXNzdWVyIjoiZGlkOmtleTp6Nk1rZ0U4NE5DTXBNZUF4OWpLOWNmNVc0RzhnY1o5eHV3SnZHMWU3d05rOEtDZ3QiLCJpc3N1YW5jZURhdGUiOiIyMDEwLTAxLTAxVDAwOjAwOjAwWiJ9fQ.jQEpI7DhjOcmyhPEpfGARwcRyzor_fUvynb43-eqD9175FBoshENX0S-8qlloQ7vbT5gat8TjvcDlGDN720ZBw";
+
     async fn bootstrap_issuer_server() -> CredentialOffer {
         let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
         let issuer_url = format!("http://{}", listener.local_addr().unwrap());
 
-        let issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
+        let mut issuance_state = in_memory::issuance_state(Service::default(), Default::default()).await;
         initialize(&issuance_state, startup_commands(issuer_url.parse().unwrap())).await;
 
+        // The `htu` of the `DPoP` proofs of the Holder refers to the URL at which the Credential Issuer is served.
+        issuance_state.url = issuer_url.clone();
+
         let offer_id = generate_random_string();
 
         let mut app = issuance::router(issuance_state);
@@ -422,9 +929,10 @@ pub mod tests {
         offer_id: String,
         #[future(awt)] credential_offer_parameters: Box<CredentialOfferParameters>,
         #[future(awt)] token_response: TokenResponse,
+        #[future(awt)] notification_id: String,
         credential_configurations_supported: HashMap<String, CredentialConfigurationsSupportedObject>,
     ) {
-        OfferTestFramework::with(Service::default())
+        let events = OfferTestFramework::with(Service::default())
             .given(vec![
                 OfferEvent::CredentialOfferReceived {
                     offer_id: offer_id.clone(),
@@ -437,18 +945,204 @@ pub mod tests {
                 },
                 OfferEvent::TokenResponseReceived {
                     offer_id: offer_id.clone(),
-                    token_response
+                    token_response,
                 },
             ])
             .when_async(OfferCommand::SendCredentialRequest {
                 offer_id: offer_id.clone(),
             })
             .await
-            .then_expect_events(vec![OfferEvent::CredentialResponseReceived {
+            .inspect_result()
+            .unwrap();
+
+        // The credential is issued with a unique `id`, so it is taken from the received credential.
+        let credential = events
+            .iter()
+            .find_map(|event| match event {
+                OfferEvent::CredentialResponseReceived { credentials, .. } => credentials.first().cloned(),
+                _ => None,
+            })
+            .unwrap();
+        let payload = credential.as_str().unwrap().split('.').nth(1).unwrap();
+        let credential_id = serde_json::from_slice::<serde_json::Value>(&URL_SAFE_NO_PAD.decode(payload).unwrap())
+            .unwrap()["vc"]["id"]
+            .clone();
+        assert!(credential_id.as_str().unwrap().starts_with("urn:uuid:"));
+
+        assert_eq!(
+            events,
+            vec![OfferEvent::CredentialResponseReceived {
                 offer_id: offer_id.clone(),
                 status: Status::Received,
-                credentials: vec![json!("eyJ0eXAiOiJKV1QiLCJhbGciOiJFZERTQSIsImtpZCI6ImRpZDprZXk6ejZNa2dFODROQ01wTWVBeDlqSzljZjVXNEc4Z2NaOXh1d0p2RzFlN3dOazhLQ2d0I3o2TWtnRTg0TkNNcE1lQXg5aks5Y2Y1VzRHOGdjWjl4dXdKdkcxZTd3Tms4S0NndCJ9.eyJpc3MiOiJkaWQ6a2V5Ono2TWtnRTg0TkNNcE1lQXg5aks5Y2Y1VzRHOGdjWjl4dXdKdkcxZTd3Tms4S0NndCIsInN1YiI6ImRpZDprZXk6ejZNa2dFODROQ01wTWVBeDlqSzljZjVXNEc4Z2NaOXh1d0p2RzFlN3dOazhLQ2d0IiwiZXhwIjo5OTk5OTk5OTk5LCJpYXQiOjAsInZjIjp7IkBjb250ZXh0IjoiaHR0cHM6Ly93d3cudzMub3JnLzIwMTgvY3JlZGVudGlhbHMvdjEiLCJ0eXBlIjpbIlZlcmlmaWFibGVDcmVkZW50aWFsIl0sImNyZWRlbnRpYWxTdWJqZWN0Ijp7ImlkIjoiZGlkOmtleTp6Nk1rZ0U4NE5DTXBNZUF4OWpLOWNmNVc0RzhnY1o5eHV3SnZHMWU3d05rOEtDZ3QiLCJkZWdyZWUiOnsidHlwZSI6Ik1hc3RlckRlZ3JlZSIsIm5hbWUiOiJNYXN0ZXIgb2YgT2NlYW5vZ3JhcGh5In0sImZpcnN0X25hbWUiOiJGZXJyaXMiLCJsYXN0X25hbWUiOiJSdXN0YWNlYW4ifSwiaXNzdWVyIjoiZGlkOmtleTp6Nk1rZ0U4NE5DTXBNZUF4OWpLOWNmNVc0RzhnY1o5eHV3SnZHMWU3d05rOEtDZ3QiLCJpc3N1YW5jZURhdGUiOiIyMDEwLTAxLTAxVDAwOjAwOjAwWiJ9fQ.jQEpI7DhjOcmyhPEpfGARwcRyzor_fUvynb43-eqD9175FBoshENX0S-8qlloQ7vbT5gat8TjvcDlGDN720ZBw")],
+                credentials: vec![json!(
+                    credential_jwt_signed_by_unicore(CREDENTIAL_JWT, credential_id.as_str().unwrap()).await
+                )],
+                notification_ids: vec![notification_id],
+            },]
+        );
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    #[tokio::test]
+    async fn test_send_credential_request_with_dpop(
+        offer_id: String,
+        #[future(awt)] credential_offer_parameters: Box<CredentialOfferParameters>,
+        credential_configurations_supported: HashMap<String, CredentialConfigurationsSupportedObject>,
+    ) {
+        set_config().dpop_enabled = true;
+
+        let credential_offer_received = OfferEvent::CredentialOfferReceived {
+            offer_id: offer_id.clone(),
+            credential_offer: credential_offer_parameters,
+            credential_configurations: credential_configurations_supported,
+        };
+
+        // The Credential Issuer advertises DPoP support, so the Access Token is bound to the key of the Holder.
+        let events = OfferTestFramework::with(Service::default())
+            .given(vec![credential_offer_received.clone()])
+            .when_async(OfferCommand::AcceptCredentialOffer {
+                offer_id: offer_id.clone(),
+            })
+            .await
+            .inspect_result()
+            .unwrap();
+
+        let token_response = events
+            .iter()
+            .find_map(|event| match event {
+                OfferEvent::TokenResponseReceived { token_response, .. } => Some(token_response.clone()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(token_response.token_type, "DPoP");
+
+        let mut given = vec![
+            credential_offer_received,
+            OfferEvent::CredentialOfferAccepted {
+                offer_id: offer_id.clone(),
+                status: Status::Accepted,
+            },
+            OfferEvent::TokenResponseReceived {
+                offer_id: offer_id.clone(),
+                token_response,
+            },
+        ];
+
+        // The Credential Request is sent with a `DPoP` proof.
+        let events = OfferTestFramework::with(Service::default())
+            .given(given.clone())
+            .when_async(OfferCommand::SendCredentialRequest {
+                offer_id: offer_id.clone(),
+            })
+            .await
+            .inspect_result()
+            .unwrap();
+
+        assert!(matches!(
+            events.as_slice(),
+            [OfferEvent::CredentialResponseReceived { .. }]
+        ));
+
+        // The Notification Request is sent with a `DPoP` proof as well.
+        given.extend(events);
+        OfferTestFramework::with(Service::default())
+            .given(given)
+            .when_async(OfferCommand::SendCredentialNotifications {
+                offer_id: offer_id.clone(),
+                notification_event: NotificationEvent::CredentialAccepted,
+            })
+            .await
+            .then_expect_events(vec![OfferEvent::CredentialNotificationSent {
+                offer_id,
+                notification_event: NotificationEvent::CredentialAccepted,
             }]);
+
+        set_config().dpop_enabled = false;
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    #[tokio::test]
+    async fn test_send_credential_notifications(
+        offer_id: String,
+        #[future(awt)] credential_offer_parameters: Box<CredentialOfferParameters>,
+        #[future(awt)] token_response: TokenResponse,
+        #[future(awt)] notification_id: String,
+        credential_configurations_supported: HashMap<String, CredentialConfigurationsSupportedObject>,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferReceived {
+                    offer_id: offer_id.clone(),
+                    credential_offer: credential_offer_parameters,
+                    credential_configurations: credential_configurations_supported,
+                },
+                OfferEvent::CredentialOfferAccepted {
+                    offer_id: offer_id.clone(),
+                    status: Status::Accepted,
+                },
+                OfferEvent::TokenResponseReceived {
+                    offer_id: offer_id.clone(),
+                    token_response,
+                },
+                OfferEvent::CredentialResponseReceived {
+                    offer_id: offer_id.clone(),
+                    status: Status::Received,
+                    credentials: vec![json!(CREDENTIAL_JWT)],
+                    notification_ids: vec![notification_id],
+                },
+            ])
+            .when_async(OfferCommand::SendCredentialNotifications {
+                offer_id: offer_id.clone(),
+                notification_event: NotificationEvent::CredentialAccepted,
+            })
+            .await
+            .then_expect_events(vec![OfferEvent::CredentialNotificationSent {
+                offer_id,
+                notification_event: NotificationEvent::CredentialAccepted,
+            }]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    #[tokio::test]
+    async fn test_send_credential_notifications_without_notification_ids(
+        offer_id: String,
+        #[future(awt)] credential_offer_parameters: Box<CredentialOfferParameters>,
+        credential_configurations_supported: HashMap<String, CredentialConfigurationsSupportedObject>,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![OfferEvent::CredentialOfferReceived {
+                offer_id: offer_id.clone(),
+                credential_offer: credential_offer_parameters,
+                credential_configurations: credential_configurations_supported,
+            }])
+            .when_async(OfferCommand::SendCredentialNotifications {
+                offer_id,
+                notification_event: NotificationEvent::CredentialDeleted,
+            })
+            .await
+            .then_expect_events(vec![]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    #[tokio::test]
+    async fn test_poll_deferred_credentials_without_deferred_credentials(
+        offer_id: String,
+        #[future(awt)] credential_offer_parameters: Box<CredentialOfferParameters>,
+        credential_configurations_supported: HashMap<String, CredentialConfigurationsSupportedObject>,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![OfferEvent::CredentialOfferReceived {
+                offer_id: offer_id.clone(),
+                credential_offer: credential_offer_parameters,
+                credential_configurations: credential_configurations_supported,
+            }])
+            .when_async(OfferCommand::PollDeferredCredentials { offer_id })
+            .await
+            .then_expect_error_message("The Credential Offer has no deferred credentials");
     }
 
     #[rstest]
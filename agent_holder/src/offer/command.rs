@@ -1,3 +1,4 @@
+use agent_shared::notification::NotificationEvent;
 use oid4vci::credential_offer::CredentialOffer;
 use serde::Deserialize;
 
@@ -14,7 +15,14 @@ pub enum OfferCommand {
     SendCredentialRequest {
         offer_id: String,
     },
+    PollDeferredCredentials {
+        offer_id: String,
+    },
     RejectCredentialOffer {
         offer_id: String,
     },
+    SendCredentialNotifications {
+        offer_id: String,
+        notification_event: NotificationEvent,
+    },
 }
@@ -6,7 +6,7 @@ pub enum OfferError {
     CredentialOfferByReferenceRetrievalError,
     #[error("The Credential Issuer Metadata could not be retrieved")]
     CredentialIssuerMetadataRetrievalError,
-    #[error("The Credential Offer has already been accepted and cannot be rejected anymore")]
+    #[error("The Credential Offer has already been accepted or rejected and cannot be rejected anymore")]
     CredentialOfferStatusNotPendingError,
     #[error("The Credential Offer is missing")]
     MissingCredentialOfferError,
@@ -18,6 +18,8 @@ pub enum OfferError {
     MissingTokenEndpointError,
     #[error("An error occurred while requesting the access token")]
     TokenResponseError,
+    #[error("The DPoP proof could not be created: {0}")]
+    DpopProofError(String),
     #[error("The Credential Offer has not been accepted yet")]
     CredentialOfferStatusNotAcceptedError,
     #[error("The Token Response is missing from the Credential Offer")]
@@ -28,8 +30,12 @@ pub enum OfferError {
     MissingCredentialConfigurationError,
     #[error("An error occurred while requesting the credentials")]
     CredentialResponseError,
-    #[error("Deferred Credential Responses are not supported")]
-    UnsupportedDeferredCredentialResponseError,
-    #[error("Batch Credential Request are not supported")]
-    BatchCredentialRequestError,
+    #[error("The Credential Issuer Metadata is missing the `deferred_credential_endpoint` parameter")]
+    MissingDeferredCredentialEndpointError,
+    #[error("An error occurred while requesting the deferred credential")]
+    DeferredCredentialResponseError,
+    #[error("The Credential Offer has no deferred credentials")]
+    MissingTransactionIdsError,
+    #[error("An error occurred while requesting the batch credentials")]
+    BatchCredentialResponseError,
 }
@@ -1,4 +1,5 @@
 use super::aggregate::Status;
+use agent_shared::notification::NotificationEvent;
 use cqrs_es::DomainEvent;
 use oid4vci::{
     credential_issuer::credential_configurations_supported::CredentialConfigurationsSupportedObject,
@@ -26,11 +27,29 @@ pub enum OfferEvent {
         offer_id: String,
         status: Status,
         credentials: Vec<serde_json::Value>,
+        #[serde(default)]
+        notification_ids: Vec<String>,
+    },
+    CredentialIssuanceDeferred {
+        offer_id: String,
+        credentials: Vec<serde_json::Value>,
+        notification_ids: Vec<String>,
+        transaction_ids: Vec<String>,
+    },
+    DeferredCredentialReceived {
+        offer_id: String,
+        transaction_id: String,
+        credential: serde_json::Value,
+        notification_id: Option<String>,
     },
     CredentialOfferRejected {
         offer_id: String,
         status: Status,
     },
+    CredentialNotificationSent {
+        offer_id: String,
+        notification_event: NotificationEvent,
+    },
 }
 
 impl DomainEvent for OfferEvent {
@@ -42,7 +61,10 @@ impl DomainEvent for OfferEvent {
             CredentialOfferAccepted { .. } => "CredentialOfferAccepted",
             TokenResponseReceived { .. } => "AccessTokenReceived",
             CredentialResponseReceived { .. } => "CredentialResponseReceived",
+            CredentialIssuanceDeferred { .. } => "CredentialIssuanceDeferred",
+            DeferredCredentialReceived { .. } => "DeferredCredentialReceived",
             CredentialOfferRejected { .. } => "CredentialOfferRejected",
+            CredentialNotificationSent { .. } => "CredentialNotificationSent",
         };
         event_type.to_string()
     }
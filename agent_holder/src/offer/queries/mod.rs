@@ -17,6 +17,9 @@ pub struct ReceivedOfferView {
     pub credential_configurations: Option<HashMap<String, CredentialConfigurationsSupportedObject>>,
     pub token_response: Option<TokenResponse>,
     pub credentials: Vec<serde_json::Value>,
+    /// The `transaction_id`s of the credentials whose issuance is deferred by the Credential Issuer.
+    #[serde(default)]
+    pub transaction_ids: Vec<String>,
 }
 
 impl View<Offer> for ReceivedOfferView {
@@ -45,9 +48,28 @@ impl View<Offer> for ReceivedOfferView {
                 self.status.clone_from(status);
                 self.credentials.clone_from(credentials);
             }
+            CredentialIssuanceDeferred {
+                credentials,
+                transaction_ids,
+                ..
+            } => {
+                self.credentials.clone_from(credentials);
+                self.transaction_ids.clone_from(transaction_ids);
+            }
+            DeferredCredentialReceived {
+                transaction_id,
+                credential,
+                ..
+            } => {
+                if let Some(index) = self.transaction_ids.iter().position(|id| id == transaction_id) {
+                    self.transaction_ids.remove(index);
+                }
+                self.credentials.push(credential.clone());
+            }
             CredentialOfferRejected { status, .. } => {
                 self.status.clone_from(status);
             }
+            CredentialNotificationSent { .. } => {}
         }
     }
 }
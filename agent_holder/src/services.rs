@@ -1,4 +1,4 @@
-use agent_secret_manager::service::Service;
+use agent_secret_manager::{service::Service, subject::Subject as SecretManagerSubject};
 use agent_shared::config::{config, get_all_enabled_did_methods, get_preferred_did_method};
 use jsonwebtoken::Algorithm;
 use oid4vc_core::{Subject, SubjectSyntaxType};
@@ -9,10 +9,14 @@ use std::sync::Arc;
 pub struct HolderServices {
     pub holder: Arc<dyn Subject>,
     pub wallet: Wallet,
+    /// HTTP client used for the requests to the Credential Issuer that are not covered by the `Wallet`.
+    pub client: reqwest::Client,
 }
 
 impl Service for HolderServices {
-    fn new(holder: Arc<dyn Subject>) -> Self {
+    fn new(holder: Arc<SecretManagerSubject>) -> Self {
+        let holder: Arc<dyn Subject> = holder;
+
         let signing_algorithms_supported: Vec<Algorithm> = config()
             .signing_algorithms_supported
             .iter()
@@ -43,6 +47,10 @@ impl Service for HolderServices {
         // TODO: make `Wallet::new` return `Wallet` instead of `Result<Self, _>`
         .expect("Failed to create wallet");
 
-        Self { holder, wallet }
+        Self {
+            holder,
+            wallet,
+            client: reqwest::Client::new(),
+        }
     }
 }
@@ -1,9 +1,11 @@
-use super::entity::Data;
+use super::entity::{Data, Validity};
+use super::{credential_schema, data_integrity, mdoc, sd_jwt, template};
 use crate::credential::command::CredentialCommand;
 use crate::credential::error::CredentialError::{self};
 use crate::credential::event::CredentialEvent;
 use crate::services::IssuanceServices;
-use agent_shared::config::{config, get_preferred_did_method, get_preferred_signing_algorithm};
+use crate::status_list::aggregate::credential_status;
+use agent_shared::config::{get_preferred_did_method, get_preferred_signing_algorithm, CredentialSchema};
 use async_trait::async_trait;
 use cqrs_es::Aggregate;
 use derivative::Derivative;
@@ -11,16 +13,19 @@ use identity_core::convert::FromJson;
 use identity_credential::credential::{
     Credential as W3CVerifiableCredential, CredentialBuilder as W3CVerifiableCredentialBuilder, Issuer,
 };
-use jsonwebtoken::Header;
-use oid4vc_core::jwt;
+use jsonwebtoken::{Algorithm, Header};
+use oid4vc_core::{jwt, Sign, Verify};
+use oid4vci::credential_format_profiles::iso_mdl::mso_mdoc::{MsoMdoc, MsoMdocParameters};
+use oid4vci::credential_format_profiles::sd_jwt_vc::{SdJwtVc, SdJwtVcParameters};
 use oid4vci::credential_format_profiles::w3c_verifiable_credentials::jwt_vc_json::{
     CredentialDefinition, JwtVcJson, JwtVcJsonParameters,
 };
+use oid4vci::credential_format_profiles::w3c_verifiable_credentials::ldp_vc::{LdpVc, LdpVcParameters};
+use oid4vci::credential_format_profiles::w3c_verifiable_credentials::CredentialDefinition as LdCredentialDefinition;
 use oid4vci::credential_format_profiles::{CredentialFormats, Parameters};
 use oid4vci::credential_issuer::credential_configurations_supported::CredentialConfigurationsSupportedObject;
-use oid4vci::VerifiableCredentialJwt;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Map, Value};
 use std::sync::Arc;
 use tracing::info;
 use types_ob_v3::prelude::{
@@ -33,6 +38,8 @@ use types_ob_v3::prelude::{
 pub struct Credential {
     data: Option<Data>,
     credential_configuration: CredentialConfigurationsSupportedObject,
+    selectively_disclosable_claims: Vec<String>,
+    status_list_index: Option<usize>,
     signed: Option<serde_json::Value>,
 }
 
@@ -47,17 +54,29 @@ impl Aggregate for Credential {
         "credential".to_string()
     }
 
-    async fn handle(&self, command: Self::Command, services: &Self::Services) -> Result<Vec<Self::Event>, Self::Error> {
+    async fn handle(
+        &self,
+        mut command: Self::Command,
+        services: &Self::Services,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
         use CredentialCommand::*;
         use CredentialError::*;
         use CredentialEvent::*;
 
         info!("Handling command: {:?}", command);
 
+        let properties = render_credential_data(&mut command, services)?;
+
         match command {
             CreateUnsignedCredential {
+                credential_id,
                 data,
                 credential_configuration,
+                selectively_disclosable_claims,
+                status_list_index,
+                validity,
+                credential_schema,
+                ..
             } => match &credential_configuration.credential_format {
                 CredentialFormats::JwtVcJson(Parameters::<JwtVcJson> {
                     parameters:
@@ -66,12 +85,11 @@ impl Aggregate for Credential {
                             ..
                         },
                 }) => {
-                    #[cfg(feature = "test_utils")]
-                    let issuance_date = "2010-01-01T00:00:00Z";
-                    #[cfg(not(feature = "test_utils"))]
-                    let issuance_date = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+                    let (issuance_date, expiration_date) = validity_window(&validity)?;
+
+                    let id = credential_id_uri(&credential_id);
 
-                    let name = config()
+                    let name = services
                         .display
                         .first()
                         .expect("Configuration `display.name` missing")
@@ -79,7 +97,7 @@ impl Aggregate for Credential {
                         .clone();
 
                     let issuer: Profile = ProfileBuilder::default()
-                        .id(config().url.clone())
+                        .id(services.url.clone())
                         .type_("Profile")
                         .name(name)
                         .try_into()
@@ -117,10 +135,17 @@ impl Aggregate for Credential {
                                 // Set the type to the original credential configuration type.
                                 let mut raw = json!(credential);
                                 raw["type"] = json!(type_);
+                                raw["id"] = json!(id);
+                                if let Some(expiration_date) = &expiration_date {
+                                    raw["expirationDate"] = json!(expiration_date);
+                                }
+                                add_properties(&mut raw, properties, &credential_schema);
 
                                 return Ok(vec![UnsignedCredentialCreated {
                                     data: Data { raw },
                                     credential_configuration,
+                                    selectively_disclosable_claims,
+                                    status_list_index,
                                 }]);
                             }
                             "AchievementCredential" | "OpenBadgeCredential" => {
@@ -145,18 +170,25 @@ impl Aggregate for Credential {
                                         "VerifiableCredential",
                                         &credential_format,
                                     ]))
-                                    // TODO: Come up with a way to get the credential id.
-                                    .id("http://example.com/credentials/3527")
+                                    .id(id.clone())
                                     .name(name)
                                     .issuer(issuer)
                                     .credential_subject(credential_subject)
-                                    .issuance_date(issuance_date)
+                                    .issuance_date(issuance_date.clone())
                                     .try_into()
                                     .map_err(InvalidVerifiableCredentialError)?;
 
+                                let mut raw = json!(credential);
+                                if let Some(expiration_date) = &expiration_date {
+                                    raw["expirationDate"] = json!(expiration_date);
+                                }
+                                add_properties(&mut raw, properties, &credential_schema);
+
                                 return Ok(vec![UnsignedCredentialCreated {
-                                    data: Data { raw: json!(credential) },
+                                    data: Data { raw },
                                     credential_configuration,
+                                    selectively_disclosable_claims,
+                                    status_list_index,
                                 }]);
                             }
                             _ => continue,
@@ -165,10 +197,118 @@ impl Aggregate for Credential {
 
                     Err(UnsupportedCredentialFormat)
                 }
+                CredentialFormats::SdJwtVc(Parameters::<SdJwtVc> {
+                    parameters: SdJwtVcParameters { vct, .. },
+                }) => {
+                    if let Some(claim_name) = selectively_disclosable_claims
+                        .iter()
+                        .find(|claim_name| sd_jwt::NON_SELECTIVELY_DISCLOSABLE_CLAIMS.contains(&claim_name.as_str()))
+                    {
+                        return Err(InvalidSelectivelyDisclosableClaimError(claim_name.clone()));
+                    }
+
+                    let credential_subject = data
+                        .raw
+                        .get("credentialSubject")
+                        .ok_or(MissingCredentialSubjectError)?
+                        .as_object()
+                        .ok_or(InvalidCredentialSubjectError("must be an object".to_string()))?;
+
+                    // SD-JWT VCs do not follow the W3C VC Data Model, so the claims of the `credentialSubject` are
+                    // placed at the top-level of the payload.
+                    let mut raw = credential_subject.clone();
+                    raw.insert("vct".to_string(), json!(vct));
+
+                    Ok(vec![UnsignedCredentialCreated {
+                        data: Data { raw: json!(raw) },
+                        credential_configuration,
+                        selectively_disclosable_claims,
+                        status_list_index,
+                    }])
+                }
+                CredentialFormats::MsoMdoc(Parameters::<MsoMdoc> {
+                    parameters: MsoMdocParameters { doctype, .. },
+                }) => {
+                    // The data elements of an mdoc are grouped by namespace, e.g. `org.iso.18013.5.1`.
+                    let name_spaces = data
+                        .raw
+                        .get("credentialSubject")
+                        .ok_or(MissingCredentialSubjectError)?
+                        .as_object()
+                        .ok_or(InvalidCredentialSubjectError("must be an object".to_string()))?;
+
+                    if let Some((name_space, _)) =
+                        name_spaces.iter().find(|(_, data_elements)| !data_elements.is_object())
+                    {
+                        return Err(InvalidCredentialSubjectError(format!(
+                            "namespace `{name_space}` must be an object"
+                        )));
+                    }
+
+                    // Unlike W3C Credentials, the Mobile Security Object of an mdoc always expires.
+                    let (valid_from, valid_until) = validity_window(&validity)?;
+                    let valid_until = valid_until.ok_or(InvalidValidityError(
+                        "an mdoc requires a `valid_until` or a `validity_period`".to_string(),
+                    ))?;
+
+                    Ok(vec![UnsignedCredentialCreated {
+                        data: Data {
+                            raw: json!({
+                                "docType": doctype,
+                                "nameSpaces": name_spaces,
+                                "validityInfo": {
+                                    "validFrom": valid_from,
+                                    "validUntil": valid_until,
+                                },
+                            }),
+                        },
+                        credential_configuration,
+                        selectively_disclosable_claims,
+                        status_list_index,
+                    }])
+                }
+                CredentialFormats::LdpVc(Parameters::<LdpVc> {
+                    parameters:
+                        LdpVcParameters {
+                            credential_definition: LdCredentialDefinition { context, type_, .. },
+                            ..
+                        },
+                }) => {
+                    let (issuance_date, expiration_date) = validity_window(&validity)?;
+
+                    let credential_subject = data.raw.get("credentialSubject").ok_or(MissingCredentialSubjectError)?;
+
+                    if !credential_subject.is_object() {
+                        return Err(InvalidCredentialSubjectError("must be an object".to_string()));
+                    }
+
+                    let mut raw = json!({
+                        "@context": context,
+                        "id": credential_id_uri(&credential_id),
+                        "type": type_,
+                        "issuanceDate": issuance_date,
+                        "credentialSubject": credential_subject,
+                    });
+                    if let Some(expiration_date) = expiration_date {
+                        raw["expirationDate"] = json!(expiration_date);
+                    }
+                    add_properties(&mut raw, properties, &credential_schema);
+
+                    Ok(vec![UnsignedCredentialCreated {
+                        data: Data { raw },
+                        credential_configuration,
+                        selectively_disclosable_claims,
+                        status_list_index,
+                    }])
+                }
                 _ => Err(UnsupportedCredentialFormat),
             },
             CreateSignedCredential { signed_credential } => Ok(vec![SignedCredentialCreated { signed_credential }]),
-            SignCredential { subject_id, overwrite } => {
+            SignCredential {
+                subject_id,
+                overwrite,
+                locales,
+            } => {
                 if self.signed.is_some() && !overwrite {
                     return Ok(vec![]);
                 }
@@ -180,11 +320,149 @@ impl Aggregate for Credential {
                     .identifier(&default_did_method.to_string(), get_preferred_signing_algorithm())
                     .await
                     .unwrap();
+
+                #[cfg(feature = "test_utils")]
+                let iat = 0;
+                #[cfg(not(feature = "test_utils"))]
+                let iat = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs() as i64;
+
+                if let CredentialFormats::SdJwtVc(_) = &self.credential_configuration.credential_format {
+                    let data = self.data.as_ref().ok_or(MissingCredentialDataError)?;
+                    let mut claims = data.raw.as_object().ok_or(InvalidCredentialError)?.clone();
+
+                    let disclosures = sd_jwt::conceal_claims(&mut claims, &self.selectively_disclosable_claims);
+
+                    claims.insert("iss".to_string(), json!(issuer_did));
+                    claims.insert("iat".to_string(), json!(iat));
+                    // Bind the credential to the public key of the holder.
+                    let holder_key_id = mdoc::holder_key_id(&subject_id)
+                        .ok_or_else(|| InvalidHolderKeyError(format!("unsupported DID method: {subject_id}")))?;
+                    let holder_public_key = services
+                        .issuer
+                        .public_key(&holder_key_id)
+                        .await
+                        .map_err(|e| InvalidHolderKeyError(e.to_string()))?;
+                    claims.insert(
+                        "cnf".to_string(),
+                        json!({ "jwk": sd_jwt::confirmation_jwk(&holder_public_key)? }),
+                    );
+
+                    let mut header = Header::new(get_preferred_signing_algorithm());
+                    header.typ = Some("vc+sd-jwt".to_string());
+
+                    let issuer_signed_jwt =
+                        jwt::encode(services.issuer.clone(), header, claims, &default_did_method.to_string())
+                            .await
+                            .map_err(|e| CredentialSigningError(e.to_string()))?;
+
+                    return Ok(vec![CredentialSigned {
+                        signed_credential: json!(sd_jwt::serialize(issuer_signed_jwt, disclosures)),
+                    }]);
+                }
+
+                if let CredentialFormats::MsoMdoc(Parameters::<MsoMdoc> {
+                    parameters: MsoMdocParameters { doctype, .. },
+                }) = &self.credential_configuration.credential_format
+                {
+                    let data = self.data.as_ref().ok_or(MissingCredentialDataError)?;
+                    let name_spaces = data.raw["nameSpaces"].as_object().ok_or(InvalidCredentialError)?;
+
+                    // The public key of the holder is bound to the mdoc as its `deviceKey`.
+                    let holder_key_id = mdoc::holder_key_id(&subject_id)
+                        .ok_or_else(|| InvalidDeviceKeyError(format!("unsupported DID method: {subject_id}")))?;
+                    let holder_public_key = services
+                        .issuer
+                        .public_key(&holder_key_id)
+                        .await
+                        .map_err(|e| InvalidDeviceKeyError(e.to_string()))?;
+                    let device_key = mdoc::cose_key(&holder_public_key)?;
+
+                    let (issuer_signed_name_spaces, value_digests) = mdoc::issuer_signed_name_spaces(name_spaces)?;
+
+                    let timestamp = |field: &str| {
+                        data.raw["validityInfo"][field]
+                            .as_str()
+                            .and_then(|timestamp| chrono::DateTime::parse_from_rfc3339(timestamp).ok())
+                            .map(|timestamp| timestamp.timestamp())
+                            .ok_or(InvalidCredentialError)
+                    };
+
+                    let mobile_security_object = mdoc::mobile_security_object(
+                        doctype,
+                        value_digests,
+                        device_key,
+                        iat,
+                        timestamp("validFrom")?,
+                        timestamp("validUntil")?,
+                    );
+
+                    let issuer_auth =
+                        mdoc::issuer_auth(&services.signer, &services.certificate_chain, &mobile_security_object)
+                            .await?;
+
+                    return Ok(vec![CredentialSigned {
+                        signed_credential: json!(mdoc::issuer_signed(issuer_signed_name_spaces, issuer_auth)),
+                    }]);
+                }
+
+                if let CredentialFormats::LdpVc(_) = &self.credential_configuration.credential_format {
+                    // The Data Integrity proof is always created using the EdDSA key, which is not necessarily the key
+                    // of the preferred signing algorithm.
+                    let verification_method = services
+                        .signer
+                        .verification_method(&default_did_method.to_string(), Algorithm::EdDSA)
+                        .await
+                        .ok_or(CredentialSigningError(
+                            "no EdDSA key is configured to sign `ldp_vc` Credentials".to_string(),
+                        ))?;
+                    let issuer_did = verification_method
+                        .split_once('#')
+                        .map_or(verification_method.as_str(), |(did, _)| did);
+
+                    let mut credential = self.data.as_ref().ok_or(MissingCredentialDataError)?.raw.clone();
+
+                    credential["issuer"] = json!(issuer_did);
+                    credential["credentialSubject"]["id"] = json!(subject_id);
+
+                    // The proof suite is advertised in the `credential_signing_alg_values_supported`.
+                    let proof_suite = self
+                        .credential_configuration
+                        .credential_signing_alg_values_supported
+                        .first()
+                        .and_then(|proof_suite| proof_suite.parse().ok())
+                        .unwrap_or_default();
+
+                    let created = chrono::DateTime::from_timestamp(iat, 0)
+                        .ok_or(CredentialSigningError("invalid timestamp".to_string()))?
+                        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+                    let signed_credential = data_integrity::add_proof(
+                        &services.signer,
+                        credential,
+                        proof_suite,
+                        verification_method,
+                        created,
+                    )
+                    .await?;
+
+                    return Ok(vec![CredentialSigned { signed_credential }]);
+                }
+
                 let signed_credential = {
                     let mut credential = self.data.as_ref().ok_or(MissingCredentialDataError)?.clone();
 
                     credential.raw["issuer"] = json!(issuer_did);
 
+                    // The name of an OpenBadge is localized according to the locales that are preferred by the Wallet.
+                    if credential.raw.get("name").is_some() && is_open_badge(&self.credential_configuration) {
+                        if let Some(name) = localized_name(&self.credential_configuration.display, &locales) {
+                            credential.raw["name"] = json!(name);
+                        }
+                    }
+
                     let credential_subject = credential.raw["credentialSubject"].as_object().unwrap().clone();
 
                     // Create a new Map and insert the id field first
@@ -199,30 +477,40 @@ impl Aggregate for Credential {
                     // Replace the original credentialSubject with the new map
                     credential.raw["credentialSubject"] = serde_json::Value::Object(new_credential_subject);
 
-                    #[cfg(feature = "test_utils")]
-                    let iat = 0;
-                    #[cfg(not(feature = "test_utils"))]
-                    let iat = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as i64;
+                    if let Some(status_list_index) = self.status_list_index {
+                        credential.raw["credentialStatus"] = credential_status(&services.url, status_list_index);
+                    }
+
+                    let timestamp = |property: &str| {
+                        credential.raw[property]
+                            .as_str()
+                            .and_then(|timestamp| chrono::DateTime::parse_from_rfc3339(timestamp).ok())
+                            .map(|timestamp| timestamp.timestamp())
+                    };
+
+                    // The validity of the JWT matches the validity of the credential. Credentials without an
+                    // `expirationDate` do not expire, so they are signed without `exp`.
+                    let mut claims = json!({
+                        "iss": issuer_did,
+                        "sub": subject_id,
+                    });
+                    if let Some(nbf) = timestamp("issuanceDate") {
+                        claims["nbf"] = json!(nbf);
+                    }
+                    if let Some(exp) = timestamp("expirationDate") {
+                        claims["exp"] = json!(exp);
+                    }
+                    claims["iat"] = json!(iat);
+                    claims["vc"] = credential.raw;
 
                     json!(jwt::encode(
                         services.issuer.clone(),
                         Header::new(get_preferred_signing_algorithm()),
-                        VerifiableCredentialJwt::builder()
-                            .sub(subject_id)
-                            .iss(issuer_did)
-                            .iat(iat)
-                            // TODO: find out whether this is a required field.
-                            .exp(9999999999i64)
-                            .verifiable_credential(credential.raw)
-                            .build()
-                            .ok(),
+                        claims,
                         &default_did_method.to_string()
                     )
                     .await
-                    .ok())
+                    .map_err(|e| CredentialSigningError(e.to_string()))?)
                 };
 
                 Ok(vec![CredentialSigned { signed_credential }])
@@ -239,9 +527,13 @@ impl Aggregate for Credential {
             UnsignedCredentialCreated {
                 data,
                 credential_configuration,
+                selectively_disclosable_claims,
+                status_list_index,
             } => {
                 self.data.replace(data);
                 self.credential_configuration = credential_configuration;
+                self.selectively_disclosable_claims = selectively_disclosable_claims;
+                self.status_list_index = status_list_index;
             }
             SignedCredentialCreated { signed_credential } => {
                 self.signed.replace(signed_credential);
@@ -253,12 +545,146 @@ impl Aggregate for Credential {
     }
 }
 
+/// Returns the URI that is used as the `id` of the credential with the given `credential_id`.
+fn credential_id_uri(credential_id: &str) -> String {
+    format!("urn:uuid:{credential_id}")
+}
+
+/// Returns `true` when the Credential Configuration describes an OpenBadge, i.e. a `jwt_vc_json` Credential of type
+/// `AchievementCredential` or `OpenBadgeCredential`.
+fn is_open_badge(credential_configuration: &CredentialConfigurationsSupportedObject) -> bool {
+    match &credential_configuration.credential_format {
+        CredentialFormats::JwtVcJson(Parameters::<JwtVcJson> {
+            parameters:
+                JwtVcJsonParameters {
+                    credential_definition: CredentialDefinition { type_, .. },
+                    ..
+                },
+        }) => type_
+            .iter()
+            .any(|type_| type_ == "AchievementCredential" || type_ == "OpenBadgeCredential"),
+        _ => false,
+    }
+}
+
+/// Returns the `name` of the display whose `locale` matches the first supported locale in `locales`, either exactly or
+/// by its primary language subtag (e.g. `de` for `de-CH`).
+fn localized_name(display: &[Value], locales: &[String]) -> Option<String> {
+    let primary_language = |locale: &str| locale.split('-').next().unwrap_or(locale).to_lowercase();
+    let display_locale = |display: &Value| display.get("locale").and_then(Value::as_str).map(ToString::to_string);
+
+    locales
+        .iter()
+        .find_map(|locale| {
+            display
+                .iter()
+                .find(|display| display_locale(display).is_some_and(|other| other.eq_ignore_ascii_case(locale)))
+                .or_else(|| {
+                    display.iter().find(|display| {
+                        display_locale(display)
+                            .is_some_and(|other| primary_language(&other) == primary_language(locale))
+                    })
+                })
+        })
+        .and_then(|display| display.get("name"))
+        .and_then(Value::as_str)
+        .map(ToString::to_string)
+}
+
+/// Maps the input record of a `CreateUnsignedCredential` command onto the credential when a template is used and
+/// validates the `credentialSubject` against the JSON Schema before the credential is built, so that every issue can be
+/// reported per field. Returns the top-level properties of the template.
+fn render_credential_data(
+    command: &mut CredentialCommand,
+    services: &IssuanceServices,
+) -> Result<Map<String, Value>, CredentialError> {
+    let mut properties = Map::new();
+
+    if let CredentialCommand::CreateUnsignedCredential {
+        data,
+        credential_schema,
+        credential_template,
+        ..
+    } = command
+    {
+        if let Some(credential_template) = credential_template {
+            (*data, properties) = template::render(credential_template, &data.raw)?;
+        }
+
+        if let Some(credential_schema) = credential_schema {
+            let credential_subject = data
+                .raw
+                .get("credentialSubject")
+                .ok_or(CredentialError::MissingCredentialSubjectError)?;
+            services
+                .credential_schemas
+                .validate(credential_schema, credential_subject)?;
+        }
+    }
+
+    Ok(properties)
+}
+
+/// Adds the top-level `properties` of a credential template and the `credentialSchema` to an unsigned W3C credential.
+fn add_properties(raw: &mut Value, properties: Map<String, Value>, credential_schema: &Option<CredentialSchema>) {
+    if let Some(raw) = raw.as_object_mut() {
+        raw.extend(properties);
+    }
+
+    if let Some(credential_schema) = credential_schema {
+        raw["credentialSchema"] = credential_schema::credential_schema_property(credential_schema);
+    }
+}
+
+/// Returns the `issuanceDate` and the optional `expirationDate` of a credential based on its `validity`.
+fn validity_window(validity: &Validity) -> Result<(String, Option<String>), CredentialError> {
+    use CredentialError::InvalidValidityError;
+
+    let parse = |timestamp: &str| {
+        chrono::DateTime::parse_from_rfc3339(timestamp)
+            .map(|timestamp| timestamp.with_timezone(&chrono::Utc))
+            .map_err(|e| InvalidValidityError(format!("`{timestamp}` is not a valid RFC 3339 timestamp: {e}")))
+    };
+
+    let valid_from = match &validity.valid_from {
+        Some(valid_from) => parse(valid_from)?,
+        #[cfg(feature = "test_utils")]
+        None => parse("2010-01-01T00:00:00Z")?,
+        #[cfg(not(feature = "test_utils"))]
+        None => chrono::Utc::now(),
+    };
+
+    let valid_until = match (&validity.valid_until, validity.validity_period) {
+        (Some(valid_until), _) => Some(parse(valid_until)?),
+        (None, Some(validity_period)) => Some(
+            i64::try_from(validity_period)
+                .ok()
+                .and_then(chrono::Duration::try_seconds)
+                .and_then(|validity_period| valid_from.checked_add_signed(validity_period))
+                .ok_or(InvalidValidityError("`validity_period` is too large".to_string()))?,
+        ),
+        (None, None) => None,
+    };
+
+    if valid_until.is_some_and(|valid_until| valid_until <= valid_from) {
+        return Err(InvalidValidityError(
+            "`valid_until` must be later than `valid_from`".to_string(),
+        ));
+    }
+
+    let format =
+        |timestamp: chrono::DateTime<chrono::Utc>| timestamp.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+    Ok((format(valid_from), valid_until.map(format)))
+}
+
 #[cfg(test)]
 pub mod credential_tests {
     use super::test_utils::*;
     use super::*;
 
     use agent_secret_manager::service::Service;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
     use jsonwebtoken::Algorithm;
 
     use rstest::rstest;
@@ -272,6 +698,8 @@ pub mod credential_tests {
 
     type CredentialTestFramework = TestFramework<Credential>;
 
+    const CREDENTIAL_ID: &str = "00000000-0000-0000-0000-000000000000";
+
     #[rstest]
     #[case::openbadges(
         OPENBADGE_CREDENTIAL_SUBJECT.clone(),
@@ -287,21 +715,329 @@ pub mod credential_tests {
     fn test_create_unsigned_credential(
         #[case] credential_subject: serde_json::Value,
         #[case] credential_configuration: CredentialConfigurationsSupportedObject,
-        #[case] unsigned_credential: serde_json::Value,
+        #[case] mut unsigned_credential: serde_json::Value,
     ) {
+        unsigned_credential["id"] = json!(format!("urn:uuid:{CREDENTIAL_ID}"));
+
         CredentialTestFramework::with(Service::default())
             .given_no_previous_events()
             .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
                 data: Data {
                     raw: credential_subject,
                 },
                 credential_configuration: credential_configuration.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+                validity: Default::default(),
+                credential_schema: None,
+                credential_template: None,
             })
             .then_expect_events(vec![CredentialEvent::UnsignedCredentialCreated {
                 data: Data {
                     raw: unsigned_credential,
                 },
                 credential_configuration,
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+            }])
+    }
+
+    #[rstest]
+    #[case::validity_period(
+        Validity { validity_period: Some(3600), ..Default::default() },
+        "2010-01-01T00:00:00Z",
+        "2010-01-01T01:00:00Z"
+    )]
+    #[case::valid_until_overrides_validity_period(
+        Validity {
+            valid_from: Some("2024-01-01T00:00:00Z".to_string()),
+            valid_until: Some("2025-01-01T00:00:00Z".to_string()),
+            validity_period: Some(3600),
+        },
+        "2024-01-01T00:00:00Z",
+        "2025-01-01T00:00:00Z"
+    )]
+    #[serial_test::serial]
+    fn test_create_unsigned_credential_with_validity(
+        #[case] validity: Validity,
+        #[case] issuance_date: &str,
+        #[case] expiration_date: &str,
+    ) {
+        let mut unsigned_credential = UNSIGNED_W3C_VC_CREDENTIAL.clone();
+        unsigned_credential["id"] = json!(format!("urn:uuid:{CREDENTIAL_ID}"));
+        unsigned_credential["issuanceDate"] = json!(issuance_date);
+        unsigned_credential["expirationDate"] = json!(expiration_date);
+
+        CredentialTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
+                data: Data {
+                    raw: W3C_VC_CREDENTIAL_SUBJECT.clone(),
+                },
+                credential_configuration: W3C_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+                validity,
+                credential_schema: None,
+                credential_template: None,
+            })
+            .then_expect_events(vec![CredentialEvent::UnsignedCredentialCreated {
+                data: Data {
+                    raw: unsigned_credential,
+                },
+                credential_configuration: W3C_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+            }])
+    }
+
+    #[rstest]
+    #[case::valid_until_before_valid_from(
+        Validity { valid_until: Some("2000-01-01T00:00:00Z".to_string()), ..Default::default() },
+        "The validity period of the credential is invalid: `valid_until` must be later than `valid_from`"
+    )]
+    #[case::validity_period_overflow(
+        Validity { validity_period: Some(u64::MAX), ..Default::default() },
+        "The validity period of the credential is invalid: `validity_period` is too large"
+    )]
+    #[serial_test::serial]
+    fn test_create_unsigned_credential_with_invalid_validity(#[case] validity: Validity, #[case] error_message: &str) {
+        CredentialTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
+                data: Data {
+                    raw: W3C_VC_CREDENTIAL_SUBJECT.clone(),
+                },
+                credential_configuration: W3C_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+                validity,
+                credential_schema: None,
+                credential_template: None,
+            })
+            .then_expect_error_message(error_message)
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_create_unsigned_sd_jwt_vc_credential() {
+        CredentialTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
+                data: Data {
+                    raw: W3C_VC_CREDENTIAL_SUBJECT.clone(),
+                },
+                credential_configuration: SD_JWT_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec!["first_name".to_string(), "last_name".to_string()],
+                status_list_index: None,
+                validity: Default::default(),
+                credential_schema: None,
+                credential_template: None,
+            })
+            .then_expect_events(vec![CredentialEvent::UnsignedCredentialCreated {
+                data: Data {
+                    raw: UNSIGNED_SD_JWT_VC_CREDENTIAL.clone(),
+                },
+                credential_configuration: SD_JWT_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec!["first_name".to_string(), "last_name".to_string()],
+                status_list_index: None,
+            }])
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_create_unsigned_mdoc_credential() {
+        CredentialTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
+                data: Data {
+                    raw: MDOC_CREDENTIAL_SUBJECT.clone(),
+                },
+                credential_configuration: MDOC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+                validity: MDOC_VALIDITY.clone(),
+                credential_schema: None,
+                credential_template: None,
+            })
+            .then_expect_events(vec![CredentialEvent::UnsignedCredentialCreated {
+                data: Data {
+                    raw: UNSIGNED_MDOC_CREDENTIAL.clone(),
+                },
+                credential_configuration: MDOC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+            }])
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_create_unsigned_mdoc_credential_without_validity() {
+        CredentialTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
+                data: Data {
+                    raw: MDOC_CREDENTIAL_SUBJECT.clone(),
+                },
+                credential_configuration: MDOC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+                validity: Default::default(),
+                credential_schema: None,
+                credential_template: None,
+            })
+            .then_expect_error_message(
+                "The validity period of the credential is invalid: an mdoc requires a `valid_until` or a `validity_period`",
+            )
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_create_unsigned_ldp_vc_credential() {
+        CredentialTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
+                data: Data {
+                    raw: W3C_VC_CREDENTIAL_SUBJECT.clone(),
+                },
+                credential_configuration: LDP_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+                validity: Default::default(),
+                credential_schema: None,
+                credential_template: None,
+            })
+            .then_expect_events(vec![CredentialEvent::UnsignedCredentialCreated {
+                data: Data {
+                    raw: UNSIGNED_LDP_VC_CREDENTIAL.clone(),
+                },
+                credential_configuration: LDP_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+            }])
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_create_unsigned_sd_jwt_vc_credential_with_invalid_selectively_disclosable_claim() {
+        CredentialTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
+                data: Data {
+                    raw: W3C_VC_CREDENTIAL_SUBJECT.clone(),
+                },
+                credential_configuration: SD_JWT_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec!["cnf".to_string()],
+                status_list_index: None,
+                validity: Default::default(),
+                credential_schema: None,
+                credential_template: None,
+            })
+            .then_expect_error_message("The claim `cnf` cannot be selectively disclosed")
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_create_unsigned_credential_with_credential_schema() {
+        let mut unsigned_credential = UNSIGNED_W3C_VC_CREDENTIAL.clone();
+        unsigned_credential["id"] = json!(format!("urn:uuid:{CREDENTIAL_ID}"));
+        unsigned_credential["credentialSchema"] = json!({
+            "id": "https://example.com/schemas/degree.json",
+            "type": "JsonSchema"
+        });
+
+        CredentialTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
+                data: Data {
+                    raw: W3C_VC_CREDENTIAL_SUBJECT.clone(),
+                },
+                credential_configuration: W3C_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+                validity: Default::default(),
+                credential_schema: Some(DEGREE_CREDENTIAL_SCHEMA.clone()),
+                credential_template: None,
+            })
+            .then_expect_events(vec![CredentialEvent::UnsignedCredentialCreated {
+                data: Data {
+                    raw: unsigned_credential,
+                },
+                credential_configuration: W3C_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+            }])
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_create_unsigned_credential_with_credential_schema_violation() {
+        CredentialTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
+                data: Data {
+                    raw: json!({
+                        "credentialSubject": {
+                            "first_name": "Ferris",
+                            "last_name": "Rustacean"
+                        }
+                    }),
+                },
+                credential_configuration: W3C_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+                validity: Default::default(),
+                credential_schema: Some(DEGREE_CREDENTIAL_SCHEMA.clone()),
+                credential_template: None,
+            })
+            .then_expect_error_message(
+                "The `credentialSubject` does not conform to the JSON Schema: \"degree\" is a required property",
+            )
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_create_unsigned_credential_with_credential_template() {
+        let mut unsigned_credential = UNSIGNED_W3C_VC_CREDENTIAL.clone();
+        unsigned_credential["id"] = json!(format!("urn:uuid:{CREDENTIAL_ID}"));
+        unsigned_credential["name"] = json!("Degree Credential");
+
+        CredentialTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(CredentialCommand::CreateUnsignedCredential {
+                credential_id: CREDENTIAL_ID.to_string(),
+                data: Data {
+                    raw: json!({
+                        "given_name": "Ferris",
+                        "surname": "Rustacean",
+                        "degree_type": "MasterDegree",
+                        "programme": "Master of Oceanography"
+                    }),
+                },
+                credential_configuration: W3C_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
+                validity: Default::default(),
+                credential_schema: None,
+                credential_template: Some(DEGREE_CREDENTIAL_TEMPLATE.clone()),
+            })
+            .then_expect_events(vec![CredentialEvent::UnsignedCredentialCreated {
+                data: Data {
+                    raw: unsigned_credential,
+                },
+                credential_configuration: W3C_VC_CREDENTIAL_CONFIGURATION.clone(),
+                selectively_disclosable_claims: vec![],
+                status_list_index: None,
             }])
     }
 
@@ -322,26 +1058,309 @@ pub mod credential_tests {
         #[case] credential_configuration: CredentialConfigurationsSupportedObject,
         #[case] verifiable_credential_jwt: String,
     ) {
-        CredentialTestFramework::with(Service::default())
-            .given(vec![CredentialEvent::UnsignedCredentialCreated {
-                data: Data {
-                    raw: unsigned_credential,
+        let mut credential = Credential::default();
+        credential.apply(CredentialEvent::UnsignedCredentialCreated {
+            data: Data {
+                raw: unsigned_credential,
+            },
+            credential_configuration,
+            selectively_disclosable_claims: vec![],
+            status_list_index: None,
+        });
+
+        let events = credential
+            .handle(
+                CredentialCommand::SignCredential {
+                    subject_id: SUBJECT_KEY_DID.identifier("did:key", Algorithm::EdDSA).await.unwrap(),
+                    overwrite: false,
+                    locales: vec![],
                 },
-                credential_configuration,
-            }])
-            .when(CredentialCommand::SignCredential {
-                subject_id: SUBJECT_KEY_DID.identifier("did:key", Algorithm::EdDSA).await.unwrap(),
-                overwrite: false,
-            })
-            .then_expect_events(vec![CredentialEvent::CredentialSigned {
-                signed_credential: json!(verifiable_credential_jwt),
-            }])
+                &Service::default(),
+            )
+            .await
+            .unwrap();
+
+        let CredentialEvent::CredentialSigned {
+            signed_credential: serde_json::Value::String(signed_credential),
+        } = &events[0]
+        else {
+            panic!("Expected a signed JWT");
+        };
+
+        let (header, claims) = decode_jwt(signed_credential);
+        let (expected_header, mut expected_claims) = decode_jwt(&verifiable_credential_jwt);
+
+        // The credentials have no `expirationDate`, so they do not expire, but they are not valid before their
+        // `issuanceDate`.
+        expected_claims.as_object_mut().unwrap().remove("exp");
+        expected_claims["nbf"] = json!(1262304000);
+
+        assert_eq!(header, expected_header);
+        assert_eq!(claims, expected_claims);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_sign_credential_with_expiration_date() {
+        let mut unsigned_credential = UNSIGNED_W3C_VC_CREDENTIAL.clone();
+        unsigned_credential["expirationDate"] = json!("2011-01-01T00:00:00Z");
+
+        let mut credential = Credential::default();
+        credential.apply(CredentialEvent::UnsignedCredentialCreated {
+            data: Data {
+                raw: unsigned_credential,
+            },
+            credential_configuration: W3C_VC_CREDENTIAL_CONFIGURATION.clone(),
+            selectively_disclosable_claims: vec![],
+            status_list_index: None,
+        });
+
+        let events = credential
+            .handle(
+                CredentialCommand::SignCredential {
+                    subject_id: SUBJECT_KEY_DID.identifier("did:key", Algorithm::EdDSA).await.unwrap(),
+                    overwrite: false,
+                    locales: vec![],
+                },
+                &Service::default(),
+            )
+            .await
+            .unwrap();
+
+        let CredentialEvent::CredentialSigned {
+            signed_credential: serde_json::Value::String(signed_credential),
+        } = &events[0]
+        else {
+            panic!("Expected a signed JWT");
+        };
+
+        let (_, claims) = decode_jwt(signed_credential);
+        assert_eq!(claims["nbf"], json!(1262304000));
+        assert_eq!(claims["exp"], json!(1293840000));
+    }
+
+    fn decode_jwt(jwt: &str) -> (serde_json::Value, serde_json::Value) {
+        let mut parts = jwt
+            .split('.')
+            .map(|part| serde_json::from_slice(&URL_SAFE_NO_PAD.decode(part).unwrap()).unwrap());
+        (parts.next().unwrap(), parts.next().unwrap())
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_sign_sd_jwt_vc_credential() {
+        let mut credential = Credential::default();
+        credential.apply(CredentialEvent::UnsignedCredentialCreated {
+            data: Data {
+                raw: UNSIGNED_SD_JWT_VC_CREDENTIAL.clone(),
+            },
+            credential_configuration: SD_JWT_VC_CREDENTIAL_CONFIGURATION.clone(),
+            selectively_disclosable_claims: vec!["first_name".to_string(), "last_name".to_string()],
+            status_list_index: None,
+        });
+
+        let subject_id = SUBJECT_KEY_DID.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+        let events = credential
+            .handle(
+                CredentialCommand::SignCredential {
+                    subject_id: subject_id.clone(),
+                    overwrite: false,
+                    locales: vec![],
+                },
+                &Arc::new(IssuanceServices::default()),
+            )
+            .await
+            .unwrap();
+
+        let CredentialEvent::CredentialSigned {
+            signed_credential: serde_json::Value::String(sd_jwt),
+        } = &events[0]
+        else {
+            panic!("Expected a signed SD-JWT");
+        };
+
+        // An SD-JWT consists of the Issuer-signed JWT followed by the Disclosures, all separated by a `~`.
+        let mut parts: Vec<&str> = sd_jwt.split('~').collect();
+        assert_eq!(parts.pop(), Some(""));
+        let issuer_signed_jwt = parts.remove(0);
+        let disclosures = parts;
+        assert_eq!(disclosures.len(), 2);
+
+        let decode = |part: &str| -> serde_json::Value {
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(part).unwrap()).unwrap()
+        };
+
+        let header = decode(issuer_signed_jwt.split('.').next().unwrap());
+        assert_eq!(header["typ"], "vc+sd-jwt");
+
+        let payload = decode(issuer_signed_jwt.split('.').nth(1).unwrap());
+        assert_eq!(payload["vct"], "https://example.com/credentials/degree");
+        assert_eq!(payload["degree"], UNSIGNED_SD_JWT_VC_CREDENTIAL["degree"]);
+        let holder_public_key = IssuanceServices::default()
+            .issuer
+            .public_key(&mdoc::holder_key_id(&subject_id).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            payload["cnf"],
+            json!({ "jwk": { "kty": "OKP", "crv": "Ed25519", "x": URL_SAFE_NO_PAD.encode(holder_public_key) } })
+        );
+        assert_eq!(payload["_sd_alg"], "sha-256");
+        assert!(payload.get("first_name").is_none());
+        assert!(payload.get("last_name").is_none());
+
+        // Every Disclosure must be referenced by its digest in the `_sd` array.
+        let digests = payload["_sd"].as_array().unwrap();
+        for disclosure in disclosures {
+            assert!(digests.contains(&json!(sd_jwt::digest(disclosure))));
+
+            let [_salt, claim_name, claim_value] =
+                serde_json::from_value::<[serde_json::Value; 3]>(decode(disclosure)).unwrap();
+            assert_eq!(UNSIGNED_SD_JWT_VC_CREDENTIAL[claim_name.as_str().unwrap()], claim_value);
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_sign_mdoc_credential() {
+        use agent_secret_manager::{secret_manager_from_config, subject::Subject as SecretManagerSubject};
+        use agent_shared::config::SecretManagerConfig;
+        use ciborium::Value as Cbor;
+
+        let mut credential = Credential::default();
+        credential.apply(CredentialEvent::UnsignedCredentialCreated {
+            data: Data {
+                raw: UNSIGNED_MDOC_CREDENTIAL.clone(),
+            },
+            credential_configuration: MDOC_CREDENTIAL_CONFIGURATION.clone(),
+            selectively_disclosable_claims: vec![],
+            status_list_index: None,
+        });
+
+        // The Mobile Security Object is signed with an ES256 key, which the default test stronghold does not contain.
+        let signer = SecretManagerSubject {
+            secret_manager: Arc::new(tokio::sync::Mutex::new(
+                secret_manager_from_config(SecretManagerConfig {
+                    stronghold_path: "../agent_secret_manager/tests/res/all_slots.stronghold".to_string(),
+                    stronghold_password: "sup3rSecr3t".to_string(),
+                    issuer_eddsa_key_id: Some("ed25519-0".to_string()),
+                    issuer_es256_key_id: Some("es256-0".to_string()),
+                    issuer_did: Some("did:foo:bar".to_string()),
+                    issuer_fragment: Some("0".to_string()),
+                    issuer_certificate_chain_path: None,
+                })
+                .await,
+            )),
+        };
+        // The content of the certificates is not inspected when signing.
+        let certificate_chain = vec![b"document signer certificate".to_vec(), b"iaca certificate".to_vec()];
+
+        let services = IssuanceServices {
+            signer: Arc::new(signer),
+            certificate_chain: certificate_chain.clone(),
+            ..Arc::into_inner(IssuanceServices::default()).unwrap()
+        };
+
+        let events = credential
+            .handle(
+                CredentialCommand::SignCredential {
+                    subject_id: SUBJECT_KEY_DID.identifier("did:key", Algorithm::EdDSA).await.unwrap(),
+                    overwrite: false,
+                    locales: vec![],
+                },
+                &Arc::new(services),
+            )
+            .await
+            .unwrap();
+
+        let CredentialEvent::CredentialSigned {
+            signed_credential: serde_json::Value::String(issuer_signed),
+        } = &events[0]
+        else {
+            panic!("Expected a base64url encoded `IssuerSigned` structure");
+        };
+
+        let decode = |bytes: &[u8]| -> Cbor { ciborium::from_reader(bytes).unwrap() };
+        let entry = |map: &Cbor, key: &str| -> Cbor {
+            map.as_map()
+                .unwrap()
+                .iter()
+                .find(|(k, _)| k.as_text() == Some(key))
+                .map(|(_, value)| value.clone())
+                .unwrap()
+        };
+
+        let issuer_signed = decode(&URL_SAFE_NO_PAD.decode(issuer_signed).unwrap());
+        let name_spaces = entry(&issuer_signed, "nameSpaces");
+        assert_eq!(entry(&name_spaces, "org.iso.18013.5.1").as_array().unwrap().len(), 3);
+
+        let issuer_auth = entry(&issuer_signed, "issuerAuth");
+        let [protected, unprotected, payload, _signature] = issuer_auth.as_array().unwrap().as_slice() else {
+            panic!("Expected a COSE_Sign1 structure");
+        };
+
+        assert_eq!(
+            decode(protected.as_bytes().unwrap()),
+            Cbor::Map(vec![(Cbor::Integer(1.into()), Cbor::Integer((-7).into()))])
+        );
+        // The certificate chain is included as the `x5chain` in the unprotected header.
+        assert_eq!(
+            unprotected,
+            &Cbor::Map(vec![(
+                Cbor::Integer(33.into()),
+                Cbor::Array(certificate_chain.into_iter().map(Cbor::Bytes).collect())
+            )])
+        );
+
+        let Cbor::Tag(24, mobile_security_object_bytes) = decode(payload.as_bytes().unwrap()) else {
+            panic!("Expected `MobileSecurityObjectBytes`");
+        };
+        let mobile_security_object = decode(mobile_security_object_bytes.as_bytes().unwrap());
+        assert_eq!(
+            entry(&mobile_security_object, "docType"),
+            Cbor::Text("org.iso.18013.5.1.mDL".to_string())
+        );
+
+        // The validity of the mdoc is based on the `validity_period` of the Credential Configuration.
+        let validity_info = entry(&mobile_security_object, "validityInfo");
+        let tdate = |timestamp: &str| Cbor::Tag(0, Box::new(Cbor::Text(timestamp.to_string())));
+        assert_eq!(entry(&validity_info, "signed"), tdate("1970-01-01T00:00:00Z"));
+        assert_eq!(entry(&validity_info, "validFrom"), tdate("2010-01-01T00:00:00Z"));
+        assert_eq!(entry(&validity_info, "validUntil"), tdate("2011-01-01T00:00:00Z"));
+    }
+
+    #[rstest]
+    #[case::exact(&["de-CH", "en"], Some("Abzeichen (CH)"))]
+    #[case::primary_language(&["de-AT"], Some("Abzeichen"))]
+    #[case::order_of_preference(&["fr", "en"], Some("Badge"))]
+    #[case::unsupported(&["fr"], None)]
+    #[case::no_locales(&[], None)]
+    fn test_localized_name(#[case] locales: &[&str], #[case] name: Option<&str>) {
+        let display = vec![
+            json!({ "name": "Badge", "locale": "en" }),
+            json!({ "name": "Abzeichen", "locale": "de" }),
+            json!({ "name": "Abzeichen (CH)", "locale": "de-CH" }),
+        ];
+        let locales: Vec<String> = locales.iter().map(ToString::to_string).collect();
+
+        assert_eq!(localized_name(&display, &locales).as_deref(), name);
+    }
+
+    #[test]
+    fn test_is_open_badge() {
+        assert!(is_open_badge(&OPENBADGE_CREDENTIAL_CONFIGURATION));
+        assert!(!is_open_badge(&W3C_VC_CREDENTIAL_CONFIGURATION));
+        assert!(!is_open_badge(&SD_JWT_VC_CREDENTIAL_CONFIGURATION));
     }
 }
 
 #[cfg(feature = "test_utils")]
 pub mod test_utils {
     use super::*;
+    use agent_secret_manager::service::Service;
+    use agent_shared::config::{CredentialSchema, CredentialTemplate};
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
     use jsonwebtoken::Algorithm;
     use lazy_static::lazy_static;
     use oid4vci::{
@@ -354,6 +1373,35 @@ pub mod test_utils {
     use serde_json::json;
     use std::collections::HashMap;
 
+    /// Returns the `jwt` as it is signed by UniCore, i.e. including the unique `id` of the credential and the
+    /// `credentialStatus` that refers to the status lists.
+    pub async fn credential_jwt_signed_by_unicore(jwt: &str, credential_id: &str) -> String {
+        let issuance_services: Arc<IssuanceServices> = Service::default();
+
+        let payload = jwt.split('.').nth(1).unwrap();
+        let mut claims: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap();
+        claims["vc"]["id"] = json!(credential_id);
+        claims["vc"]["credentialStatus"] = credential_status(&issuance_services.url, 0);
+
+        // The JWT is valid from the `issuanceDate` of the credential and does not expire.
+        let claims = json!({
+            "iss": claims["iss"],
+            "sub": claims["sub"],
+            "nbf": 1262304000,
+            "iat": claims["iat"],
+            "vc": claims["vc"],
+        });
+
+        jwt::encode(
+            issuance_services.issuer.clone(),
+            Header::new(get_preferred_signing_algorithm()),
+            claims,
+            &get_preferred_did_method().to_string(),
+        )
+        .await
+        .unwrap()
+    }
+
     pub const OPENBADGE_VERIFIABLE_CREDENTIAL_JWT: &str = "eyJ0eXAiOiJKV1QiLCJhbGciOiJFZERTQSIsImtpZCI6ImRpZDprZXk6ejZNa2dFODROQ01wTWVBeDlqSzljZjVXNEc4Z2NaOXh1d0p2RzFlN3dOazhLQ2d0I3o2TWtnRTg0TkNNcE1lQXg5aks5Y2Y1VzRHOGdjWjl4dXdKdkcxZTd3Tms4S0NndCJ9.eyJpc3MiOiJkaWQ6a2V5Ono2TWtnRTg0TkNNcE1lQXg5aks5Y2Y1VzRHOGdjWjl4dXdKdkcxZTd3Tms4S0NndCIsInN1YiI6ImRpZDprZXk6ejZNa2dFODROQ01wTWVBeDlqSzljZjVXNEc4Z2NaOXh1d0p2RzFlN3dOazhLQ2d0IiwiZXhwIjo5OTk5OTk5OTk5LCJpYXQiOjAsInZjIjp7IkBjb250ZXh0IjpbImh0dHBzOi8vd3d3LnczLm9yZy8yMDE4L2NyZWRlbnRpYWxzL3YxIiwiaHR0cHM6Ly9wdXJsLmltc2dsb2JhbC5vcmcvc3BlYy9vYi92M3AwL2NvbnRleHQtMy4wLjIuanNvbiJdLCJpZCI6Imh0dHA6Ly9leGFtcGxlLmNvbS9jcmVkZW50aWFscy8zNTI3IiwidHlwZSI6WyJWZXJpZmlhYmxlQ3JlZGVudGlhbCIsIk9wZW5CYWRnZUNyZWRlbnRpYWwiXSwiaXNzdWVyIjoiZGlkOmtleTp6Nk1rZ0U4NE5DTXBNZUF4OWpLOWNmNVc0RzhnY1o5eHV3SnZHMWU3d05rOEtDZ3QiLCJpc3N1YW5jZURhdGUiOiIyMDEwLTAxLTAxVDAwOjAwOjAwWiIsIm5hbWUiOiJUZWFtd29yayBCYWRnZSIsImNyZWRlbnRpYWxTdWJqZWN0Ijp7ImlkIjoiZGlkOmtleTp6Nk1rZ0U4NE5DTXBNZUF4OWpLOWNmNVc0RzhnY1o5eHV3SnZHMWU3d05rOEtDZ3QiLCJ0eXBlIjpbIkFjaGlldmVtZW50U3ViamVjdCJdLCJhY2hpZXZlbWVudCI6eyJpZCI6Imh0dHBzOi8vZXhhbXBsZS5jb20vYWNoaWV2ZW1lbnRzLzIxc3QtY2VudHVyeS1za2lsbHMvdGVhbXdvcmsiLCJ0eXBlIjoiQWNoaWV2ZW1lbnQiLCJjcml0ZXJpYSI6eyJuYXJyYXRpdmUiOiJUZWFtIG1lbWJlcnMgYXJlIG5vbWluYXRlZCBmb3IgdGhpcyBiYWRnZSBieSB0aGVpciBwZWVycyBhbmQgcmVjb2duaXplZCB1cG9uIHJldmlldyBieSBFeGFtcGxlIENvcnAgbWFuYWdlbWVudC4ifSwiZGVzY3JpcHRpb24iOiJUaGlzIGJhZGdlIHJlY29nbml6ZXMgdGhlIGRldmVsb3BtZW50IG9mIHRoZSBjYXBhY2l0eSB0byBjb2xsYWJvcmF0ZSB3aXRoaW4gYSBncm91cCBlbnZpcm9ubWVudC4iLCJuYW1lIjoiVGVhbXdvcmsifX19fQ.SkC7IvpBGB9e98eobnE9qcLjs-yoZup3cieBla3DRTlcRezXEDPv4YRoUgffho9LJ0rkmfFPsPwb-owXMWyPAA";
 
     pub const W3C_VC_VERIFIABLE_CREDENTIAL_JWT: &str = "eyJ0eXAiOiJKV1QiLCJhbGciOiJFZERTQSIsImtpZCI6ImRpZDprZXk6ejZNa2dFODROQ01wTWVBeDlqSzljZjVXNEc4Z2NaOXh1d0p2RzFlN3dOazhLQ2d0I3o2TWtnRTg0TkNNcE1lQXg5aks5Y2Y1VzRHOGdjWjl4dXdKdkcxZTd3Tms4S0NndCJ9.eyJpc3MiOiJkaWQ6a2V5Ono2TWtnRTg0TkNNcE1lQXg5aks5Y2Y1VzRHOGdjWjl4dXdKdkcxZTd3Tms4S0NndCIsInN1YiI6ImRpZDprZXk6ejZNa2dFODROQ01wTWVBeDlqSzljZjVXNEc4Z2NaOXh1d0p2RzFlN3dOazhLQ2d0IiwiZXhwIjo5OTk5OTk5OTk5LCJpYXQiOjAsInZjIjp7IkBjb250ZXh0IjoiaHR0cHM6Ly93d3cudzMub3JnLzIwMTgvY3JlZGVudGlhbHMvdjEiLCJ0eXBlIjpbIlZlcmlmaWFibGVDcmVkZW50aWFsIl0sImNyZWRlbnRpYWxTdWJqZWN0Ijp7ImlkIjoiZGlkOmtleTp6Nk1rZ0U4NE5DTXBNZUF4OWpLOWNmNVc0RzhnY1o5eHV3SnZHMWU3d05rOEtDZ3QiLCJmaXJzdF9uYW1lIjoiRmVycmlzIiwibGFzdF9uYW1lIjoiUnVzdGFjZWFuIiwiZGVncmVlIjp7InR5cGUiOiJNYXN0ZXJEZWdyZWUiLCJuYW1lIjoiTWFzdGVyIG9mIE9jZWFub2dyYXBoeSJ9fSwiaXNzdWVyIjoiZGlkOmtleTp6Nk1rZ0U4NE5DTXBNZUF4OWpLOWNmNVc0RzhnY1o5eHV3SnZHMWU3d05rOEtDZ3QiLCJpc3N1YW5jZURhdGUiOiIyMDEwLTAxLTAxVDAwOjAwOjAwWiJ9fQ.MUDBbPJfXe0G9sjVTF3RuR6ukRM0d4N57iMGNFcIKMFPIEdig12v-YFB0qfnSghGcQo8hUw3jzxZXTSJATEgBg";
@@ -441,6 +1489,38 @@ pub mod test_utils {
                   }
             }
         );
+        pub static ref DEGREE_CREDENTIAL_SCHEMA: CredentialSchema = CredentialSchema {
+            id: "https://example.com/schemas/degree.json".parse().unwrap(),
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "first_name": { "type": "string" },
+                    "last_name": { "type": "string" },
+                    "degree": {
+                        "type": "object",
+                        "properties": {
+                            "type": { "type": "string" },
+                            "name": { "type": "string" }
+                        },
+                        "required": ["type", "name"]
+                    }
+                },
+                "required": ["first_name", "last_name", "degree"]
+            }),
+        };
+        pub static ref DEGREE_CREDENTIAL_TEMPLATE: CredentialTemplate = serde_json::from_value(json!({
+            "template_id": "degree",
+            "credential_subject": {
+                "first_name": { "field": "given_name" },
+                "last_name": { "field": "surname" },
+                "degree.type": { "field": "degree_type" },
+                "degree.name": { "field": "programme" }
+            },
+            "properties": {
+                "name": { "value": "Degree Credential" }
+            }
+        }))
+        .unwrap();
         pub static ref W3C_VC_CREDENTIAL_SUBJECT: serde_json::Value = json!(
             {
                 "credentialSubject": {
@@ -469,6 +1549,127 @@ pub mod test_utils {
           "name": "Teamwork Badge",
           "credentialSubject": OPENBADGE_CREDENTIAL_SUBJECT["credentialSubject"].clone(),
         });
+        pub static ref SD_JWT_VC_CREDENTIAL_CONFIGURATION: CredentialConfigurationsSupportedObject =
+            CredentialConfigurationsSupportedObject {
+                credential_format: CredentialFormats::SdJwtVc(Parameters {
+                    parameters: SdJwtVcParameters {
+                        vct: "https://example.com/credentials/degree".to_string(),
+                        claims: None,
+                        order: None,
+                    },
+                }),
+                cryptographic_binding_methods_supported: vec![
+                    "did:iota:rms".to_string(),
+                    "did:jwk".to_string(),
+                    "did:key".to_string(),
+                ],
+                credential_signing_alg_values_supported: vec!["EdDSA".to_string()],
+                proof_types_supported: HashMap::from_iter(vec![(
+                    ProofType::Jwt,
+                    KeyProofMetadata {
+                        proof_signing_alg_values_supported: vec![Algorithm::EdDSA],
+                    },
+                )]),
+                display: vec![json!({
+                    "locale": "en",
+                    "name": "Degree Credential"
+                })],
+                ..Default::default()
+            };
+        pub static ref MDOC_CREDENTIAL_CONFIGURATION: CredentialConfigurationsSupportedObject =
+            CredentialConfigurationsSupportedObject {
+                credential_format: CredentialFormats::MsoMdoc(Parameters {
+                    parameters: MsoMdocParameters {
+                        doctype: "org.iso.18013.5.1.mDL".to_string(),
+                        claims: None,
+                        order: None,
+                    },
+                }),
+                cryptographic_binding_methods_supported: vec![
+                    "did:iota:rms".to_string(),
+                    "did:jwk".to_string(),
+                    "did:key".to_string(),
+                ],
+                credential_signing_alg_values_supported: vec!["ES256".to_string()],
+                proof_types_supported: HashMap::from_iter(vec![(
+                    ProofType::Jwt,
+                    KeyProofMetadata {
+                        proof_signing_alg_values_supported: vec![Algorithm::EdDSA],
+                    },
+                )]),
+                display: vec![json!({
+                    "locale": "en",
+                    "name": "Mobile Driving Licence"
+                })],
+                ..Default::default()
+            };
+        pub static ref LDP_VC_CREDENTIAL_CONFIGURATION: CredentialConfigurationsSupportedObject =
+            CredentialConfigurationsSupportedObject {
+                credential_format: CredentialFormats::LdpVc(Parameters {
+                    parameters: LdpVcParameters {
+                        credential_definition: LdCredentialDefinition {
+                            context: vec![json!("https://www.w3.org/2018/credentials/v1")],
+                            type_: vec!["VerifiableCredential".to_string()],
+                            credential_subject: Default::default(),
+                        },
+                        order: None,
+                    },
+                }),
+                cryptographic_binding_methods_supported: vec![
+                    "did:iota:rms".to_string(),
+                    "did:jwk".to_string(),
+                    "did:key".to_string(),
+                ],
+                credential_signing_alg_values_supported: vec!["eddsa-rdfc-2022".to_string()],
+                proof_types_supported: HashMap::from_iter(vec![(
+                    ProofType::Jwt,
+                    KeyProofMetadata {
+                        proof_signing_alg_values_supported: vec![Algorithm::EdDSA],
+                    },
+                )]),
+                display: vec![json!({
+                    "locale": "en",
+                    "name": "Verifiable Credential"
+                })],
+                ..Default::default()
+            };
+        pub static ref MDOC_CREDENTIAL_SUBJECT: serde_json::Value = json!({
+            "credentialSubject": {
+                "org.iso.18013.5.1": {
+                    "given_name": "Ferris",
+                    "family_name": "Rustacean",
+                    "birth_date": "2015-05-15"
+                }
+            }
+        });
+        pub static ref MDOC_VALIDITY: Validity = Validity {
+            validity_period: Some(365 * 24 * 60 * 60),
+            ..Default::default()
+        };
+        pub static ref UNSIGNED_MDOC_CREDENTIAL: serde_json::Value = json!({
+            "docType": "org.iso.18013.5.1.mDL",
+            "nameSpaces": MDOC_CREDENTIAL_SUBJECT["credentialSubject"].clone(),
+            "validityInfo": {
+                "validFrom": "2010-01-01T00:00:00Z",
+                "validUntil": "2011-01-01T00:00:00Z"
+            }
+        });
+        pub static ref UNSIGNED_LDP_VC_CREDENTIAL: serde_json::Value = json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "id": "urn:uuid:00000000-0000-0000-0000-000000000000",
+            "type": ["VerifiableCredential"],
+            "issuanceDate": "2010-01-01T00:00:00Z",
+            "credentialSubject": W3C_VC_CREDENTIAL_SUBJECT["credentialSubject"].clone()
+        });
+        pub static ref UNSIGNED_SD_JWT_VC_CREDENTIAL: serde_json::Value = json!({
+            "first_name": "Ferris",
+            "last_name": "Rustacean",
+            "degree": {
+                "type": "MasterDegree",
+                "name": "Master of Oceanography"
+            },
+            "vct": "https://example.com/credentials/degree"
+        });
         pub static ref UNSIGNED_W3C_VC_CREDENTIAL: serde_json::Value = json!({
           "@context": "https://www.w3.org/2018/credentials/v1",
           "type": [ "VerifiableCredential" ],
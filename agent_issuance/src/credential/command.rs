@@ -1,14 +1,22 @@
+use agent_shared::config::{CredentialSchema, CredentialTemplate};
 use oid4vci::credential_issuer::credential_configurations_supported::CredentialConfigurationsSupportedObject;
 use serde::Deserialize;
 
-use super::entity::Data;
+use super::entity::{Data, Validity};
 
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum CredentialCommand {
     CreateUnsignedCredential {
+        credential_id: String,
         data: Data,
         credential_configuration: CredentialConfigurationsSupportedObject,
+        selectively_disclosable_claims: Vec<String>,
+        status_list_index: Option<usize>,
+        validity: Validity,
+        credential_schema: Option<CredentialSchema>,
+        /// When set, `data` is a flat input record that is mapped onto the credential using this template.
+        credential_template: Option<CredentialTemplate>,
     },
     CreateSignedCredential {
         signed_credential: serde_json::Value,
@@ -17,5 +25,9 @@ pub enum CredentialCommand {
         subject_id: String,
         // When true, a credential will be re-signed if it already exists.
         overwrite: bool,
+        /// The locales preferred by the Wallet (e.g. from its `Accept-Language` header), in order of preference. Used to
+        /// pick the localized name of the credential.
+        #[serde(default)]
+        locales: Vec<String>,
     },
 }
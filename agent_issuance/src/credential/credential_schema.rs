@@ -0,0 +1,124 @@
+use agent_shared::config::CredentialSchema;
+use jsonschema::JSONSchema;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use super::{entity::SchemaViolation, error::CredentialError};
+
+/// The type of the `credentialSchema` as defined in the Verifiable Credentials JSON Schema Specification.
+pub const CREDENTIAL_SCHEMA_TYPE: &str = "JsonSchema";
+
+/// Compiles the JSON Schema of the `credential_schema` to make sure that it can be used for validation.
+pub fn compile(credential_schema: &CredentialSchema) -> Result<JSONSchema, String> {
+    JSONSchema::compile(&credential_schema.schema).map_err(|e| e.to_string())
+}
+
+/// The compiled JSON Schemas of the Credential Configurations, keyed by the `id` of their `credentialSchema`. Each JSON
+/// Schema is only compiled again when it has been changed by an update of its Credential Configuration.
+#[derive(Default)]
+pub struct CredentialSchemas {
+    compiled: Mutex<HashMap<String, (Value, Arc<JSONSchema>)>>,
+}
+
+impl CredentialSchemas {
+    /// Validates the `credential_subject` against the JSON Schema of the `credential_schema`. Returns all the
+    /// violations at once so that they can be reported per field.
+    pub fn validate(
+        &self,
+        credential_schema: &CredentialSchema,
+        credential_subject: &Value,
+    ) -> Result<(), CredentialError> {
+        let schema = self.compiled(credential_schema)?;
+
+        if let Err(errors) = schema.validate(credential_subject) {
+            return Err(CredentialError::CredentialSchemaViolationError(
+                errors
+                    .map(|error| SchemaViolation {
+                        instance_path: error.instance_path.to_string(),
+                        message: error.to_string(),
+                    })
+                    .collect(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the compiled JSON Schema of the `credential_schema`, compiling it only when it is not compiled yet.
+    fn compiled(&self, credential_schema: &CredentialSchema) -> Result<Arc<JSONSchema>, CredentialError> {
+        let mut compiled = self.compiled.lock().unwrap();
+
+        match compiled.get(credential_schema.id.as_str()) {
+            Some((schema, compiled_schema)) if *schema == credential_schema.schema => Ok(compiled_schema.clone()),
+            _ => {
+                let compiled_schema =
+                    Arc::new(compile(credential_schema).map_err(CredentialError::InvalidCredentialSchemaError)?);
+                compiled.insert(
+                    credential_schema.id.to_string(),
+                    (credential_schema.schema.clone(), compiled_schema.clone()),
+                );
+                Ok(compiled_schema)
+            }
+        }
+    }
+}
+
+/// Returns the `credentialSchema` property that refers to the JSON Schema of the `credential_schema`.
+pub fn credential_schema_property(credential_schema: &CredentialSchema) -> Value {
+    json!({
+        "id": credential_schema.id,
+        "type": CREDENTIAL_SCHEMA_TYPE,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let credential_schema = CredentialSchema {
+            id: "https://example.com/schemas/degree.json".parse().unwrap(),
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "first_name": { "type": "string" },
+                    "degree": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" }
+                        },
+                        "required": ["name"]
+                    }
+                },
+                "required": ["first_name", "degree"]
+            }),
+        };
+
+        let credential_schemas = CredentialSchemas::default();
+
+        assert!(credential_schemas
+            .validate(
+                &credential_schema,
+                &json!({ "first_name": "Ferris", "degree": { "name": "Master of Oceanography" } })
+            )
+            .is_ok());
+
+        let Err(CredentialError::CredentialSchemaViolationError(violations)) =
+            credential_schemas.validate(&credential_schema, &json!({ "first_name": 42, "degree": {} }))
+        else {
+            panic!("Expected the credential subject to violate the credential schema");
+        };
+
+        let instance_paths: Vec<_> = violations
+            .iter()
+            .map(|violation| violation.instance_path.as_str())
+            .collect();
+        assert_eq!(violations.len(), 2);
+        assert!(instance_paths.contains(&"/first_name"));
+        assert!(instance_paths.contains(&"/degree"));
+    }
+}
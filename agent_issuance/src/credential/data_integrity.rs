@@ -0,0 +1,231 @@
+use agent_secret_manager::subject::Subject as SecretManagerSubject;
+use agent_shared::config::ProofSuite;
+use jsonwebtoken::Algorithm;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sophia::api::source::QuadSource;
+use sophia::c14n::rdfc10;
+use sophia::inmem::dataset::LightDataset;
+use sophia::jsonld::{loader::StaticLoader, JsonLdOptions, JsonLdParser};
+
+use super::error::CredentialError::{self, *};
+
+pub const CREDENTIALS_V1_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+pub const DATA_INTEGRITY_V2_CONTEXT: &str = "https://w3id.org/security/data-integrity/v2";
+pub const ED25519_SIGNATURE_2020_V1_CONTEXT: &str = "https://w3id.org/security/suites/ed25519-2020/v1";
+
+/// The vocabulary that is used for terms which are not defined by any of the contexts of a credential, as defined by
+/// the W3C Verifiable Credentials Data Model v2.0.
+pub const ISSUER_DEPENDENT_VOCABULARY: &str = "https://www.w3.org/ns/credentials/issuer-dependent#";
+
+/// The JSON-LD contexts that are bundled with UniCore. Remote contexts are never fetched, so signing `ldp_vc`
+/// Credentials does not require network access.
+const BUNDLED_CONTEXTS: [(&str, &str); 3] = [
+    (
+        CREDENTIALS_V1_CONTEXT,
+        include_str!("../../res/json_ld_contexts/credentials_v1.json"),
+    ),
+    (
+        DATA_INTEGRITY_V2_CONTEXT,
+        include_str!("../../res/json_ld_contexts/data_integrity_v2.json"),
+    ),
+    (
+        ED25519_SIGNATURE_2020_V1_CONTEXT,
+        include_str!("../../res/json_ld_contexts/ed25519_signature_2020_v1.json"),
+    ),
+];
+
+/// Returns the context that is required to express the proof of the given `proof_suite`.
+fn proof_suite_context(proof_suite: ProofSuite) -> &'static str {
+    match proof_suite {
+        ProofSuite::EddsaRdfc2022 => DATA_INTEGRITY_V2_CONTEXT,
+        ProofSuite::Ed25519Signature2020 => ED25519_SIGNATURE_2020_V1_CONTEXT,
+    }
+}
+
+/// Returns an error if the `document` references a remote context that is not bundled.
+fn check_contexts(document: &Value) -> Result<(), CredentialError> {
+    let contexts = match &document["@context"] {
+        Value::Array(contexts) => contexts.iter().collect(),
+        context => vec![context],
+    };
+
+    for context in contexts {
+        if let Value::String(context) = context {
+            if !BUNDLED_CONTEXTS.iter().any(|(url, _)| url == context) {
+                return Err(UnsupportedContextError(context.clone()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Canonicalizes the JSON-LD `document` using the RDF Dataset Canonicalization algorithm (RDFC-1.0).
+pub fn canonicalize(document: &Value) -> Result<String, CredentialError> {
+    check_contexts(document)?;
+
+    let options = JsonLdOptions::new().with_document_loader_closure(|| {
+        let mut loader = StaticLoader::default();
+        for (url, context) in BUNDLED_CONTEXTS {
+            loader.insert(url, context);
+        }
+        loader
+    });
+
+    let dataset: LightDataset = JsonLdParser::new_with_options(options)
+        .parse_str(&document.to_string())
+        .collect_quads()
+        .map_err(|e| CanonicalizationError(e.to_string()))?;
+
+    let mut canonical_document = vec![];
+    rdfc10::normalize(&dataset, &mut canonical_document).map_err(|e| CanonicalizationError(e.to_string()))?;
+
+    String::from_utf8(canonical_document).map_err(|e| CanonicalizationError(e.to_string()))
+}
+
+/// Adds a Data Integrity proof to the `credential` using the EdDSA key of the issuer as described in the Data
+/// Integrity EdDSA Cryptosuites specification. Both `eddsa-rdfc-2022` and `Ed25519Signature2020` proofs are created
+/// by signing the concatenation of the hashes of the canonicalized proof configuration and credential.
+pub async fn add_proof(
+    signer: &SecretManagerSubject,
+    mut credential: Value,
+    proof_suite: ProofSuite,
+    verification_method: String,
+    created: String,
+) -> Result<Value, CredentialError> {
+    let mut contexts = match credential["@context"].take() {
+        Value::Array(contexts) => contexts,
+        context => vec![context],
+    };
+
+    // Terms that are not defined by any context are dropped during expansion, which would leave them unsigned. The
+    // Verifiable Credentials Data Model v1.1 context does not define a `@vocab`, so an issuer-dependent one is added.
+    if !contexts.iter().any(|context| context.get("@vocab").is_some()) {
+        contexts.push(json!({ "@vocab": ISSUER_DEPENDENT_VOCABULARY }));
+    }
+
+    // Make sure that the terms of the proof can be expressed using the context of the credential.
+    let context = json!(proof_suite_context(proof_suite));
+    if !contexts.contains(&context) {
+        contexts.push(context);
+    }
+    credential["@context"] = json!(contexts);
+
+    let mut proof = match proof_suite {
+        ProofSuite::EddsaRdfc2022 => json!({
+            "type": "DataIntegrityProof",
+            "cryptosuite": "eddsa-rdfc-2022",
+        }),
+        ProofSuite::Ed25519Signature2020 => json!({
+            "type": "Ed25519Signature2020",
+        }),
+    };
+    proof["created"] = json!(created);
+    proof["verificationMethod"] = json!(verification_method);
+    proof["proofPurpose"] = json!("assertionMethod");
+
+    let mut proof_configuration = proof.clone();
+    proof_configuration["@context"] = credential["@context"].clone();
+
+    let hash_data = [
+        Sha256::digest(canonicalize(&proof_configuration)?.as_bytes()),
+        Sha256::digest(canonicalize(&credential)?.as_bytes()),
+    ]
+    .concat();
+
+    let signature = signer
+        .sign_bytes(&hash_data, Algorithm::EdDSA)
+        .await
+        .map_err(|e| CredentialSigningError(e.to_string()))?;
+
+    // The `proofValue` is encoded as a base58-btc Multibase string.
+    proof["proofValue"] = json!(format!("z{}", bs58::encode(signature).into_string()));
+
+    credential["proof"] = proof;
+
+    Ok(credential)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::IssuanceServices;
+    use agent_secret_manager::service::Service;
+    use oid4vc_core::Verify;
+    use ring::signature::{UnparsedPublicKey, ED25519};
+
+    /// Verifies the Data Integrity proof of the `credential` by recomputing the hash data that was signed.
+    fn verify_proof(public_key: &[u8], credential: &Value) -> bool {
+        let mut credential = credential.clone();
+        let mut proof = credential.as_object_mut().unwrap().remove("proof").unwrap();
+        let proof_value = proof.as_object_mut().unwrap().remove("proofValue").unwrap();
+        let signature = bs58::decode(proof_value.as_str().unwrap().strip_prefix('z').unwrap())
+            .into_vec()
+            .unwrap();
+        proof["@context"] = credential["@context"].clone();
+
+        let hash_data = [
+            Sha256::digest(canonicalize(&proof).unwrap().as_bytes()),
+            Sha256::digest(canonicalize(&credential).unwrap().as_bytes()),
+        ]
+        .concat();
+
+        UnparsedPublicKey::new(&ED25519, public_key)
+            .verify(&hash_data, &signature)
+            .is_ok()
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_add_proof_signs_undefined_terms() {
+        let signer = IssuanceServices::default().signer.clone();
+        let verification_method = signer.verification_method("did:key", Algorithm::EdDSA).await.unwrap();
+        let public_key = signer.public_key(&verification_method).await.unwrap();
+
+        // `degree` is not defined by the Verifiable Credentials Data Model v1.1 context.
+        let credential = json!({
+            "@context": [CREDENTIALS_V1_CONTEXT],
+            "id": "urn:uuid:00000000-0000-0000-0000-000000000000",
+            "type": ["VerifiableCredential"],
+            "issuer": "did:example:issuer",
+            "issuanceDate": "2010-01-01T00:00:00Z",
+            "credentialSubject": {
+                "id": "did:example:subject",
+                "degree": "Master of Oceanography"
+            }
+        });
+
+        for proof_suite in [ProofSuite::EddsaRdfc2022, ProofSuite::Ed25519Signature2020] {
+            let signed_credential = add_proof(
+                &signer,
+                credential.clone(),
+                proof_suite,
+                verification_method.clone(),
+                "2010-01-01T00:00:00Z".to_string(),
+            )
+            .await
+            .unwrap();
+
+            assert!(verify_proof(&public_key, &signed_credential));
+
+            let mut tampered_credential = signed_credential.clone();
+            tampered_credential["credentialSubject"]["degree"] = json!("Bachelor of Oceanography");
+
+            assert!(!verify_proof(&public_key, &tampered_credential));
+        }
+    }
+
+    #[test]
+    fn test_check_contexts() {
+        assert!(check_contexts(&json!({ "@context": CREDENTIALS_V1_CONTEXT })).is_ok());
+        assert!(check_contexts(&json!({
+            "@context": [CREDENTIALS_V1_CONTEXT, DATA_INTEGRITY_V2_CONTEXT, { "name": "https://schema.org/name" }]
+        }))
+        .is_ok());
+        assert!(matches!(
+            check_contexts(&json!({ "@context": [CREDENTIALS_V1_CONTEXT, "https://example.com/context.json"] })),
+            Err(UnsupportedContextError(context)) if context == "https://example.com/context.json"
+        ));
+    }
+}
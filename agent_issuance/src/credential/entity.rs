@@ -1,6 +1,37 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct Data {
     pub raw: serde_json::Value,
 }
+
+/// The period during which a credential is valid. The timestamps are RFC 3339 formatted.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Validity {
+    /// Defaults to the moment the credential is created.
+    pub valid_from: Option<String>,
+    pub valid_until: Option<String>,
+    /// The number of seconds after `valid_from` until the credential expires. Only used when `valid_until` is not set.
+    pub validity_period: Option<u64>,
+}
+
+/// A field of the `credentialSubject` that does not conform to the JSON Schema of the Credential Configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaViolation {
+    /// The JSON Pointer to the field within the `credentialSubject`, e.g. `/degree/name`.
+    pub instance_path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The `instance_path` of the `credentialSubject` itself is empty.
+        if self.instance_path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "`{}`: {}", self.instance_path, self.message)
+        }
+    }
+}
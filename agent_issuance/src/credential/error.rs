@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use super::entity::SchemaViolation;
+
 #[derive(Error, Debug)]
 pub enum CredentialError {
     #[error("Credential must be an object")]
@@ -17,6 +19,48 @@ pub enum CredentialError {
     #[error("The verifiable credential is invalid: {0}")]
     InvalidVerifiableCredentialError(String),
 
+    #[error("The JSON Schema of the Credential Configuration is invalid: {0}")]
+    InvalidCredentialSchemaError(String),
+
+    #[error(
+        "The `credentialSubject` does not conform to the JSON Schema: {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    CredentialSchemaViolationError(Vec<SchemaViolation>),
+
+    #[error("The credential template is invalid: {0}")]
+    InvalidCredentialTemplateError(String),
+
+    #[error("The field `{0}` is missing from the credential data")]
+    MissingTemplateFieldError(String),
+
+    #[error("The credential data is invalid: {0}")]
+    InvalidTemplateFieldError(String),
+
+    #[error("The validity period of the credential is invalid: {0}")]
+    InvalidValidityError(String),
+
     #[error("Could not find any data to be signed")]
     MissingCredentialDataError,
+
+    #[error("The claim `{0}` cannot be selectively disclosed")]
+    InvalidSelectivelyDisclosableClaimError(String),
+
+    #[error("Could not sign the credential: {0}")]
+    CredentialSigningError(String),
+
+    #[error("The key of the holder cannot be used as a device key: {0}")]
+    InvalidDeviceKeyError(String),
+
+    #[error("The key of the holder cannot be bound to the credential: {0}")]
+    InvalidHolderKeyError(String),
+
+    #[error("No X.509 certificate chain is configured to sign `mso_mdoc` Credentials")]
+    MissingCertificateChainError,
+
+    #[error("The JSON-LD context `{0}` is not supported")]
+    UnsupportedContextError(String),
+
+    #[error("Could not canonicalize the credential: {0}")]
+    CanonicalizationError(String),
 }
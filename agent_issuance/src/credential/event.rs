@@ -10,6 +10,10 @@ pub enum CredentialEvent {
     UnsignedCredentialCreated {
         data: Data,
         credential_configuration: CredentialConfigurationsSupportedObject,
+        #[serde(default)]
+        selectively_disclosable_claims: Vec<String>,
+        #[serde(default)]
+        status_list_index: Option<usize>,
     },
     SignedCredentialCreated {
         signed_credential: serde_json::Value,
@@ -0,0 +1,271 @@
+use agent_secret_manager::subject::Subject as SecretManagerSubject;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ciborium::Value;
+use jsonwebtoken::Algorithm;
+use serde_json::Map;
+use sha2::{Digest, Sha256};
+
+use super::error::CredentialError::{self, *};
+
+/// The COSE algorithm identifier of ES256 as registered in the IANA COSE Algorithms registry.
+const COSE_ALGORITHM_ES256: i64 = -7;
+
+/// The COSE header parameter label of `x5chain` as described in RFC 9360.
+const COSE_HEADER_X5CHAIN: i64 = 33;
+
+/// Encodes the `value` as CBOR.
+pub fn to_cbor(value: &Value) -> Vec<u8> {
+    let mut bytes = vec![];
+    ciborium::into_writer(value, &mut bytes).expect("Failed to encode CBOR value");
+    bytes
+}
+
+/// Wraps the CBOR encoded `value` in a `#6.24(bstr)` as used for `IssuerSignedItemBytes` and
+/// `MobileSecurityObjectBytes` in ISO/IEC 18013-5.
+fn embedded_cbor(value: &Value) -> Value {
+    Value::Tag(24, Box::new(Value::Bytes(to_cbor(value))))
+}
+
+/// Encodes the `timestamp` as a `tdate` (`#6.0(tstr)`).
+fn tdate(timestamp: i64) -> Value {
+    let date_time = chrono::DateTime::from_timestamp(timestamp, 0).expect("Invalid timestamp");
+    Value::Tag(
+        0,
+        Box::new(Value::Text(
+            date_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        )),
+    )
+}
+
+fn text_map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (Value::Text(key.to_string()), value))
+            .collect(),
+    )
+}
+
+/// Returns the DID URL of the key of the Holder, since the `subject_id` only contains the DID. Only `did:key` and
+/// `did:jwk` are supported, since the key of the Holder cannot be derived from the DID for other methods.
+pub fn holder_key_id(subject_id: &str) -> Option<String> {
+    match subject_id.strip_prefix("did:key:") {
+        Some(method_specific_id) => Some(format!("{subject_id}#{method_specific_id}")),
+        None if subject_id.starts_with("did:jwk:") => Some(format!("{subject_id}#0")),
+        None => None,
+    }
+}
+
+/// Converts the raw public key of the Holder into a `COSE_Key` which is used as the `deviceKey` of the mdoc.
+pub fn cose_key(public_key: &[u8]) -> Result<Value, CredentialError> {
+    match public_key {
+        // Ed25519 (OKP)
+        x if x.len() == 32 => Ok(Value::Map(vec![
+            (Value::Integer(1.into()), Value::Integer(1.into())),
+            (Value::Integer((-1).into()), Value::Integer(6.into())),
+            (Value::Integer((-2).into()), Value::Bytes(x.to_vec())),
+        ])),
+        // P-256 (EC2) as an uncompressed point.
+        [0x04, coordinates @ ..] if coordinates.len() == 64 => Ok(Value::Map(vec![
+            (Value::Integer(1.into()), Value::Integer(2.into())),
+            (Value::Integer((-1).into()), Value::Integer(1.into())),
+            (Value::Integer((-2).into()), Value::Bytes(coordinates[..32].to_vec())),
+            (Value::Integer((-3).into()), Value::Bytes(coordinates[32..].to_vec())),
+        ])),
+        _ => Err(InvalidDeviceKeyError("unsupported key type".to_string())),
+    }
+}
+
+/// Creates the `IssuerSignedItemBytes` for every data element in the `name_spaces` and returns them together with the
+/// `ValueDigests` that need to be included in the Mobile Security Object.
+pub fn issuer_signed_name_spaces(
+    name_spaces: &Map<String, serde_json::Value>,
+) -> Result<(Value, Value), CredentialError> {
+    let mut digest_id: u64 = 0;
+    let mut issuer_signed_name_spaces = vec![];
+    let mut value_digests = vec![];
+
+    for (name_space, data_elements) in name_spaces {
+        let data_elements = data_elements.as_object().ok_or(InvalidCredentialSubjectError(format!(
+            "namespace `{name_space}` must be an object"
+        )))?;
+
+        let mut issuer_signed_items = vec![];
+        let mut digests = vec![];
+
+        for (element_identifier, element_value) in data_elements {
+            let issuer_signed_item = text_map(vec![
+                ("digestID", Value::Integer(digest_id.into())),
+                ("random", Value::Bytes(rand::random::<[u8; 16]>().to_vec())),
+                ("elementIdentifier", Value::Text(element_identifier.clone())),
+                (
+                    "elementValue",
+                    Value::serialized(element_value).map_err(|e| InvalidCredentialSubjectError(e.to_string()))?,
+                ),
+            ]);
+
+            let issuer_signed_item_bytes = embedded_cbor(&issuer_signed_item);
+
+            digests.push((
+                Value::Integer(digest_id.into()),
+                Value::Bytes(Sha256::digest(to_cbor(&issuer_signed_item_bytes)).to_vec()),
+            ));
+            issuer_signed_items.push(issuer_signed_item_bytes);
+
+            digest_id += 1;
+        }
+
+        issuer_signed_name_spaces.push((Value::Text(name_space.clone()), Value::Array(issuer_signed_items)));
+        value_digests.push((Value::Text(name_space.clone()), Value::Map(digests)));
+    }
+
+    Ok((Value::Map(issuer_signed_name_spaces), Value::Map(value_digests)))
+}
+
+/// Creates the Mobile Security Object (MSO) as described in ISO/IEC 18013-5, Section 9.1.2.4.
+pub fn mobile_security_object(
+    doc_type: &str,
+    value_digests: Value,
+    device_key: Value,
+    signed: i64,
+    valid_from: i64,
+    valid_until: i64,
+) -> Value {
+    text_map(vec![
+        ("version", Value::Text("1.0".to_string())),
+        ("digestAlgorithm", Value::Text("SHA-256".to_string())),
+        ("valueDigests", value_digests),
+        ("deviceKeyInfo", text_map(vec![("deviceKey", device_key)])),
+        ("docType", Value::Text(doc_type.to_string())),
+        (
+            "validityInfo",
+            text_map(vec![
+                ("signed", tdate(signed)),
+                ("validFrom", tdate(valid_from)),
+                ("validUntil", tdate(valid_until)),
+            ]),
+        ),
+    ])
+}
+
+/// Signs the `mobile_security_object` with the ES256 key of the issuer and returns the `IssuerAuth` (COSE_Sign1). The
+/// `certificate_chain` of that key is included as the `x5chain` in the unprotected header, so that the mdoc can be
+/// verified against the IACA root certificate.
+pub async fn issuer_auth(
+    signer: &SecretManagerSubject,
+    certificate_chain: &[Vec<u8>],
+    mobile_security_object: &Value,
+) -> Result<Value, CredentialError> {
+    // A single certificate is encoded as a `bstr`, a chain as an array of `bstr`s (RFC 9360, Section 2).
+    let x5chain = match certificate_chain {
+        [] => return Err(MissingCertificateChainError),
+        [certificate] => Value::Bytes(certificate.clone()),
+        certificates => Value::Array(certificates.iter().cloned().map(Value::Bytes).collect()),
+    };
+    let unprotected = Value::Map(vec![(Value::Integer(COSE_HEADER_X5CHAIN.into()), x5chain)]);
+
+    let protected = to_cbor(&Value::Map(vec![(
+        Value::Integer(1.into()),
+        Value::Integer(COSE_ALGORITHM_ES256.into()),
+    )]));
+    let payload = to_cbor(&embedded_cbor(mobile_security_object));
+
+    // The `Sig_structure` as described in RFC 9052, Section 4.4.
+    let sig_structure = Value::Array(vec![
+        Value::Text("Signature1".to_string()),
+        Value::Bytes(protected.clone()),
+        Value::Bytes(vec![]),
+        Value::Bytes(payload.clone()),
+    ]);
+
+    let signature = signer
+        .sign_bytes(&to_cbor(&sig_structure), Algorithm::ES256)
+        .await
+        .map_err(|e| CredentialSigningError(e.to_string()))?;
+
+    Ok(Value::Array(vec![
+        Value::Bytes(protected),
+        unprotected,
+        Value::Bytes(payload),
+        Value::Bytes(signature),
+    ]))
+}
+
+/// Returns the base64url encoded `IssuerSigned` structure, which is the format in which `mso_mdoc` Credentials are
+/// returned in the Credential Response.
+pub fn issuer_signed(issuer_signed_name_spaces: Value, issuer_auth: Value) -> String {
+    URL_SAFE_NO_PAD.encode(to_cbor(&text_map(vec![
+        ("nameSpaces", issuer_signed_name_spaces),
+        ("issuerAuth", issuer_auth),
+    ])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_holder_key_id() {
+        assert_eq!(
+            holder_key_id("did:key:z6MkiieyoLMSVsJAZv7Jje5wWSkDEymUgkyF8kbcrjZpX3qd"),
+            Some(
+                "did:key:z6MkiieyoLMSVsJAZv7Jje5wWSkDEymUgkyF8kbcrjZpX3qd#z6MkiieyoLMSVsJAZv7Jje5wWSkDEymUgkyF8kbcrjZpX3qd"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            holder_key_id("did:jwk:eyJrdHkiOiJPS1AifQ"),
+            Some("did:jwk:eyJrdHkiOiJPS1AifQ#0".to_string())
+        );
+        // The key of the Holder cannot be derived from a `did:web` without knowing which of its keys was used.
+        assert_eq!(holder_key_id("did:web:example.org"), None);
+    }
+
+    #[test]
+    fn test_issuer_signed_name_spaces() {
+        let name_spaces = json!({
+            "org.iso.18013.5.1": {
+                "given_name": "Ferris",
+                "family_name": "Rustacean"
+            }
+        });
+
+        let (issuer_signed_name_spaces, value_digests) =
+            issuer_signed_name_spaces(name_spaces.as_object().unwrap()).unwrap();
+
+        let Value::Map(issuer_signed_name_spaces) = issuer_signed_name_spaces else {
+            panic!("Expected a map");
+        };
+        let Value::Map(value_digests) = value_digests else {
+            panic!("Expected a map");
+        };
+
+        let (_, Value::Array(issuer_signed_items)) = &issuer_signed_name_spaces[0] else {
+            panic!("Expected an array of `IssuerSignedItemBytes`");
+        };
+        let (_, Value::Map(digests)) = &value_digests[0] else {
+            panic!("Expected a map of digests");
+        };
+
+        assert_eq!(issuer_signed_items.len(), 2);
+
+        // Every `IssuerSignedItemBytes` must be referenced by its digest in the `ValueDigests`.
+        for (index, issuer_signed_item_bytes) in issuer_signed_items.iter().enumerate() {
+            assert_eq!(
+                digests[index],
+                (
+                    Value::Integer((index as u64).into()),
+                    Value::Bytes(Sha256::digest(to_cbor(issuer_signed_item_bytes)).to_vec())
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_cose_key() {
+        assert!(cose_key(&[0; 32]).is_ok());
+        assert!(cose_key(&[[0x04].as_slice(), &[0; 64]].concat()).is_ok());
+        assert!(cose_key(&[0; 16]).is_err());
+    }
+}
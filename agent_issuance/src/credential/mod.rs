@@ -1,6 +1,11 @@
 pub mod aggregate;
 pub mod command;
+pub mod credential_schema;
+pub mod data_integrity;
 pub mod entity;
 pub mod error;
 pub mod event;
+pub mod mdoc;
 pub mod queries;
+pub mod sd_jwt;
+pub mod template;
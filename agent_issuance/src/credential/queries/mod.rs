@@ -19,6 +19,7 @@ impl View<Credential> for CredentialView {
             CredentialEvent::UnsignedCredentialCreated {
                 data,
                 credential_configuration,
+                ..
             } => {
                 self.data.replace(data.clone());
                 self.credential_configuration = credential_configuration.clone();
@@ -0,0 +1,76 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use super::error::CredentialError::{self, *};
+
+/// The hash algorithm that is used to calculate the digests of the Disclosures.
+pub const SD_ALG: &str = "sha-256";
+
+/// Claims that are not allowed to be selectively disclosed as described in SD-JWT VC, Section 3.2.2.2.
+pub const NON_SELECTIVELY_DISCLOSABLE_CLAIMS: [&str; 6] = ["iss", "nbf", "exp", "cnf", "vct", "status"];
+
+/// Removes the `selectively_disclosable_claims` from the `claims` and replaces them with the digests of their
+/// Disclosures in the `_sd` array. Returns the Disclosures.
+pub fn conceal_claims(claims: &mut Map<String, Value>, selectively_disclosable_claims: &[String]) -> Vec<String> {
+    let mut digests = vec![];
+    let mut disclosures = vec![];
+
+    for claim_name in selectively_disclosable_claims {
+        let Some(claim_value) = claims.remove(claim_name) else {
+            continue;
+        };
+
+        let disclosure = URL_SAFE_NO_PAD.encode(json!([salt(), claim_name, claim_value]).to_string());
+
+        digests.push(digest(&disclosure));
+        disclosures.push(disclosure);
+    }
+
+    if !digests.is_empty() {
+        // Sort the digests so that the original order of the claims is not revealed.
+        digests.sort();
+
+        claims.insert("_sd".to_string(), json!(digests));
+        claims.insert("_sd_alg".to_string(), json!(SD_ALG));
+    }
+
+    disclosures
+}
+
+/// Returns the base64url-encoded SHA-256 digest of the `disclosure`.
+pub fn digest(disclosure: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(disclosure.as_bytes()))
+}
+
+/// Combines the Issuer-signed JWT and the Disclosures into an SD-JWT: `<Issuer-signed JWT>~<Disclosure 1>~...~`.
+pub fn serialize(issuer_signed_jwt: String, disclosures: Vec<String>) -> String {
+    std::iter::once(issuer_signed_jwt)
+        .chain(disclosures)
+        .map(|part| format!("{part}~"))
+        .collect()
+}
+
+/// Converts the raw public key of the Holder into the JWK that is included in the `cnf` claim (RFC 7800, Section 3.2).
+pub fn confirmation_jwk(public_key: &[u8]) -> Result<Value, CredentialError> {
+    match public_key {
+        // Ed25519 (OKP)
+        x if x.len() == 32 => Ok(json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": URL_SAFE_NO_PAD.encode(x),
+        })),
+        // P-256 (EC) as an uncompressed point.
+        [0x04, coordinates @ ..] if coordinates.len() == 64 => Ok(json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": URL_SAFE_NO_PAD.encode(&coordinates[..32]),
+            "y": URL_SAFE_NO_PAD.encode(&coordinates[32..]),
+        })),
+        _ => Err(InvalidHolderKeyError("unsupported key type".to_string())),
+    }
+}
+
+fn salt() -> String {
+    agent_shared::generate_random_code(22, false)
+}
@@ -0,0 +1,404 @@
+use agent_shared::config::{ClaimMapping, CredentialTemplate, Transform};
+use serde_json::{json, Map, Value};
+
+use super::{entity::Data, error::CredentialError};
+
+/// Top-level properties that are set by UniCore itself and can therefore not be mapped by a template.
+pub const RESERVED_PROPERTIES: [&str; 10] = [
+    "@context",
+    "id",
+    "type",
+    "issuer",
+    "issuanceDate",
+    "expirationDate",
+    "credentialSubject",
+    "credentialSchema",
+    "credentialStatus",
+    "proof",
+];
+
+/// Makes sure that the `template` can be used to render credentials, i.e. that all of its paths are valid, that its
+/// claims do not conflict with each other and that it does not map any of the `RESERVED_PROPERTIES`.
+pub fn validate(template: &CredentialTemplate) -> Result<(), String> {
+    let mut claim_paths = vec![];
+    for (claim_path, claim_mapping) in &template.credential_subject {
+        claim_paths.push(segments(claim_path).ok_or(format!("the claim `{claim_path}` is not a valid path"))?);
+        validate_claim_mapping(claim_mapping)?;
+    }
+
+    // A claim cannot be nested inside another claim, e.g. `degree` and `degree.name`.
+    for (index, claim_path) in claim_paths.iter().enumerate() {
+        if let Some(other) = claim_paths[index + 1..]
+            .iter()
+            .find(|other| other.starts_with(claim_path) || claim_path.starts_with(other))
+        {
+            return Err(format!(
+                "the claim `{}` conflicts with the claim `{}`",
+                claim_path.join("."),
+                other.join(".")
+            ));
+        }
+    }
+
+    for (property_name, claim_mapping) in &template.properties {
+        if RESERVED_PROPERTIES.contains(&property_name.as_str()) {
+            return Err(format!("the property `{property_name}` cannot be mapped"));
+        }
+        validate_claim_mapping(claim_mapping)?;
+    }
+
+    Ok(())
+}
+
+/// Makes sure that the `field` of the `claim_mapping` (if any) is a valid path.
+fn validate_claim_mapping(claim_mapping: &ClaimMapping) -> Result<(), String> {
+    match &claim_mapping.field {
+        Some(field) if segments(field).is_none() => Err(format!("the field `{field}` is not a valid path")),
+        _ => Ok(()),
+    }
+}
+
+/// Maps the flat `record` onto the `credentialSubject` of a credential using the `template`. Returns the data of the
+/// credential together with its top-level properties.
+pub fn render(template: &CredentialTemplate, record: &Value) -> Result<(Data, Map<String, Value>), CredentialError> {
+    use CredentialError::*;
+
+    let record = record.as_object().ok_or(InvalidCredentialTemplateError(
+        "the input record must be an object".to_string(),
+    ))?;
+
+    let mut credential_subject = Value::Object(Map::new());
+    for (claim_path, claim_mapping) in &template.credential_subject {
+        if let Some(claim_value) = claim_value(claim_mapping, record)? {
+            insert(&mut credential_subject, claim_path, claim_value)?;
+        }
+    }
+
+    let mut properties = Map::new();
+    for (property_name, claim_mapping) in &template.properties {
+        if RESERVED_PROPERTIES.contains(&property_name.as_str()) {
+            return Err(InvalidCredentialTemplateError(format!(
+                "the property `{property_name}` cannot be mapped"
+            )));
+        }
+
+        if let Some(property_value) = claim_value(claim_mapping, record)? {
+            properties.insert(property_name.clone(), property_value);
+        }
+    }
+
+    Ok((
+        Data {
+            raw: json!({ "credentialSubject": credential_subject }),
+        },
+        properties,
+    ))
+}
+
+/// Returns the value of a single claim, or `None` when the claim is optional and missing from the `record`.
+fn claim_value(claim_mapping: &ClaimMapping, record: &Map<String, Value>) -> Result<Option<Value>, CredentialError> {
+    use CredentialError::*;
+
+    let ClaimMapping {
+        field,
+        value,
+        default,
+        optional,
+        transform,
+    } = claim_mapping;
+
+    let claim_value = match (value, field) {
+        (Some(value), _) => Some(value.clone()),
+        (None, Some(field)) => lookup(record, field)
+            .filter(|value| !value.is_null())
+            .cloned()
+            .or_else(|| default.clone()),
+        (None, None) => default.clone(),
+    };
+
+    match (claim_value, transform) {
+        (Some(claim_value), Some(transform)) => apply(*transform, claim_value).map(Some),
+        (Some(claim_value), None) => Ok(Some(claim_value)),
+        (None, _) if *optional => Ok(None),
+        (None, _) => Err(MissingTemplateFieldError(field.clone().unwrap_or_default())),
+    }
+}
+
+/// Splits a dot-separated `path` into its segments. Segments that contain dots themselves, such as the
+/// `org.iso.18013.5.1` namespace of `mso_mdoc` Credentials, are enclosed in square brackets, e.g.
+/// `[org.iso.18013.5.1].family_name`. Returns `None` when the `path` is not valid.
+fn segments(path: &str) -> Option<Vec<&str>> {
+    let mut segments = vec![];
+    let mut rest = path;
+
+    loop {
+        let (segment, remainder) = match rest.strip_prefix('[') {
+            Some(bracketed) => bracketed.split_once(']')?,
+            None => rest.split_at(rest.find('.').unwrap_or(rest.len())),
+        };
+
+        if segment.is_empty() {
+            return None;
+        }
+        segments.push(segment);
+
+        match remainder.strip_prefix('.') {
+            Some(remainder) => rest = remainder,
+            None if remainder.is_empty() => return Some(segments),
+            None => return None,
+        }
+    }
+}
+
+/// Looks up the value of a dot-separated `field` in the `record`.
+fn lookup<'a>(record: &'a Map<String, Value>, field: &str) -> Option<&'a Value> {
+    // Flat records may contain keys with dots, so an exact match takes precedence.
+    if let Some(value) = record.get(field) {
+        return Some(value);
+    }
+
+    let segments = segments(field)?;
+    let first = record.get(segments[0])?;
+    segments[1..]
+        .iter()
+        .try_fold(first, |value, segment| value.get(segment))
+}
+
+/// Inserts the `claim_value` at the dot-separated `claim_path`, creating the intermediate objects when needed.
+fn insert(credential_subject: &mut Value, claim_path: &str, claim_value: Value) -> Result<(), CredentialError> {
+    let mut segments = segments(claim_path).ok_or(CredentialError::InvalidCredentialTemplateError(format!(
+        "the claim `{claim_path}` is not a valid path"
+    )))?;
+    let last = segments.pop().unwrap_or_default();
+
+    let mut object = credential_subject;
+    for segment in segments {
+        object = object
+            .as_object_mut()
+            .ok_or(CredentialError::InvalidCredentialTemplateError(format!(
+                "the claim `{claim_path}` conflicts with another claim"
+            )))?
+            .entry(segment)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+
+    object
+        .as_object_mut()
+        .ok_or(CredentialError::InvalidCredentialTemplateError(format!(
+            "the claim `{claim_path}` conflicts with another claim"
+        )))?
+        .insert(last.to_string(), claim_value);
+
+    Ok(())
+}
+
+/// Applies the `transform` to the `value`.
+fn apply(transform: Transform, value: Value) -> Result<Value, CredentialError> {
+    let invalid = |value: &Value, expected: &str| {
+        CredentialError::InvalidTemplateFieldError(format!("`{value}` cannot be transformed into a {expected}"))
+    };
+
+    match transform {
+        Transform::Uppercase => value
+            .as_str()
+            .map(|s| json!(s.to_uppercase()))
+            .ok_or_else(|| invalid(&value, "string")),
+        Transform::Lowercase => value
+            .as_str()
+            .map(|s| json!(s.to_lowercase()))
+            .ok_or_else(|| invalid(&value, "string")),
+        Transform::Trim => value
+            .as_str()
+            .map(|s| json!(s.trim()))
+            .ok_or_else(|| invalid(&value, "string")),
+        Transform::String => match &value {
+            Value::String(_) => Ok(value),
+            Value::Number(_) | Value::Bool(_) => Ok(json!(value.to_string())),
+            _ => Err(invalid(&value, "string")),
+        },
+        Transform::Number => match &value {
+            Value::Number(_) => Ok(value),
+            Value::String(s) => s
+                .trim()
+                .parse::<i64>()
+                .map(|number| json!(number))
+                .or_else(|_| s.trim().parse::<f64>().map(|number| json!(number)))
+                .map_err(|_| invalid(&value, "number")),
+            _ => Err(invalid(&value, "number")),
+        },
+        Transform::Boolean => match &value {
+            Value::Bool(_) => Ok(value),
+            Value::String(s) => match s.trim().to_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok(json!(true)),
+                "false" | "no" | "0" => Ok(json!(false)),
+                _ => Err(invalid(&value, "boolean")),
+            },
+            Value::Number(number) => match number.as_i64() {
+                Some(1) => Ok(json!(true)),
+                Some(0) => Ok(json!(false)),
+                _ => Err(invalid(&value, "boolean")),
+            },
+            _ => Err(invalid(&value, "boolean")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn mapping(field: &str) -> ClaimMapping {
+        ClaimMapping {
+            field: Some(field.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let template = CredentialTemplate {
+            template_id: "employee".to_string(),
+            credential_subject: BTreeMap::from_iter([
+                (
+                    "first_name".to_string(),
+                    ClaimMapping {
+                        transform: Some(Transform::Trim),
+                        ..mapping("given_name")
+                    },
+                ),
+                (
+                    "last_name".to_string(),
+                    ClaimMapping {
+                        transform: Some(Transform::Uppercase),
+                        ..mapping("surname")
+                    },
+                ),
+                (
+                    "degree.type".to_string(),
+                    ClaimMapping {
+                        value: Some(json!("MasterDegree")),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "degree.name".to_string(),
+                    ClaimMapping {
+                        default: Some(json!("Unknown")),
+                        ..mapping("programme")
+                    },
+                ),
+                (
+                    "graduated".to_string(),
+                    ClaimMapping {
+                        transform: Some(Transform::Boolean),
+                        ..mapping("graduated")
+                    },
+                ),
+                (
+                    "email".to_string(),
+                    ClaimMapping {
+                        optional: true,
+                        ..mapping("email")
+                    },
+                ),
+            ]),
+            properties: BTreeMap::from_iter([(
+                "name".to_string(),
+                ClaimMapping {
+                    value: Some(json!("Degree Credential")),
+                    ..Default::default()
+                },
+            )]),
+        };
+
+        let (data, properties) = render(
+            &template,
+            &json!({
+                "given_name": " Ferris ",
+                "surname": "Rustacean",
+                "graduated": "yes"
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            data.raw,
+            json!({
+                "credentialSubject": {
+                    "first_name": "Ferris",
+                    "last_name": "RUSTACEAN",
+                    "degree": {
+                        "type": "MasterDegree",
+                        "name": "Unknown"
+                    },
+                    "graduated": true
+                }
+            })
+        );
+        assert_eq!(
+            properties,
+            json!({ "name": "Degree Credential" }).as_object().unwrap().clone()
+        );
+
+        // Fields without a default are required.
+        assert_eq!(
+            render(&template, &json!({ "given_name": "Ferris", "graduated": true }))
+                .unwrap_err()
+                .to_string(),
+            "The field `surname` is missing from the credential data"
+        );
+    }
+    #[test]
+    fn test_render_namespaced_claims() {
+        let template = CredentialTemplate {
+            template_id: "mdl".to_string(),
+            credential_subject: BTreeMap::from_iter([
+                ("[org.iso.18013.5.1].family_name".to_string(), mapping("surname")),
+                ("[org.iso.18013.5.1].given_name".to_string(), mapping("[person.name]")),
+            ]),
+            properties: Default::default(),
+        };
+        assert!(validate(&template).is_ok());
+
+        let (data, _) = render(&template, &json!({ "surname": "Rustacean", "person.name": "Ferris" })).unwrap();
+
+        assert_eq!(
+            data.raw,
+            json!({
+                "credentialSubject": {
+                    "org.iso.18013.5.1": {
+                        "family_name": "Rustacean",
+                        "given_name": "Ferris"
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let template = |claim_paths: &[&str], property_name: &str| CredentialTemplate {
+            template_id: "employee".to_string(),
+            credential_subject: claim_paths
+                .iter()
+                .map(|claim_path| (claim_path.to_string(), mapping("field")))
+                .collect(),
+            properties: BTreeMap::from_iter([(property_name.to_string(), mapping("field"))]),
+        };
+
+        assert!(validate(&template(&["degree.type", "degree.name"], "name")).is_ok());
+        assert_eq!(
+            validate(&template(&["degree", "degree.name"], "name")).unwrap_err(),
+            "the claim `degree` conflicts with the claim `degree.name`"
+        );
+        assert_eq!(
+            validate(&template(&["[org.iso.18013.5.1.family_name"], "name")).unwrap_err(),
+            "the claim `[org.iso.18013.5.1.family_name` is not a valid path"
+        );
+        assert_eq!(
+            validate(&template(&["first_name"], "credentialStatus")).unwrap_err(),
+            "the property `credentialStatus` cannot be mapped"
+        );
+    }
+}
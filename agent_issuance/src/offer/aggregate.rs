@@ -1,30 +1,183 @@
+use agent_shared::config::config;
+use agent_shared::dpop::DPOP;
+use agent_shared::notification::{NotificationEvent, NotificationRequest};
+use agent_shared::url_utils::UrlAppendHelpers;
 use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
 use cqrs_es::Aggregate;
 use oid4vc_core::Validator;
 use oid4vci::credential_issuer::CredentialIssuer;
-use oid4vci::credential_offer::{CredentialOffer, CredentialOfferParameters, Grants, PreAuthorizedCode};
-use oid4vci::credential_response::{CredentialResponse, CredentialResponseType};
+use oid4vci::credential_offer::{
+    AuthorizationCode, CredentialOffer, CredentialOfferParameters, Grants, PreAuthorizedCode, TransactionCode,
+};
+use oid4vci::credential_response::{BatchCredentialResponse, CredentialResponse, CredentialResponseType};
 use oid4vci::token_request::TokenRequest;
 use oid4vci::token_response::TokenResponse;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::offer::command::OfferCommand;
 use crate::offer::error::OfferError::{self, *};
 use crate::offer::event::OfferEvent;
 use crate::services::IssuanceServices;
 
+/// The maximum number of times a Wallet can provide a Transaction Code before the Pre-Authorized Code can no longer be
+/// redeemed.
+pub const MAX_TRANSACTION_CODE_ATTEMPTS: u8 = 3;
+
+/// The length of a Transaction Code when no length is specified.
+pub const DEFAULT_TRANSACTION_CODE_LENGTH: usize = 6;
+
+/// The maximum length of a Transaction Code.
+pub const MAX_TRANSACTION_CODE_LENGTH: usize = 16;
+
+/// The number of seconds a `c_nonce` can be used in the `Proof` of a Credential Request before it expires.
+pub const C_NONCE_EXPIRES_IN: u64 = 300;
+
+/// The default number of seconds during which a Pre-Authorized Code can be redeemed. Can be overridden with the
+/// `pre_authorized_code_expires_in` configuration option.
+pub const DEFAULT_PRE_AUTHORIZED_CODE_EXPIRES_IN: u64 = 3600;
+
+/// The default number of seconds during which an Access Token is valid. Can be overridden with the
+/// `access_token_expires_in` configuration option.
+pub const DEFAULT_ACCESS_TOKEN_EXPIRES_IN: u64 = 300;
+
+/// The default number of seconds during which a Refresh Token can be used. Can be overridden with the
+/// `refresh_token_expires_in` configuration option.
+pub const DEFAULT_REFRESH_TOKEN_EXPIRES_IN: u64 = 86400;
+
+/// The default number of seconds during which a Credential Offer can be redeemed. Can be overridden with the
+/// `offer_expires_in` configuration option.
+pub const DEFAULT_OFFER_EXPIRES_IN: u64 = 604800;
+
+/// The lifecycle status of a Credential Offer.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OfferStatus {
+    #[default]
+    Created,
+    Sent,
+    TokenIssued,
+    CredentialIssued,
+    Expired,
+    Cancelled,
+}
+
+impl OfferStatus {
+    /// Returns the status at this moment: a Credential Offer that has not been redeemed before `expires_at` is expired.
+    /// Expiry is not recorded as an event, so it is derived from the timestamp whenever the status is read.
+    pub fn current(self, expires_at: Option<i64>) -> Self {
+        match self {
+            OfferStatus::Created | OfferStatus::Sent
+                if expires_at.is_some_and(|expires_at| Utc::now().timestamp() >= expires_at) =>
+            {
+                OfferStatus::Expired
+            }
+            status => status,
+        }
+    }
+}
+
+/// The method that was used to derive the `code_challenge` from the `code_verifier` (PKCE, RFC 7636).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CodeChallengeMethod {
+    S256,
+    #[serde(rename = "plain")]
+    Plain,
+}
+
+impl CodeChallengeMethod {
+    /// Returns `true` if the `code_verifier` corresponds to the `code_challenge`.
+    pub fn verify(&self, code_verifier: &str, code_challenge: &str) -> bool {
+        match self {
+            CodeChallengeMethod::S256 => {
+                URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes())) == code_challenge
+            }
+            CodeChallengeMethod::Plain => code_verifier == code_challenge,
+        }
+    }
+}
+
+/// The Authorization Code that was issued to the Wallet in the Authorization Code Flow, together with the PKCE
+/// parameters that are needed to validate the subsequent Token Request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuthorizationCodeGrant {
+    pub authorization_code: String,
+    pub code_challenge: String,
+    pub code_challenge_method: CodeChallengeMethod,
+    pub redirect_uri: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Offer {
     pub credential_offer: Option<CredentialOffer>,
     pub subject_id: Option<String>,
     pub credential_ids: Vec<String>,
+    /// The id of the Credential Configuration of each credential, keyed by the id of the credential.
+    pub credential_configuration_ids: HashMap<String, String>,
     pub form_url_encoded_credential_offer: String,
     pub pre_authorized_code: String,
+    pub pre_authorized_code_expires_at: Option<i64>,
+    pub pre_authorized_code_redeemed: bool,
+    /// The hash of the Transaction Code, see `transaction_code_hash`.
+    pub transaction_code_hash: Option<String>,
+    pub transaction_code_attempts: u8,
+    pub issuer_state: String,
+    pub authorization_code_grant: Option<AuthorizationCodeGrant>,
+    pub authorization_code_redeemed: bool,
     pub token_response: Option<TokenResponse>,
     pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub refresh_token_expires_at: Option<i64>,
+    /// The JWK Thumbprint of the key the Access Token and Refresh Token are bound to using DPoP.
+    pub dpop_jkt: Option<String>,
+    pub c_nonce: Option<String>,
+    pub c_nonce_expires_at: Option<i64>,
     pub credential_response: Option<CredentialResponse>,
+    pub transaction_id: Option<String>,
+    /// The Credential Requests whose issuance is deferred and that have not been issued using the `transaction_id` yet.
+    pub deferred_credential_requests: Vec<serde_json::Value>,
+    /// The ids of the credentials that have been issued using the `transaction_id`.
+    pub deferred_credential_ids: Vec<String>,
+    pub batch_credential_response: Option<BatchCredentialResponse>,
+    /// The `notification_id`s of all the Credential Responses that have been created for this Credential Offer.
+    pub notification_ids: Vec<String>,
+    pub status: OfferStatus,
+    /// The timestamp at which the Credential Offer expires when it has not been redeemed.
+    pub expires_at: Option<i64>,
+}
+
+impl Offer {
+    /// Returns a Credential Response for the signed credential, including a fresh `notification_id`.
+    async fn credential_response(&self, signed_credential: serde_json::Value) -> CredentialResponse {
+        #[cfg(feature = "test_utils")]
+        let notification_id = test_utils::notification_id().await;
+        #[cfg(not(feature = "test_utils"))]
+        let notification_id = agent_shared::generate_random_string();
+
+        CredentialResponse {
+            credential: CredentialResponseType::Immediate {
+                credential: signed_credential,
+                notification_id: Some(notification_id),
+            },
+            c_nonce: self.c_nonce.clone(),
+            c_nonce_expires_in: self.c_nonce.as_ref().map(|_| C_NONCE_EXPIRES_IN),
+        }
+    }
+
+    /// Returns an error when the Credential Offer can no longer be redeemed at the Authorization or Token Endpoint.
+    fn ensure_redeemable(&self) -> Result<(), OfferError> {
+        match self.status.current(self.expires_at) {
+            OfferStatus::Expired => Err(OfferExpiredError),
+            OfferStatus::Cancelled => Err(OfferCancelledError),
+            _ => Ok(()),
+        }
+    }
 }
 
 #[async_trait]
@@ -48,28 +201,66 @@ impl Aggregate for Offer {
             CreateCredentialOffer {
                 offer_id,
                 credential_issuer_metadata,
+                credential_configuration_ids,
             } => {
                 #[cfg(feature = "test_utils")]
-                let (pre_authorized_code, access_token) = {
+                let (pre_authorized_code, pre_authorized_code_expires_at, issuer_state, access_token, expires_at) = {
                     let pre_authorized_code = test_utils::pre_authorized_code().await;
+                    let issuer_state = test_utils::issuer_state().await;
                     let access_token = test_utils::access_token().await;
-                    (pre_authorized_code, access_token)
+                    (
+                        pre_authorized_code,
+                        test_utils::PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                        issuer_state,
+                        access_token,
+                        test_utils::OFFER_EXPIRES_AT,
+                    )
                 };
                 #[cfg(not(feature = "test_utils"))]
-                let (pre_authorized_code, access_token) = {
+                let (pre_authorized_code, pre_authorized_code_expires_at, issuer_state, access_token, expires_at) = {
+                    let expires_in = config()
+                        .pre_authorized_code_expires_in
+                        .unwrap_or(DEFAULT_PRE_AUTHORIZED_CODE_EXPIRES_IN);
+                    let offer_expires_in = config().offer_expires_in.unwrap_or(DEFAULT_OFFER_EXPIRES_IN);
                     (
+                        agent_shared::generate_random_string(),
+                        Some(Utc::now().timestamp() + expires_in as i64),
                         agent_shared::generate_random_string(),
                         agent_shared::generate_random_string(),
+                        Utc::now().timestamp() + offer_expires_in as i64,
                     )
                 };
 
-                // TODO: This needs to be fixed when we implement Batch credentials.
-                let credentials_supported = credential_issuer_metadata.credential_configurations_supported.clone();
+                let credentials_supported = &credential_issuer_metadata.credential_configurations_supported;
+
+                if let Some(credential_configuration_id) =
+                    credential_configuration_ids.iter().find(|credential_configuration_id| {
+                        !credentials_supported.contains_key(*credential_configuration_id)
+                    })
+                {
+                    return Err(CredentialConfigurationNotFoundError(
+                        credential_configuration_id.clone(),
+                    ));
+                }
+
+                // When the credentials are not known yet, e.g. because they will be provided by an external server,
+                // all supported Credential Configurations are offered.
+                let credential_configuration_ids = if credential_configuration_ids.is_empty() {
+                    credentials_supported.keys().cloned().collect()
+                } else {
+                    credential_configuration_ids
+                };
+
                 let credential_offer = CredentialOffer::CredentialOffer(Box::new(CredentialOfferParameters {
                     credential_issuer: credential_issuer_metadata.credential_issuer.clone(),
-                    credential_configuration_ids: credentials_supported.keys().cloned().collect(),
+                    credential_configuration_ids,
                     grants: Some(Grants {
-                        authorization_code: None,
+                        // The Authorization Code Flow can only be used by Wallets that are registered as
+                        // `authorization_clients`.
+                        authorization_code: (!config().authorization_clients.is_empty()).then(|| AuthorizationCode {
+                            issuer_state: Some(issuer_state.clone()),
+                            ..Default::default()
+                        }),
                         pre_authorized_code: Some(PreAuthorizedCode {
                             pre_authorized_code: pre_authorized_code.clone(),
                             ..Default::default()
@@ -81,24 +272,58 @@ impl Aggregate for Offer {
                     offer_id,
                     credential_offer,
                     pre_authorized_code,
+                    pre_authorized_code_expires_at,
+                    issuer_state,
                     access_token,
+                    expires_at: Some(expires_at),
                 }])
             }
             AddCredentials {
                 offer_id,
                 credential_ids,
+                credential_configuration_id,
             } => Ok(vec![CredentialsAdded {
                 offer_id,
                 credential_ids,
+                credential_configuration_id: Some(credential_configuration_id),
             }]),
-            CreateFormUrlEncodedCredentialOffer { offer_id } => Ok(vec![FormUrlEncodedCredentialOfferCreated {
-                offer_id,
-                form_url_encoded_credential_offer: self
-                    .credential_offer
-                    .as_ref()
-                    .ok_or(MissingCredentialOfferError)?
-                    .to_string(),
-            }]),
+            CreateFormUrlEncodedCredentialOffer { offer_id, by_reference } => {
+                let credential_offer = self.credential_offer.as_ref().ok_or(MissingCredentialOfferError)?;
+
+                self.ensure_redeemable()?;
+
+                // The Credential Offer is referenced by a random id rather than by the `offer_id`, so that it cannot be
+                // retrieved by anyone who only knows the `offer_id`.
+                let credential_offer_uri_id = if by_reference {
+                    #[cfg(feature = "test_utils")]
+                    let credential_offer_uri_id = test_utils::credential_offer_uri_id().await;
+                    #[cfg(not(feature = "test_utils"))]
+                    let credential_offer_uri_id = agent_shared::generate_random_string();
+
+                    Some(credential_offer_uri_id)
+                } else {
+                    None
+                };
+
+                let form_url_encoded_credential_offer = match (credential_offer, &credential_offer_uri_id) {
+                    // The Wallet can retrieve the Credential Offer from the `credential_offer_uri`.
+                    (CredentialOffer::CredentialOffer(credential_offer_parameters), Some(credential_offer_uri_id)) => {
+                        CredentialOffer::CredentialOfferUri(
+                            credential_offer_parameters
+                                .credential_issuer
+                                .append_path_segment(&format!("openid4vci/credential_offer/{credential_offer_uri_id}")),
+                        )
+                        .to_string()
+                    }
+                    (credential_offer, _) => credential_offer.to_string(),
+                };
+
+                Ok(vec![FormUrlEncodedCredentialOfferCreated {
+                    offer_id,
+                    form_url_encoded_credential_offer,
+                    credential_offer_uri_id,
+                }])
+            }
             SendCredentialOffer { offer_id, target_url } => {
                 // TODO: add to `service`?
                 let client = reqwest::Client::new();
@@ -112,84 +337,432 @@ impl Aggregate for Offer {
 
                 Ok(vec![CredentialOfferSent { offer_id, target_url }])
             }
+            CreateTransactionCode {
+                offer_id,
+                mut tx_code,
+                transaction_code,
+            } => {
+                // The `tx_code` is added to the `pre-authorized_code` grant of the existing Credential Offer.
+                self.credential_offer.as_ref().ok_or(MissingCredentialOfferError)?;
+
+                let length = transaction_code.chars().count();
+                if !(1..=MAX_TRANSACTION_CODE_LENGTH).contains(&length) {
+                    return Err(InvalidTransactionCodeLengthError);
+                }
+                tx_code.length.replace(length as _);
+
+                Ok(vec![TransactionCodeCreated {
+                    offer_id,
+                    tx_code,
+                    transaction_code: None,
+                    transaction_code_hash: Some(transaction_code_hash(&transaction_code)),
+                }])
+            }
+            RegisterTransactionCodeAttempt { offer_id } => {
+                if self.transaction_code_hash.is_none() {
+                    return Ok(vec![]);
+                }
+
+                if self.transaction_code_attempts >= MAX_TRANSACTION_CODE_ATTEMPTS {
+                    return Err(TransactionCodeAttemptsExceededError);
+                }
+
+                Ok(vec![TransactionCodeAttemptRegistered {
+                    offer_id,
+                    attempts: self.transaction_code_attempts + 1,
+                }])
+            }
+            CreateAuthorizationCode {
+                offer_id,
+                code_challenge,
+                code_challenge_method,
+                redirect_uri,
+                tx_code,
+            } => {
+                self.ensure_redeemable()?;
+
+                // Once an Access Token has been issued, no new Authorization Codes can be created, since each of them
+                // would grant access to the same Credential Offer again.
+                if self.pre_authorized_code_redeemed || !matches!(self.status, OfferStatus::Created | OfferStatus::Sent)
+                {
+                    return Err(OfferAlreadyRedeemedError);
+                }
+
+                // The attempt itself has already been registered using `RegisterTransactionCodeAttempt`.
+                if let Some(expected_transaction_code_hash) = &self.transaction_code_hash {
+                    let tx_code = tx_code.ok_or(MissingTransactionCodeError)?;
+
+                    if transaction_code_hash(&tx_code) != *expected_transaction_code_hash {
+                        warn!("Invalid Transaction Code provided for Credential Offer: {offer_id}");
+
+                        return Err(InvalidTransactionCodeError);
+                    }
+                }
+
+                #[cfg(feature = "test_utils")]
+                let authorization_code = test_utils::authorization_code().await;
+                #[cfg(not(feature = "test_utils"))]
+                let authorization_code = agent_shared::generate_random_string();
+
+                Ok(vec![AuthorizationCodeCreated {
+                    offer_id,
+                    authorization_code,
+                    code_challenge,
+                    code_challenge_method,
+                    redirect_uri,
+                }])
+            }
             CreateTokenResponse {
                 offer_id,
                 token_request,
+                dpop_jkt,
+            } => {
+                self.ensure_redeemable()?;
+
+                let mut events = vec![];
+
+                match token_request {
+                    TokenRequest::PreAuthorizedCode {
+                        pre_authorized_code,
+                        tx_code,
+                    } => {
+                        // A Pre-Authorized Code can only be redeemed once.
+                        if self.pre_authorized_code_redeemed {
+                            return Err(PreAuthorizedCodeAlreadyRedeemedError);
+                        }
+
+                        if self
+                            .pre_authorized_code_expires_at
+                            .is_some_and(|expires_at| Utc::now().timestamp() >= expires_at)
+                        {
+                            return Err(PreAuthorizedCodeExpiredError);
+                        }
+
+                        // The attempt itself has already been registered using `RegisterTransactionCodeAttempt`.
+                        if let Some(expected_transaction_code_hash) = &self.transaction_code_hash {
+                            let tx_code = tx_code.ok_or(MissingTransactionCodeError)?;
+
+                            if transaction_code_hash(&tx_code) != *expected_transaction_code_hash {
+                                warn!("Invalid Transaction Code provided for Credential Offer: {offer_id}");
+
+                                return Err(InvalidTransactionCodeError);
+                            }
+                        }
+
+                        events.push(PreAuthorizedCodeRedeemed {
+                            offer_id: offer_id.clone(),
+                            pre_authorized_code,
+                        });
+                    }
+                    TokenRequest::AuthorizationCode {
+                        code,
+                        code_verifier,
+                        redirect_uri,
+                    } => {
+                        let AuthorizationCodeGrant {
+                            authorization_code,
+                            code_challenge,
+                            code_challenge_method,
+                            redirect_uri: authorization_redirect_uri,
+                        } = self
+                            .authorization_code_grant
+                            .as_ref()
+                            .ok_or(MissingAuthorizationCodeError)?;
+
+                        if *authorization_code != code {
+                            return Err(InvalidAuthorizationCodeError);
+                        }
+
+                        // An Authorization Code can only be redeemed once (RFC 6749, section 4.1.2).
+                        if self.authorization_code_redeemed {
+                            return Err(AuthorizationCodeAlreadyRedeemedError);
+                        }
+
+                        if *authorization_redirect_uri != redirect_uri {
+                            return Err(RedirectUriMismatchError);
+                        }
+
+                        let code_verifier = code_verifier.ok_or(MissingCodeVerifierError)?;
+
+                        if !code_challenge_method.verify(&code_verifier, code_challenge) {
+                            return Err(InvalidCodeVerifierError);
+                        }
+
+                        events.push(AuthorizationCodeRedeemed {
+                            offer_id: offer_id.clone(),
+                            authorization_code: code,
+                        });
+                    }
+                }
+
+                // Only a single Access Token can be issued per Credential Offer, using either of the grants.
+                if !matches!(self.status, OfferStatus::Created | OfferStatus::Sent) {
+                    return Err(OfferAlreadyRedeemedError);
+                }
+
+                // An Access Token that is already bound to a key can never be bound to another one.
+                if self.dpop_jkt.is_some() && self.dpop_jkt != dpop_jkt {
+                    return Err(DpopKeyMismatchError);
+                }
+
+                events.extend(token_response_events(offer_id, self.access_token.clone(), dpop_jkt).await);
+
+                Ok(events)
+            }
+            RefreshAccessToken {
+                offer_id,
+                refresh_token,
+                dpop_jkt,
             } => {
+                if self.status == OfferStatus::Cancelled {
+                    return Err(OfferCancelledError);
+                }
+
+                // Only the most recently issued Refresh Token can be used, any previous one has been rotated.
+                if self.refresh_token.as_ref() != Some(&refresh_token) {
+                    return Err(InvalidRefreshTokenError);
+                }
+
+                if self
+                    .refresh_token_expires_at
+                    .is_some_and(|expires_at| Utc::now().timestamp() >= expires_at)
+                {
+                    return Err(RefreshTokenExpiredError);
+                }
+
+                // A Refresh Token that was issued together with a DPoP-bound Access Token is bound to the same key.
+                if self.dpop_jkt.is_some() && self.dpop_jkt != dpop_jkt {
+                    return Err(DpopKeyMismatchError);
+                }
+
                 #[cfg(feature = "test_utils")]
-                let c_nonce = test_utils::c_nonce().await;
+                let access_token = test_utils::access_token().await;
                 #[cfg(not(feature = "test_utils"))]
-                let c_nonce = agent_shared::generate_random_string();
+                let access_token = agent_shared::generate_random_string();
 
-                match token_request {
-                    TokenRequest::PreAuthorizedCode { .. } => Ok(vec![TokenResponseCreated {
-                        offer_id,
-                        token_response: TokenResponse {
-                            access_token: self.access_token.clone(),
-                            token_type: "bearer".to_string(),
-                            expires_in: None,
-                            refresh_token: None,
-                            scope: None,
-                            c_nonce: Some(c_nonce),
-                            c_nonce_expires_in: None,
-                        },
-                    }]),
-                    _ => todo!(),
+                // The previous Access Token can no longer be used once it has been refreshed.
+                let mut events = vec![AccessTokenRevoked {
+                    offer_id: offer_id.clone(),
+                    access_token: self.access_token.clone(),
+                }];
+
+                events.extend(token_response_events(offer_id, access_token, dpop_jkt).await);
+
+                Ok(events)
+            }
+            CreateNonce { offer_id } => {
+                if self.status == OfferStatus::Cancelled {
+                    return Err(RevokedAccessTokenError);
                 }
+
+                let (c_nonce, c_nonce_expires_at) = generate_nonce().await;
+
+                Ok(vec![NonceCreated {
+                    offer_id,
+                    c_nonce,
+                    c_nonce_expires_at,
+                }])
             }
             VerifyCredentialRequest {
                 offer_id,
                 credential_issuer_metadata,
                 authorization_server_metadata,
-                credential_request,
+                credential_requests,
             } => {
+                // The Access Token of a cancelled Credential Offer can no longer be used to obtain credentials.
+                if self.status == OfferStatus::Cancelled {
+                    return Err(RevokedAccessTokenError);
+                }
+
                 let credential_issuer = CredentialIssuer {
                     subject: services.issuer.clone(),
                     metadata: *credential_issuer_metadata,
                     authorization_server_metadata: *authorization_server_metadata,
                 };
 
-                let proof = credential_issuer
-                    .validate_proof(
-                        credential_request.proof.ok_or(MissingProofError)?,
-                        Validator::Subject(services.issuer.clone()),
-                    )
-                    .await
-                    .map_err(|e| InvalidProofError(e.to_string()))?;
-
-                let subject_did = proof
-                    .rfc7519_claims
-                    .iss()
+                // The `nonce` must be bound to this Credential Offer to prevent Proofs from being replayed.
+                let (c_nonce, c_nonce_expires_at) = self
+                    .c_nonce
                     .as_ref()
-                    .ok_or(MissingProofIssuerError)?
-                    .clone();
+                    .zip(self.c_nonce_expires_at)
+                    .ok_or(MissingNonceError)?;
+
+                let mut events = vec![];
+                for credential_request in credential_requests {
+                    let proof = credential_issuer
+                        .validate_proof(
+                            credential_request.proof.ok_or(MissingProofError)?,
+                            Validator::Subject(services.issuer.clone()),
+                        )
+                        .await
+                        .map_err(|e| InvalidProofError(e.to_string()))?;
 
-                Ok(vec![CredentialRequestVerified {
+                    if proof.nonce != *c_nonce {
+                        return Err(InvalidNonceError);
+                    }
+
+                    if Utc::now().timestamp() >= c_nonce_expires_at {
+                        return Err(ExpiredNonceError);
+                    }
+
+                    let subject_did = proof
+                        .rfc7519_claims
+                        .iss()
+                        .as_ref()
+                        .ok_or(MissingProofIssuerError)?
+                        .clone();
+
+                    events.push(CredentialRequestVerified {
+                        offer_id: offer_id.clone(),
+                        subject_id: subject_did,
+                    });
+                }
+
+                // The `c_nonce` is consumed by the Credential Request, so a fresh one is created that the Wallet must
+                // use in the `Proof` of its next Credential Request.
+                let (c_nonce, c_nonce_expires_at) = generate_nonce().await;
+
+                events.push(NonceCreated {
                     offer_id,
-                    subject_id: subject_did,
-                }])
+                    c_nonce,
+                    c_nonce_expires_at,
+                });
+
+                Ok(events)
             }
             CreateCredentialResponse {
                 offer_id,
-                mut signed_credentials,
+                signed_credential,
+            } => Ok(vec![CredentialResponseCreated {
+                offer_id,
+                credential_response: self.credential_response(signed_credential).await,
+            }]),
+            CreateDeferredCredentialResponse {
+                offer_id,
+                credential_requests,
             } => {
-                // TODO: support batch credentials.
-                let signed_credential = signed_credentials.pop().ok_or(MissingCredentialError)?;
+                #[cfg(feature = "test_utils")]
+                let transaction_id = test_utils::transaction_id().await;
+                #[cfg(not(feature = "test_utils"))]
+                let transaction_id = agent_shared::generate_random_string();
 
                 let credential_response = CredentialResponse {
-                    credential: CredentialResponseType::Immediate {
-                        credential: signed_credential,
-                        notification_id: None,
+                    credential: CredentialResponseType::Deferred {
+                        transaction_id: transaction_id.clone(),
                     },
-                    c_nonce: None,
-                    c_nonce_expires_in: None,
+                    c_nonce: self.c_nonce.clone(),
+                    c_nonce_expires_in: self.c_nonce.as_ref().map(|_| C_NONCE_EXPIRES_IN),
                 };
 
-                Ok(vec![CredentialResponseCreated {
+                Ok(vec![DeferredCredentialResponseCreated {
                     offer_id,
+                    transaction_id,
                     credential_response,
+                    credential_requests: credential_requests.iter().map(|request| json!(request)).collect(),
+                }])
+            }
+            CreateBatchCredentialResponse {
+                offer_id,
+                signed_credentials,
+            } => {
+                if signed_credentials.is_empty() {
+                    return Err(MissingCredentialError);
+                }
+
+                #[cfg(feature = "test_utils")]
+                let notification_id = test_utils::notification_id().await;
+                #[cfg(not(feature = "test_utils"))]
+                let notification_id = agent_shared::generate_random_string();
+
+                // All Credentials in the Batch Credential Response share the same `notification_id`.
+                let batch_credential_response = BatchCredentialResponse {
+                    credential_responses: signed_credentials
+                        .into_iter()
+                        .map(|signed_credential| CredentialResponseType::Immediate {
+                            credential: signed_credential,
+                            notification_id: Some(notification_id.clone()),
+                        })
+                        .collect(),
+                    c_nonce: self.c_nonce.clone(),
+                    c_nonce_expires_in: self.c_nonce.as_ref().map(|_| C_NONCE_EXPIRES_IN),
+                };
+
+                Ok(vec![BatchCredentialResponseCreated {
+                    offer_id,
+                    batch_credential_response,
                 }])
             }
+            RedeemTransactionId {
+                offer_id,
+                transaction_id,
+                credential_id,
+                signed_credential,
+            } => {
+                // Each of the deferred Credential Requests can be redeemed exactly once, after which the
+                // `transaction_id` is no longer valid.
+                if self.transaction_id.as_ref() != Some(&transaction_id)
+                    || self.deferred_credential_requests.is_empty()
+                    || self.deferred_credential_ids.contains(&credential_id)
+                {
+                    return Err(InvalidTransactionIdError);
+                }
+
+                // The `transaction_id` is only redeemed together with the Credential Response, so that it cannot be
+                // redeemed for a credential that was never returned to the Wallet.
+                Ok(vec![
+                    TransactionIdRedeemed {
+                        offer_id: offer_id.clone(),
+                        transaction_id,
+                        credential_id,
+                    },
+                    CredentialResponseCreated {
+                        offer_id,
+                        credential_response: self.credential_response(signed_credential).await,
+                    },
+                ])
+            }
+            HandleNotification {
+                offer_id,
+                notification_request:
+                    NotificationRequest {
+                        notification_id,
+                        event,
+                        event_description,
+                    },
+            } => {
+                if !self.notification_ids.contains(&notification_id) {
+                    return Err(InvalidNotificationIdError);
+                }
+
+                let event = match event {
+                    NotificationEvent::CredentialAccepted => CredentialAccepted {
+                        offer_id,
+                        notification_id,
+                        event_description,
+                    },
+                    NotificationEvent::CredentialFailure => CredentialFailure {
+                        offer_id,
+                        notification_id,
+                        event_description,
+                    },
+                    NotificationEvent::CredentialDeleted => CredentialDeleted {
+                        offer_id,
+                        notification_id,
+                        event_description,
+                    },
+                };
+
+                Ok(vec![event])
+            }
+            CancelOffer { offer_id } => {
+                self.credential_offer.as_ref().ok_or(MissingCredentialOfferError)?;
+
+                match self.status {
+                    OfferStatus::Cancelled => Ok(vec![]),
+                    OfferStatus::CredentialIssued => Err(OfferNotCancellableError),
+                    _ => Ok(vec![OfferCancelled { offer_id }]),
+                }
+            }
         }
     }
 
@@ -201,16 +774,37 @@ impl Aggregate for Offer {
         match event {
             CredentialOfferCreated {
                 pre_authorized_code,
+                pre_authorized_code_expires_at,
+                issuer_state,
                 access_token,
                 credential_offer,
+                expires_at,
                 ..
             } => {
+                self.status = OfferStatus::Created;
+                self.expires_at = expires_at;
                 self.pre_authorized_code = pre_authorized_code;
+                self.pre_authorized_code_expires_at = pre_authorized_code_expires_at;
+                self.issuer_state = issuer_state;
                 self.access_token = access_token;
                 self.credential_offer.replace(credential_offer);
             }
-            CredentialsAdded { credential_ids, .. } => {
-                self.credential_ids = credential_ids;
+            CredentialsAdded {
+                credential_ids,
+                credential_configuration_id,
+                ..
+            } => {
+                if let Some(credential_configuration_id) = credential_configuration_id {
+                    if let Some(credential_offer) = self.credential_offer.as_mut() {
+                        add_credential_configuration_id(credential_offer, &credential_configuration_id);
+                    }
+                    self.credential_configuration_ids.extend(
+                        credential_ids
+                            .iter()
+                            .map(|credential_id| (credential_id.clone(), credential_configuration_id.clone())),
+                    );
+                }
+                self.credential_ids.extend(credential_ids);
             }
             FormUrlEncodedCredentialOfferCreated {
                 form_url_encoded_credential_offer,
@@ -218,70 +812,338 @@ impl Aggregate for Offer {
             } => {
                 self.form_url_encoded_credential_offer = form_url_encoded_credential_offer;
             }
-            CredentialOfferSent { .. } => {}
+            CredentialOfferSent { .. } => {
+                if self.status == OfferStatus::Created {
+                    self.status = OfferStatus::Sent;
+                }
+            }
+            TransactionCodeCreated {
+                tx_code,
+                transaction_code,
+                transaction_code_hash: hash,
+                ..
+            } => {
+                if let Some(credential_offer) = self.credential_offer.as_mut() {
+                    add_transaction_code(credential_offer, tx_code);
+                }
+                // Creating a new Transaction Code does not reset the number of attempts, otherwise the limit could be
+                // circumvented by creating the Credential Offer again.
+                self.transaction_code_hash = hash.or(transaction_code.as_deref().map(transaction_code_hash));
+            }
+            TransactionCodeVerificationFailed {
+                failed_attempts: attempts,
+                ..
+            }
+            | TransactionCodeAttemptRegistered { attempts, .. } => {
+                self.transaction_code_attempts = attempts;
+            }
+            AuthorizationCodeCreated {
+                authorization_code,
+                code_challenge,
+                code_challenge_method,
+                redirect_uri,
+                ..
+            } => {
+                self.authorization_code_grant.replace(AuthorizationCodeGrant {
+                    authorization_code,
+                    code_challenge,
+                    code_challenge_method,
+                    redirect_uri,
+                });
+                self.authorization_code_redeemed = false;
+            }
+            AuthorizationCodeRedeemed { .. } => {
+                self.authorization_code_redeemed = true;
+            }
             CredentialRequestVerified { subject_id, .. } => {
                 self.subject_id.replace(subject_id);
             }
-            TokenResponseCreated { token_response, .. } => {
+            PreAuthorizedCodeRedeemed { .. } => {
+                self.pre_authorized_code_redeemed = true;
+            }
+            TokenResponseCreated {
+                token_response,
+                refresh_token_expires_at,
+                dpop_jkt,
+                ..
+            } => {
+                if matches!(self.status, OfferStatus::Created | OfferStatus::Sent) {
+                    self.status = OfferStatus::TokenIssued;
+                }
+                self.access_token.clone_from(&token_response.access_token);
+                self.refresh_token.clone_from(&token_response.refresh_token);
+                self.refresh_token_expires_at = refresh_token_expires_at;
                 self.token_response.replace(token_response);
+                // An existing key binding is never replaced.
+                if self.dpop_jkt.is_none() {
+                    self.dpop_jkt = dpop_jkt;
+                }
+            }
+            AccessTokenRevoked { .. } => {}
+            NonceCreated {
+                c_nonce,
+                c_nonce_expires_at,
+                ..
+            } => {
+                self.c_nonce.replace(c_nonce);
+                self.c_nonce_expires_at.replace(c_nonce_expires_at);
             }
             CredentialResponseCreated {
                 credential_response, ..
             } => {
+                if let CredentialResponseType::Immediate {
+                    notification_id: Some(notification_id),
+                    ..
+                } = &credential_response.credential
+                {
+                    if !self.notification_ids.contains(notification_id) {
+                        self.notification_ids.push(notification_id.clone());
+                    }
+                }
+                self.status = OfferStatus::CredentialIssued;
+                self.credential_response.replace(credential_response);
+            }
+            DeferredCredentialResponseCreated {
+                transaction_id,
+                credential_response,
+                credential_requests,
+                ..
+            } => {
+                self.transaction_id.replace(transaction_id);
                 self.credential_response.replace(credential_response);
+                self.deferred_credential_requests = credential_requests;
+                self.deferred_credential_ids.clear();
+            }
+            BatchCredentialResponseCreated {
+                batch_credential_response,
+                ..
+            } => {
+                if let Some(CredentialResponseType::Immediate {
+                    notification_id: Some(notification_id),
+                    ..
+                }) = batch_credential_response.credential_responses.first()
+                {
+                    if !self.notification_ids.contains(notification_id) {
+                        self.notification_ids.push(notification_id.clone());
+                    }
+                }
+                self.status = OfferStatus::CredentialIssued;
+                self.batch_credential_response.replace(batch_credential_response);
+            }
+            TransactionIdRedeemed { credential_id, .. } => {
+                if !self.deferred_credential_requests.is_empty() {
+                    self.deferred_credential_requests.remove(0);
+                }
+                self.deferred_credential_ids.push(credential_id);
+                if self.deferred_credential_requests.is_empty() {
+                    self.transaction_id.take();
+                }
+            }
+            CredentialAccepted { .. } | CredentialFailure { .. } | CredentialDeleted { .. } => {}
+            OfferCancelled { .. } => {
+                self.status = OfferStatus::Cancelled;
             }
         }
     }
 }
 
-#[cfg(test)]
-pub mod tests {
-    use super::test_utils::*;
-    use crate::{
-        credential::aggregate::test_utils::OPENBADGE_VERIFIABLE_CREDENTIAL_JWT, server_config::aggregate::test_utils::*,
-    };
-    use agent_secret_manager::service::Service;
-    use cqrs_es::test::TestFramework;
-    use jsonwebtoken::Algorithm;
-    use oid4vc_core::Subject;
-    use oid4vci::{
-        credential_issuer::{
-            authorization_server_metadata::AuthorizationServerMetadata,
-            credential_issuer_metadata::CredentialIssuerMetadata,
-        },
-        credential_request::CredentialRequest,
-    };
+/// Generates a fresh `c_nonce` that the Wallet must include in the `Proof` of its next Credential Request, together with
+/// the timestamp at which it expires.
+async fn generate_nonce() -> (String, i64) {
+    #[cfg(feature = "test_utils")]
+    let nonce = (test_utils::c_nonce().await, test_utils::C_NONCE_EXPIRES_AT);
+    #[cfg(not(feature = "test_utils"))]
+    let nonce = (
+        agent_shared::generate_random_string(),
+        Utc::now().timestamp() + C_NONCE_EXPIRES_IN as i64,
+    );
 
-    use serde_json::json;
+    nonce
+}
 
-    type OfferTestFramework = TestFramework<Offer>;
+/// Returns the events for a Token Response containing the `access_token`, a fresh Refresh Token and a fresh `c_nonce`.
+/// When `dpop_jkt` is set, the Access Token is bound to that key and has the `DPoP` token type.
+async fn token_response_events(offer_id: String, access_token: String, dpop_jkt: Option<String>) -> Vec<OfferEvent> {
+    use OfferEvent::*;
 
-    #[rstest]
-    #[serial_test::serial]
-    async fn test_create_offer(
-        #[future(awt)] pre_authorized_code: String,
-        #[future(awt)] access_token: String,
-        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
-        #[future(awt)] credential_offer: CredentialOffer,
-    ) {
+    #[cfg(feature = "test_utils")]
+    let (refresh_token, access_token_expires_at, refresh_token_expires_at) = (
+        test_utils::refresh_token().await,
+        test_utils::ACCESS_TOKEN_EXPIRES_AT,
+        test_utils::REFRESH_TOKEN_EXPIRES_AT,
+    );
+    #[cfg(not(feature = "test_utils"))]
+    let (refresh_token, access_token_expires_at, refresh_token_expires_at) = {
+        let refresh_token_expires_in = config()
+            .refresh_token_expires_in
+            .unwrap_or(DEFAULT_REFRESH_TOKEN_EXPIRES_IN);
+        (
+            agent_shared::generate_random_string(),
+            Some(Utc::now().timestamp() + access_token_expires_in() as i64),
+            Some(Utc::now().timestamp() + refresh_token_expires_in as i64),
+        )
+    };
+
+    let (c_nonce, c_nonce_expires_at) = generate_nonce().await;
+
+    vec![
+        TokenResponseCreated {
+            offer_id: offer_id.clone(),
+            token_response: TokenResponse {
+                access_token,
+                token_type: match dpop_jkt {
+                    Some(_) => DPOP.to_string(),
+                    None => "bearer".to_string(),
+                },
+                expires_in: Some(access_token_expires_in()),
+                refresh_token: Some(refresh_token),
+                scope: None,
+                c_nonce: Some(c_nonce.clone()),
+                c_nonce_expires_in: Some(C_NONCE_EXPIRES_IN),
+            },
+            access_token_expires_at,
+            refresh_token_expires_at,
+            dpop_jkt,
+        },
+        NonceCreated {
+            offer_id,
+            c_nonce,
+            c_nonce_expires_at,
+        },
+    ]
+}
+
+/// Returns the number of seconds during which an Access Token is valid.
+fn access_token_expires_in() -> u64 {
+    config()
+        .access_token_expires_in
+        .unwrap_or(DEFAULT_ACCESS_TOKEN_EXPIRES_IN)
+}
+
+/// Adds the `credential_configuration_id` to the Credential Offer, unless it is already offered.
+pub(crate) fn add_credential_configuration_id(
+    credential_offer: &mut CredentialOffer,
+    credential_configuration_id: &str,
+) {
+    if let CredentialOffer::CredentialOffer(credential_offer_parameters) = credential_offer {
+        if !credential_offer_parameters
+            .credential_configuration_ids
+            .iter()
+            .any(|id| id == credential_configuration_id)
+        {
+            credential_offer_parameters
+                .credential_configuration_ids
+                .push(credential_configuration_id.to_string());
+        }
+    }
+}
+
+/// Generates a Transaction Code according to the `length` and `input_mode` of the `tx_code`. Defaults to a 6-digit
+/// Transaction Code when no length is specified.
+pub fn generate_transaction_code(tx_code: &TransactionCode) -> Result<String, OfferError> {
+    let length = tx_code
+        .length
+        .map(|length| length as usize)
+        .unwrap_or(DEFAULT_TRANSACTION_CODE_LENGTH);
+
+    if !(1..=MAX_TRANSACTION_CODE_LENGTH).contains(&length) {
+        return Err(InvalidTransactionCodeLengthError);
+    }
+
+    Ok(agent_shared::generate_random_code(
+        length,
+        tx_code.input_mode.as_deref() != Some("text"),
+    ))
+}
+
+/// Returns the base64url-encoded SHA-256 hash of a Transaction Code, which is stored instead of the Transaction Code.
+fn transaction_code_hash(transaction_code: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(transaction_code.as_bytes()))
+}
+
+/// Adds the `tx_code` to the `pre-authorized_code` grant of the Credential Offer.
+pub(crate) fn add_transaction_code(credential_offer: &mut CredentialOffer, tx_code: TransactionCode) {
+    if let CredentialOffer::CredentialOffer(credential_offer_parameters) = credential_offer {
+        if let Some(Grants {
+            pre_authorized_code: Some(pre_authorized_code),
+            ..
+        }) = credential_offer_parameters.grants.as_mut()
+        {
+            pre_authorized_code.tx_code.replace(tx_code);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::test_utils::*;
+    use super::transaction_code_hash;
+    use crate::{
+        credential::aggregate::test_utils::OPENBADGE_VERIFIABLE_CREDENTIAL_JWT, server_config::aggregate::test_utils::*,
+    };
+    use agent_secret_manager::service::Service;
+    use cqrs_es::test::TestFramework;
+    use jsonwebtoken::Algorithm;
+    use oid4vc_core::Subject;
+    use oid4vci::{
+        credential_issuer::{
+            authorization_server_metadata::AuthorizationServerMetadata,
+            credential_issuer_metadata::CredentialIssuerMetadata,
+        },
+        credential_request::CredentialRequest,
+    };
+
+    use serde_json::json;
+
+    type OfferTestFramework = TestFramework<Offer>;
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_offer(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        #[future(awt)] credential_offer: CredentialOffer,
+    ) {
         OfferTestFramework::with(Service::default())
             .given_no_previous_events()
             .when(OfferCommand::CreateCredentialOffer {
                 offer_id: Default::default(),
                 credential_issuer_metadata,
+                credential_configuration_ids: vec!["badge".to_string()],
             })
             .then_expect_events(vec![OfferEvent::CredentialOfferCreated {
                 offer_id: Default::default(),
                 credential_offer,
                 pre_authorized_code,
+                pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                expires_at: Some(OFFER_EXPIRES_AT),
+                issuer_state,
                 access_token,
             }]);
     }
 
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_offer_with_unknown_credential_configuration_id(
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(OfferCommand::CreateCredentialOffer {
+                offer_id: Default::default(),
+                credential_issuer_metadata,
+                credential_configuration_ids: vec!["unknown".to_string()],
+            })
+            .then_expect_error_message("No Credential Configuration found with id: `unknown`");
+    }
+
     #[rstest]
     #[serial_test::serial]
     async fn test_add_credential(
         #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
         #[future(awt)] access_token: String,
         #[future(awt)] credential_offer: CredentialOffer,
     ) {
@@ -290,25 +1152,1039 @@ pub mod tests {
                 offer_id: Default::default(),
                 credential_offer,
                 pre_authorized_code,
+                pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                expires_at: Some(OFFER_EXPIRES_AT),
+                issuer_state,
                 access_token,
             }])
             .when(OfferCommand::AddCredentials {
                 offer_id: Default::default(),
-                credential_ids: vec!["credential-id".to_string()],
+                credential_ids: vec!["credential-id".to_string()],
+                credential_configuration_id: "badge".to_string(),
+            })
+            .then_expect_events(vec![OfferEvent::CredentialsAdded {
+                offer_id: Default::default(),
+                credential_ids: vec!["credential-id".to_string()],
+                credential_configuration_id: Some("badge".to_string()),
+            }]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_credential_offer(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] form_url_encoded_credential_offer: String,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::CredentialsAdded {
+                    offer_id: Default::default(),
+                    credential_ids: vec!["credential-id".to_string()],
+                    credential_configuration_id: Some("badge".to_string()),
+                },
+            ])
+            .when(OfferCommand::CreateFormUrlEncodedCredentialOffer {
+                offer_id: Default::default(),
+                by_reference: false,
+            })
+            .then_expect_events(vec![OfferEvent::FormUrlEncodedCredentialOfferCreated {
+                offer_id: Default::default(),
+                form_url_encoded_credential_offer,
+                credential_offer_uri_id: None,
+            }]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_credential_offer_by_reference(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] credential_offer_uri_id: String,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![OfferEvent::CredentialOfferCreated {
+                offer_id: "offer-id".to_string(),
+                credential_offer,
+                pre_authorized_code,
+                pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                expires_at: Some(OFFER_EXPIRES_AT),
+                issuer_state,
+                access_token,
+            }])
+            .when(OfferCommand::CreateFormUrlEncodedCredentialOffer {
+                offer_id: "offer-id".to_string(),
+                by_reference: true,
+            })
+            .then_expect_events(vec![OfferEvent::FormUrlEncodedCredentialOfferCreated {
+                offer_id: "offer-id".to_string(),
+                form_url_encoded_credential_offer: format!(
+                    "openid-credential-offer://?credential_offer_uri=https%3A%2F%2Fexample.com%2Fopenid4vci%2Fcredential_offer%2F{credential_offer_uri_id}"
+                ),
+                credential_offer_uri_id: Some(credential_offer_uri_id),
+            }]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_token_response(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] form_url_encoded_credential_offer: String,
+        #[future(awt)] token_request: TokenRequest,
+        #[future(awt)] c_nonce: String,
+        #[future(awt)] token_response: TokenResponse,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code: pre_authorized_code.clone(),
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::CredentialsAdded {
+                    offer_id: Default::default(),
+                    credential_ids: vec!["credential-id".to_string()],
+                    credential_configuration_id: Some("badge".to_string()),
+                },
+                OfferEvent::FormUrlEncodedCredentialOfferCreated {
+                    offer_id: Default::default(),
+                    form_url_encoded_credential_offer,
+                    credential_offer_uri_id: None,
+                },
+            ])
+            .when(OfferCommand::CreateTokenResponse {
+                offer_id: Default::default(),
+                token_request,
+                dpop_jkt: None,
+            })
+            .then_expect_events(vec![
+                OfferEvent::PreAuthorizedCodeRedeemed {
+                    offer_id: Default::default(),
+                    pre_authorized_code,
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
+                },
+                OfferEvent::NonceCreated {
+                    offer_id: Default::default(),
+                    c_nonce,
+                    c_nonce_expires_at: C_NONCE_EXPIRES_AT,
+                },
+            ]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_authorization_code(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] authorization_code: String,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![OfferEvent::CredentialOfferCreated {
+                offer_id: Default::default(),
+                credential_offer,
+                pre_authorized_code,
+                pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                expires_at: Some(OFFER_EXPIRES_AT),
+                issuer_state,
+                access_token,
+            }])
+            .when(OfferCommand::CreateAuthorizationCode {
+                offer_id: Default::default(),
+                code_challenge: CODE_CHALLENGE.to_string(),
+                code_challenge_method: CodeChallengeMethod::S256,
+                redirect_uri: Some("https://wallet.example.org/callback".to_string()),
+                tx_code: None,
+            })
+            .then_expect_events(vec![OfferEvent::AuthorizationCodeCreated {
+                offer_id: Default::default(),
+                authorization_code,
+                code_challenge: CODE_CHALLENGE.to_string(),
+                code_challenge_method: CodeChallengeMethod::S256,
+                redirect_uri: Some("https://wallet.example.org/callback".to_string()),
+            }]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_authorization_code_after_token_response(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] token_response: TokenResponse,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code: pre_authorized_code.clone(),
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::PreAuthorizedCodeRedeemed {
+                    offer_id: Default::default(),
+                    pre_authorized_code,
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
+                },
+            ])
+            .when(OfferCommand::CreateAuthorizationCode {
+                offer_id: Default::default(),
+                code_challenge: CODE_CHALLENGE.to_string(),
+                code_challenge_method: CodeChallengeMethod::S256,
+                redirect_uri: Some("https://wallet.example.org/callback".to_string()),
+                tx_code: None,
+            })
+            .then_expect_error_message("An Access Token has already been issued for this Credential Offer");
+    }
+
+    #[rstest]
+    #[case::missing_transaction_code(None, "Missing `tx_code`")]
+    #[case::invalid_transaction_code(Some("000000".to_string()), "Invalid Transaction Code")]
+    #[serial_test::serial]
+    async fn test_create_authorization_code_requires_transaction_code(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] transaction_code: String,
+        tx_code: TransactionCode,
+        #[case] provided_transaction_code: Option<String>,
+        #[case] expected_error_message: &str,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::TransactionCodeCreated {
+                    offer_id: Default::default(),
+                    tx_code,
+                    transaction_code: None,
+                    transaction_code_hash: Some(transaction_code_hash(&transaction_code)),
+                },
+            ])
+            .when(OfferCommand::CreateAuthorizationCode {
+                offer_id: Default::default(),
+                code_challenge: CODE_CHALLENGE.to_string(),
+                code_challenge_method: CodeChallengeMethod::S256,
+                redirect_uri: Some("https://wallet.example.org/callback".to_string()),
+                tx_code: provided_transaction_code,
+            })
+            .then_expect_error_message(expected_error_message);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_token_response_with_authorization_code(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] authorization_code: String,
+        #[future(awt)] authorization_code_token_request: TokenRequest,
+        #[future(awt)] c_nonce: String,
+        #[future(awt)] token_response: TokenResponse,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::AuthorizationCodeCreated {
+                    offer_id: Default::default(),
+                    authorization_code: authorization_code.clone(),
+                    code_challenge: CODE_CHALLENGE.to_string(),
+                    code_challenge_method: CodeChallengeMethod::S256,
+                    redirect_uri: Some("https://wallet.example.org/callback".to_string()),
+                },
+            ])
+            .when(OfferCommand::CreateTokenResponse {
+                offer_id: Default::default(),
+                token_request: authorization_code_token_request,
+                dpop_jkt: None,
+            })
+            .then_expect_events(vec![
+                OfferEvent::AuthorizationCodeRedeemed {
+                    offer_id: Default::default(),
+                    authorization_code,
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
+                },
+                OfferEvent::NonceCreated {
+                    offer_id: Default::default(),
+                    c_nonce,
+                    c_nonce_expires_at: C_NONCE_EXPIRES_AT,
+                },
+            ]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_token_response_with_redeemed_authorization_code(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] authorization_code: String,
+        #[future(awt)] authorization_code_token_request: TokenRequest,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::AuthorizationCodeCreated {
+                    offer_id: Default::default(),
+                    authorization_code: authorization_code.clone(),
+                    code_challenge: CODE_CHALLENGE.to_string(),
+                    code_challenge_method: CodeChallengeMethod::S256,
+                    redirect_uri: Some("https://wallet.example.org/callback".to_string()),
+                },
+                OfferEvent::AuthorizationCodeRedeemed {
+                    offer_id: Default::default(),
+                    authorization_code,
+                },
+            ])
+            .when(OfferCommand::CreateTokenResponse {
+                offer_id: Default::default(),
+                token_request: authorization_code_token_request,
+                dpop_jkt: None,
+            })
+            .then_expect_error_message("The Authorization Code has already been redeemed");
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_token_response_with_invalid_code_verifier(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] authorization_code: String,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::AuthorizationCodeCreated {
+                    offer_id: Default::default(),
+                    authorization_code: authorization_code.clone(),
+                    code_challenge: CODE_CHALLENGE.to_string(),
+                    code_challenge_method: CodeChallengeMethod::S256,
+                    redirect_uri: Some("https://wallet.example.org/callback".to_string()),
+                },
+            ])
+            .when(OfferCommand::CreateTokenResponse {
+                offer_id: Default::default(),
+                token_request: TokenRequest::AuthorizationCode {
+                    code: authorization_code,
+                    code_verifier: Some("invalid-code-verifier".to_string()),
+                    redirect_uri: Some("https://wallet.example.org/callback".to_string()),
+                },
+                dpop_jkt: None,
+            })
+            .then_expect_error_message("The `code_verifier` does not match the `code_challenge`");
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_transaction_code(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] transaction_code: String,
+        tx_code: TransactionCode,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![OfferEvent::CredentialOfferCreated {
+                offer_id: Default::default(),
+                credential_offer,
+                pre_authorized_code,
+                pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                expires_at: Some(OFFER_EXPIRES_AT),
+                issuer_state,
+                access_token,
+            }])
+            .when(OfferCommand::CreateTransactionCode {
+                offer_id: Default::default(),
+                tx_code: tx_code.clone(),
+                transaction_code: transaction_code.clone(),
+            })
+            .then_expect_events(vec![OfferEvent::TransactionCodeCreated {
+                offer_id: Default::default(),
+                tx_code,
+                transaction_code: None,
+                transaction_code_hash: Some(transaction_code_hash(&transaction_code)),
+            }]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_transaction_code_with_invalid_length(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        tx_code: TransactionCode,
+    ) {
+        assert!(generate_transaction_code(&TransactionCode {
+            length: Some(1000),
+            ..tx_code.clone()
+        })
+        .is_err());
+
+        OfferTestFramework::with(Service::default())
+            .given(vec![OfferEvent::CredentialOfferCreated {
+                offer_id: Default::default(),
+                credential_offer,
+                pre_authorized_code,
+                pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                expires_at: Some(OFFER_EXPIRES_AT),
+                issuer_state,
+                access_token,
+            }])
+            .when(OfferCommand::CreateTransactionCode {
+                offer_id: Default::default(),
+                tx_code,
+                transaction_code: "0".repeat(MAX_TRANSACTION_CODE_LENGTH + 1),
+            })
+            .then_expect_error_message("The length of the Transaction Code must be between 1 and 16");
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_token_response_with_transaction_code(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] transaction_code: String,
+        tx_code: TransactionCode,
+        #[future(awt)] c_nonce: String,
+        #[future(awt)] token_response: TokenResponse,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code: pre_authorized_code.clone(),
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::TransactionCodeCreated {
+                    offer_id: Default::default(),
+                    tx_code,
+                    transaction_code: None,
+                    transaction_code_hash: Some(transaction_code_hash(&transaction_code)),
+                },
+                OfferEvent::TransactionCodeAttemptRegistered {
+                    offer_id: Default::default(),
+                    attempts: 1,
+                },
+            ])
+            .when(OfferCommand::CreateTokenResponse {
+                offer_id: Default::default(),
+                token_request: TokenRequest::PreAuthorizedCode {
+                    pre_authorized_code: pre_authorized_code.clone(),
+                    tx_code: Some(transaction_code),
+                },
+                dpop_jkt: None,
+            })
+            .then_expect_events(vec![
+                OfferEvent::PreAuthorizedCodeRedeemed {
+                    offer_id: Default::default(),
+                    pre_authorized_code,
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
+                },
+                OfferEvent::NonceCreated {
+                    offer_id: Default::default(),
+                    c_nonce,
+                    c_nonce_expires_at: C_NONCE_EXPIRES_AT,
+                },
+            ]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_token_response_with_invalid_transaction_code(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] transaction_code: String,
+        tx_code: TransactionCode,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code: pre_authorized_code.clone(),
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                // Transaction Codes that were created before only their hash was stored can still be verified.
+                OfferEvent::TransactionCodeCreated {
+                    offer_id: Default::default(),
+                    tx_code,
+                    transaction_code: Some(transaction_code),
+                    transaction_code_hash: None,
+                },
+                OfferEvent::TransactionCodeAttemptRegistered {
+                    offer_id: Default::default(),
+                    attempts: 1,
+                },
+            ])
+            .when(OfferCommand::CreateTokenResponse {
+                offer_id: Default::default(),
+                token_request: TokenRequest::PreAuthorizedCode {
+                    pre_authorized_code,
+                    tx_code: Some("000000".to_string()),
+                },
+                dpop_jkt: None,
+            })
+            .then_expect_error_message("Invalid Transaction Code");
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_register_transaction_code_attempt(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] transaction_code: String,
+        tx_code: TransactionCode,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::TransactionCodeCreated {
+                    offer_id: Default::default(),
+                    tx_code: tx_code.clone(),
+                    transaction_code: None,
+                    transaction_code_hash: Some(transaction_code_hash(&transaction_code)),
+                },
+                OfferEvent::TransactionCodeAttemptRegistered {
+                    offer_id: Default::default(),
+                    attempts: 1,
+                },
+                // A new Transaction Code does not reset the number of attempts.
+                OfferEvent::TransactionCodeCreated {
+                    offer_id: Default::default(),
+                    tx_code,
+                    transaction_code: None,
+                    transaction_code_hash: Some(transaction_code_hash(&transaction_code)),
+                },
+            ])
+            .when(OfferCommand::RegisterTransactionCodeAttempt {
+                offer_id: Default::default(),
+            })
+            .then_expect_events(vec![OfferEvent::TransactionCodeAttemptRegistered {
+                offer_id: Default::default(),
+                attempts: 2,
+            }]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_token_response_with_transaction_code_attempts_exceeded(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] transaction_code: String,
+        tx_code: TransactionCode,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code: pre_authorized_code.clone(),
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::TransactionCodeCreated {
+                    offer_id: Default::default(),
+                    tx_code,
+                    transaction_code: None,
+                    transaction_code_hash: Some(transaction_code_hash(&transaction_code)),
+                },
+                OfferEvent::TransactionCodeAttemptRegistered {
+                    offer_id: Default::default(),
+                    attempts: MAX_TRANSACTION_CODE_ATTEMPTS,
+                },
+            ])
+            .when(OfferCommand::RegisterTransactionCodeAttempt {
+                offer_id: Default::default(),
+            })
+            .then_expect_error_message("The maximum number of Transaction Code attempts has been exceeded");
+    }
+
+    #[rstest]
+    #[case::redeemed(
+        PRE_AUTHORIZED_CODE_EXPIRES_AT,
+        true,
+        "The Pre-Authorized Code has already been redeemed"
+    )]
+    #[case::expired(Some(0), false, "The Pre-Authorized Code has expired")]
+    #[serial_test::serial]
+    async fn test_create_token_response_with_unusable_pre_authorized_code(
+        #[case] pre_authorized_code_expires_at: Option<i64>,
+        #[case] redeemed: bool,
+        #[case] expected_error_message: &str,
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] token_request: TokenRequest,
+    ) {
+        let mut given = vec![OfferEvent::CredentialOfferCreated {
+            offer_id: Default::default(),
+            credential_offer,
+            pre_authorized_code: pre_authorized_code.clone(),
+            pre_authorized_code_expires_at,
+            issuer_state,
+            access_token,
+            expires_at: Some(OFFER_EXPIRES_AT),
+        }];
+
+        if redeemed {
+            given.push(OfferEvent::PreAuthorizedCodeRedeemed {
+                offer_id: Default::default(),
+                pre_authorized_code,
+            });
+        }
+
+        OfferTestFramework::with(Service::default())
+            .given(given)
+            .when(OfferCommand::CreateTokenResponse {
+                offer_id: Default::default(),
+                token_request,
+                dpop_jkt: None,
+            })
+            .then_expect_error_message(expected_error_message);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_refresh_access_token(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] refresh_token: String,
+        #[future(awt)] c_nonce: String,
+        #[future(awt)] token_response: TokenResponse,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response: token_response.clone(),
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
+                },
+            ])
+            .when(OfferCommand::RefreshAccessToken {
+                offer_id: Default::default(),
+                refresh_token,
+                dpop_jkt: None,
+            })
+            .then_expect_events(vec![
+                OfferEvent::AccessTokenRevoked {
+                    offer_id: Default::default(),
+                    access_token: token_response.access_token.clone(),
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
+                },
+                OfferEvent::NonceCreated {
+                    offer_id: Default::default(),
+                    c_nonce,
+                    c_nonce_expires_at: C_NONCE_EXPIRES_AT,
+                },
+            ]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_refresh_access_token_with_invalid_refresh_token(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] token_response: TokenResponse,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
+                },
+            ])
+            .when(OfferCommand::RefreshAccessToken {
+                offer_id: Default::default(),
+                refresh_token: "invalid-refresh-token".to_string(),
+                dpop_jkt: None,
+            })
+            .then_expect_error_message("Invalid Refresh Token");
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_refresh_access_token_with_expired_refresh_token(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] refresh_token: String,
+        #[future(awt)] token_response: TokenResponse,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: Some(0),
+                    dpop_jkt: None,
+                },
+            ])
+            .when(OfferCommand::RefreshAccessToken {
+                offer_id: Default::default(),
+                refresh_token,
+                dpop_jkt: None,
+            })
+            .then_expect_error_message("The Refresh Token has expired");
+    }
+
+    #[rstest]
+    #[case::same_key(Some("jkt"), true)]
+    #[case::other_key(Some("other-jkt"), false)]
+    #[case::no_key(None, false)]
+    #[serial_test::serial]
+    async fn test_refresh_dpop_bound_access_token(
+        #[case] dpop_jkt: Option<&str>,
+        #[case] accepted: bool,
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] refresh_token: String,
+        #[future(awt)] c_nonce: String,
+        #[future(awt)] token_response: TokenResponse,
+    ) {
+        let dpop_token_response = TokenResponse {
+            token_type: "DPoP".to_string(),
+            ..token_response
+        };
+
+        let when = OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response: dpop_token_response.clone(),
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: Some("jkt".to_string()),
+                },
+            ])
+            .when(OfferCommand::RefreshAccessToken {
+                offer_id: Default::default(),
+                refresh_token,
+                dpop_jkt: dpop_jkt.map(ToString::to_string),
+            });
+
+        if !accepted {
+            return when
+                .then_expect_error_message("The DPoP proof is not signed by the key the Refresh Token is bound to");
+        }
+
+        when.then_expect_events(vec![
+            OfferEvent::AccessTokenRevoked {
+                offer_id: Default::default(),
+                access_token: dpop_token_response.access_token.clone(),
+            },
+            OfferEvent::TokenResponseCreated {
+                offer_id: Default::default(),
+                token_response: dpop_token_response,
+                access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                dpop_jkt: Some("jkt".to_string()),
+            },
+            OfferEvent::NonceCreated {
+                offer_id: Default::default(),
+                c_nonce,
+                c_nonce_expires_at: C_NONCE_EXPIRES_AT,
+            },
+        ]);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_verify_credential_response(
+        holder: &Arc<dyn Subject>,
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] form_url_encoded_credential_offer: String,
+        #[future(awt)] c_nonce: String,
+        #[future(awt)] token_response: TokenResponse,
+        #[future(awt)] credential_request: CredentialRequest,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::CredentialsAdded {
+                    offer_id: Default::default(),
+                    credential_ids: vec!["credential-id".to_string()],
+                    credential_configuration_id: Some("badge".to_string()),
+                },
+                OfferEvent::FormUrlEncodedCredentialOfferCreated {
+                    offer_id: Default::default(),
+                    form_url_encoded_credential_offer,
+                    credential_offer_uri_id: None,
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
+                },
+                OfferEvent::NonceCreated {
+                    offer_id: Default::default(),
+                    c_nonce: c_nonce.clone(),
+                    c_nonce_expires_at: C_NONCE_EXPIRES_AT,
+                },
+            ])
+            .when(OfferCommand::VerifyCredentialRequest {
+                offer_id: Default::default(),
+                credential_issuer_metadata,
+                authorization_server_metadata,
+                credential_requests: vec![credential_request],
+            })
+            .then_expect_events(vec![
+                OfferEvent::CredentialRequestVerified {
+                    offer_id: Default::default(),
+                    subject_id: holder.identifier("did:key", Algorithm::EdDSA).await.unwrap(),
+                },
+                OfferEvent::NonceCreated {
+                    offer_id: Default::default(),
+                    c_nonce,
+                    c_nonce_expires_at: C_NONCE_EXPIRES_AT,
+                },
+            ]);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[rstest]
+    #[case::invalid_nonce(
+        Some("replayed-nonce"),
+        C_NONCE_EXPIRES_AT,
+        "The `nonce` in the `Proof` does not match the `c_nonce` that was issued for this Credential Offer"
+    )]
+    #[case::expired_nonce(None, 0, "The `c_nonce` has expired")]
+    #[serial_test::serial]
+    async fn test_verify_credential_request_with_invalid_nonce(
+        #[case] issued_c_nonce: Option<&str>,
+        #[case] c_nonce_expires_at: i64,
+        #[case] expected_error_message: &str,
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] c_nonce: String,
+        #[future(awt)] credential_request: CredentialRequest,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::NonceCreated {
+                    offer_id: Default::default(),
+                    c_nonce: issued_c_nonce.map(ToString::to_string).unwrap_or(c_nonce),
+                    c_nonce_expires_at,
+                },
+            ])
+            .when(OfferCommand::VerifyCredentialRequest {
+                offer_id: Default::default(),
+                credential_issuer_metadata,
+                authorization_server_metadata,
+                credential_requests: vec![credential_request],
             })
-            .then_expect_events(vec![OfferEvent::CredentialsAdded {
-                offer_id: Default::default(),
-                credential_ids: vec!["credential-id".to_string()],
-            }]);
+            .then_expect_error_message(expected_error_message);
     }
 
     #[rstest]
     #[serial_test::serial]
-    async fn test_create_credential_offer(
+    async fn test_create_credential_response(
+        holder: &Arc<dyn Subject>,
         #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
         #[future(awt)] access_token: String,
         #[future(awt)] credential_offer: CredentialOffer,
         #[future(awt)] form_url_encoded_credential_offer: String,
+        #[future(awt)] token_response: TokenResponse,
+        #[future(awt)] c_nonce: String,
+        #[future(awt)] credential_response: CredentialResponse,
     ) {
         OfferTestFramework::with(Service::default())
             .given(vec![
@@ -316,31 +2192,64 @@ pub mod tests {
                     offer_id: Default::default(),
                     credential_offer,
                     pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
                     access_token,
                 },
                 OfferEvent::CredentialsAdded {
                     offer_id: Default::default(),
                     credential_ids: vec!["credential-id".to_string()],
+                    credential_configuration_id: Some("badge".to_string()),
+                },
+                OfferEvent::FormUrlEncodedCredentialOfferCreated {
+                    offer_id: Default::default(),
+                    form_url_encoded_credential_offer,
+                    credential_offer_uri_id: None,
+                },
+                OfferEvent::TokenResponseCreated {
+                    offer_id: Default::default(),
+                    token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
+                },
+                OfferEvent::CredentialRequestVerified {
+                    offer_id: Default::default(),
+                    subject_id: holder.identifier("did:key", Algorithm::EdDSA).await.unwrap(),
+                },
+                OfferEvent::NonceCreated {
+                    offer_id: Default::default(),
+                    c_nonce: c_nonce.clone(),
+                    c_nonce_expires_at: C_NONCE_EXPIRES_AT,
                 },
             ])
-            .when(OfferCommand::CreateFormUrlEncodedCredentialOffer {
+            .when(OfferCommand::CreateCredentialResponse {
                 offer_id: Default::default(),
+                signed_credential: json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT),
             })
-            .then_expect_events(vec![OfferEvent::FormUrlEncodedCredentialOfferCreated {
+            // The Credential Response contains the fresh `c_nonce` for the next Credential Request.
+            .then_expect_events(vec![OfferEvent::CredentialResponseCreated {
                 offer_id: Default::default(),
-                form_url_encoded_credential_offer,
+                credential_response: CredentialResponse {
+                    c_nonce: Some(c_nonce),
+                    c_nonce_expires_in: Some(C_NONCE_EXPIRES_IN),
+                    ..credential_response
+                },
             }]);
     }
 
     #[rstest]
     #[serial_test::serial]
-    async fn test_create_token_response(
+    async fn test_create_deferred_credential_response(
+        holder: &Arc<dyn Subject>,
         #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
         #[future(awt)] access_token: String,
         #[future(awt)] credential_offer: CredentialOffer,
-        #[future(awt)] form_url_encoded_credential_offer: String,
-        #[future(awt)] token_request: TokenRequest,
         #[future(awt)] token_response: TokenResponse,
+        #[future(awt)] transaction_id: String,
+        #[future(awt)] credential_request: CredentialRequest,
     ) {
         OfferTestFramework::with(Service::default())
             .given(vec![
@@ -348,40 +2257,110 @@ pub mod tests {
                     offer_id: Default::default(),
                     credential_offer,
                     pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
                     access_token,
                 },
-                OfferEvent::CredentialsAdded {
+                OfferEvent::TokenResponseCreated {
                     offer_id: Default::default(),
-                    credential_ids: vec!["credential-id".to_string()],
+                    token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
                 },
-                OfferEvent::FormUrlEncodedCredentialOfferCreated {
+                OfferEvent::CredentialRequestVerified {
                     offer_id: Default::default(),
-                    form_url_encoded_credential_offer,
+                    subject_id: holder.identifier("did:key", Algorithm::EdDSA).await.unwrap(),
                 },
             ])
-            .when(OfferCommand::CreateTokenResponse {
+            .when(OfferCommand::CreateDeferredCredentialResponse {
                 offer_id: Default::default(),
-                token_request,
+                credential_requests: vec![credential_request.clone()],
             })
-            .then_expect_events(vec![OfferEvent::TokenResponseCreated {
+            .then_expect_events(vec![OfferEvent::DeferredCredentialResponseCreated {
                 offer_id: Default::default(),
-                token_response,
+                transaction_id: transaction_id.clone(),
+                credential_response: CredentialResponse {
+                    credential: CredentialResponseType::Deferred { transaction_id },
+                    c_nonce: None,
+                    c_nonce_expires_in: None,
+                },
+                credential_requests: vec![json!(credential_request)],
             }]);
     }
 
-    #[allow(clippy::too_many_arguments)]
     #[rstest]
     #[serial_test::serial]
-    async fn test_verify_credential_response(
+    async fn test_redeem_transaction_id(
+        #[future(awt)] transaction_id: String,
+        #[future(awt)] credential_request: CredentialRequest,
+        #[future(awt)] credential_response: CredentialResponse,
+    ) {
+        let deferred_credential_response_created = OfferEvent::DeferredCredentialResponseCreated {
+            offer_id: Default::default(),
+            transaction_id: transaction_id.clone(),
+            credential_response: CredentialResponse {
+                credential: CredentialResponseType::Deferred {
+                    transaction_id: transaction_id.clone(),
+                },
+                c_nonce: None,
+                c_nonce_expires_in: None,
+            },
+            credential_requests: vec![json!(credential_request)],
+        };
+
+        OfferTestFramework::with(Service::default())
+            .given(vec![deferred_credential_response_created.clone()])
+            .when(OfferCommand::RedeemTransactionId {
+                offer_id: Default::default(),
+                transaction_id: transaction_id.clone(),
+                credential_id: "credential-1".to_string(),
+                signed_credential: json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT),
+            })
+            .then_expect_events(vec![
+                OfferEvent::TransactionIdRedeemed {
+                    offer_id: Default::default(),
+                    transaction_id: transaction_id.clone(),
+                    credential_id: "credential-1".to_string(),
+                },
+                OfferEvent::CredentialResponseCreated {
+                    offer_id: Default::default(),
+                    credential_response,
+                },
+            ]);
+
+        // The `transaction_id` is invalidated once all of its credentials have been issued.
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                deferred_credential_response_created,
+                OfferEvent::TransactionIdRedeemed {
+                    offer_id: Default::default(),
+                    transaction_id: transaction_id.clone(),
+                    credential_id: "credential-1".to_string(),
+                },
+            ])
+            .when(OfferCommand::RedeemTransactionId {
+                offer_id: Default::default(),
+                transaction_id,
+                credential_id: "credential-2".to_string(),
+                signed_credential: json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT),
+            })
+            .then_expect_error_message(
+                "The `transaction_id` is invalid or all of its credentials have already been issued",
+            );
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_batch_credential_response(
         holder: &Arc<dyn Subject>,
         #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
         #[future(awt)] access_token: String,
         #[future(awt)] credential_offer: CredentialOffer,
-        #[future(awt)] form_url_encoded_credential_offer: String,
         #[future(awt)] token_response: TokenResponse,
-        #[future(awt)] credential_request: CredentialRequest,
-        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
-        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        #[future(awt)] batch_credential_response: BatchCredentialResponse,
     ) {
         OfferTestFramework::with(Service::default())
             .given(vec![
@@ -389,43 +2368,200 @@ pub mod tests {
                     offer_id: Default::default(),
                     credential_offer,
                     pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
                     access_token,
                 },
                 OfferEvent::CredentialsAdded {
                     offer_id: Default::default(),
-                    credential_ids: vec!["credential-id".to_string()],
-                },
-                OfferEvent::FormUrlEncodedCredentialOfferCreated {
-                    offer_id: Default::default(),
-                    form_url_encoded_credential_offer,
+                    credential_ids: vec!["credential-id-1".to_string(), "credential-id-2".to_string()],
+                    credential_configuration_id: Some("badge".to_string()),
                 },
                 OfferEvent::TokenResponseCreated {
                     offer_id: Default::default(),
                     token_response,
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
+                },
+                OfferEvent::CredentialRequestVerified {
+                    offer_id: Default::default(),
+                    subject_id: holder.identifier("did:key", Algorithm::EdDSA).await.unwrap(),
                 },
             ])
-            .when(OfferCommand::VerifyCredentialRequest {
+            .when(OfferCommand::CreateBatchCredentialResponse {
                 offer_id: Default::default(),
-                credential_issuer_metadata,
-                authorization_server_metadata,
-                credential_request,
+                signed_credentials: vec![
+                    json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT),
+                    json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT),
+                ],
             })
-            .then_expect_events(vec![OfferEvent::CredentialRequestVerified {
+            .then_expect_events(vec![OfferEvent::BatchCredentialResponseCreated {
                 offer_id: Default::default(),
-                subject_id: holder.identifier("did:key", Algorithm::EdDSA).await.unwrap(),
+                batch_credential_response,
             }]);
     }
 
     #[rstest]
+    #[case::credential_accepted(NotificationEvent::CredentialAccepted)]
+    #[case::credential_failure(NotificationEvent::CredentialFailure)]
+    #[case::credential_deleted(NotificationEvent::CredentialDeleted)]
     #[serial_test::serial]
-    async fn test_create_credential_response(
-        holder: &Arc<dyn Subject>,
+    async fn test_handle_notification(
+        #[case] event: NotificationEvent,
+        #[future(awt)] credential_response: CredentialResponse,
+        #[future(awt)] notification_id: String,
+    ) {
+        let event_description = Some("Example description".to_string());
+
+        let expected_event = match event {
+            NotificationEvent::CredentialAccepted => OfferEvent::CredentialAccepted {
+                offer_id: Default::default(),
+                notification_id: notification_id.clone(),
+                event_description: event_description.clone(),
+            },
+            NotificationEvent::CredentialFailure => OfferEvent::CredentialFailure {
+                offer_id: Default::default(),
+                notification_id: notification_id.clone(),
+                event_description: event_description.clone(),
+            },
+            NotificationEvent::CredentialDeleted => OfferEvent::CredentialDeleted {
+                offer_id: Default::default(),
+                notification_id: notification_id.clone(),
+                event_description: event_description.clone(),
+            },
+        };
+
+        OfferTestFramework::with(Service::default())
+            .given(vec![OfferEvent::CredentialResponseCreated {
+                offer_id: Default::default(),
+                credential_response,
+            }])
+            .when(OfferCommand::HandleNotification {
+                offer_id: Default::default(),
+                notification_request: NotificationRequest {
+                    notification_id,
+                    event,
+                    event_description,
+                },
+            })
+            .then_expect_events(vec![expected_event]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_handle_notification_for_previous_credential_response(
+        #[future(awt)] credential_response: CredentialResponse,
+        #[future(awt)] notification_id: String,
+    ) {
+        let mut next_credential_response = credential_response.clone();
+        if let CredentialResponseType::Immediate {
+            notification_id: next_notification_id,
+            ..
+        } = &mut next_credential_response.credential
+        {
+            next_notification_id.replace("next-notification-id".to_string());
+        }
+
+        // The `notification_id` of a previous Credential Response remains valid after a new one has been created.
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialResponseCreated {
+                    offer_id: Default::default(),
+                    credential_response,
+                },
+                OfferEvent::CredentialResponseCreated {
+                    offer_id: Default::default(),
+                    credential_response: next_credential_response,
+                },
+            ])
+            .when(OfferCommand::HandleNotification {
+                offer_id: Default::default(),
+                notification_request: NotificationRequest {
+                    notification_id: notification_id.clone(),
+                    event: NotificationEvent::CredentialAccepted,
+                    event_description: None,
+                },
+            })
+            .then_expect_events(vec![OfferEvent::CredentialAccepted {
+                offer_id: Default::default(),
+                notification_id,
+                event_description: None,
+            }]);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_handle_notification_with_invalid_notification_id(
+        #[future(awt)] credential_response: CredentialResponse,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![OfferEvent::CredentialResponseCreated {
+                offer_id: Default::default(),
+                credential_response,
+            }])
+            .when(OfferCommand::HandleNotification {
+                offer_id: Default::default(),
+                notification_request: NotificationRequest {
+                    notification_id: "invalid-notification-id".to_string(),
+                    event: NotificationEvent::CredentialAccepted,
+                    event_description: None,
+                },
+            })
+            .then_expect_error_message(
+                "The `notification_id` does not belong to a Credential that was issued for this Credential Offer",
+            );
+    }
+
+    #[rstest]
+    #[case::expired(Some(0), false, "The Credential Offer has expired")]
+    #[case::cancelled(Some(OFFER_EXPIRES_AT), true, "The Credential Offer has been cancelled")]
+    #[serial_test::serial]
+    async fn test_create_token_response_with_unredeemable_offer(
+        #[case] expires_at: Option<i64>,
+        #[case] cancelled: bool,
+        #[case] expected_error_message: &str,
         #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] token_request: TokenRequest,
+    ) {
+        let mut given = vec![OfferEvent::CredentialOfferCreated {
+            offer_id: Default::default(),
+            credential_offer,
+            pre_authorized_code,
+            pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+            expires_at,
+            issuer_state,
+            access_token,
+        }];
+
+        if cancelled {
+            given.push(OfferEvent::OfferCancelled {
+                offer_id: Default::default(),
+            });
+        }
+
+        OfferTestFramework::with(Service::default())
+            .given(given)
+            .when(OfferCommand::CreateTokenResponse {
+                offer_id: Default::default(),
+                token_request,
+                dpop_jkt: None,
+            })
+            .then_expect_error_message(expected_error_message);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_cancel_offer(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
         #[future(awt)] access_token: String,
         #[future(awt)] credential_offer: CredentialOffer,
-        #[future(awt)] form_url_encoded_credential_offer: String,
         #[future(awt)] token_response: TokenResponse,
-        credential_response: CredentialResponse,
     ) {
         OfferTestFramework::with(Service::default())
             .given(vec![
@@ -433,34 +2569,57 @@ pub mod tests {
                     offer_id: Default::default(),
                     credential_offer,
                     pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
                     access_token,
                 },
-                OfferEvent::CredentialsAdded {
-                    offer_id: Default::default(),
-                    credential_ids: vec!["credential-id".to_string()],
-                },
-                OfferEvent::FormUrlEncodedCredentialOfferCreated {
-                    offer_id: Default::default(),
-                    form_url_encoded_credential_offer,
-                },
                 OfferEvent::TokenResponseCreated {
                     offer_id: Default::default(),
                     token_response,
-                },
-                OfferEvent::CredentialRequestVerified {
-                    offer_id: Default::default(),
-                    subject_id: holder.identifier("did:key", Algorithm::EdDSA).await.unwrap(),
+                    access_token_expires_at: ACCESS_TOKEN_EXPIRES_AT,
+                    refresh_token_expires_at: REFRESH_TOKEN_EXPIRES_AT,
+                    dpop_jkt: None,
                 },
             ])
-            .when(OfferCommand::CreateCredentialResponse {
+            .when(OfferCommand::CancelOffer {
                 offer_id: Default::default(),
-                signed_credentials: vec![json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT)],
             })
-            .then_expect_events(vec![OfferEvent::CredentialResponseCreated {
+            .then_expect_events(vec![OfferEvent::OfferCancelled {
                 offer_id: Default::default(),
-                credential_response,
             }]);
     }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_cancel_offer_with_issued_credential(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+        #[future(awt)] access_token: String,
+        #[future(awt)] credential_offer: CredentialOffer,
+        #[future(awt)] credential_response: CredentialResponse,
+    ) {
+        OfferTestFramework::with(Service::default())
+            .given(vec![
+                OfferEvent::CredentialOfferCreated {
+                    offer_id: Default::default(),
+                    credential_offer,
+                    pre_authorized_code,
+                    pre_authorized_code_expires_at: PRE_AUTHORIZED_CODE_EXPIRES_AT,
+                    expires_at: Some(OFFER_EXPIRES_AT),
+                    issuer_state,
+                    access_token,
+                },
+                OfferEvent::CredentialResponseCreated {
+                    offer_id: Default::default(),
+                    credential_response,
+                },
+            ])
+            .when(OfferCommand::CancelOffer {
+                offer_id: Default::default(),
+            })
+            .then_expect_error_message("A Credential Offer cannot be cancelled after its credentials have been issued");
+    }
 }
 
 #[cfg(feature = "test_utils")]
@@ -491,27 +2650,115 @@ pub mod test_utils {
         pub static ref SUBJECT_KEY_DID: Arc<dyn oid4vc_core::Subject> = IssuanceServices::default().issuer.clone();
     }
 
+    /// The pre-signed `jwt` Proof of the holder that is used in the Credential Requests of the REST API tests. It
+    /// contains the `c_nonce` and never expires.
+    pub const PROOF_JWT: &str = "eyJ0eXAiOiJvcGVuaWQ0dmNpLXByb29mK2p3dCIsImFsZyI6IkVkRFNBIiwia2lk\
+        IjoiZGlkOmtleTp6Nk1raWlleW9MTVNWc0pBWnY3SmplNXdXU2tERXltVWdreUY4\
+        a2JjcmpacFgzcWQjejZNa2lpZXlvTE1TVnNKQVp2N0pqZTV3V1NrREV5bVVna3lG\
+        OGtiY3JqWnBYM3FkIn0.eyJpc3MiOiJkaWQ6a2V5Ono2TWtpaWV5b0xNU1ZzSkFa\
+        djdKamU1d1dTa0RFeW1VZ2t5RjhrYmNyalpwWDNxZCIsImF1ZCI6Imh0dHBzOi8v\
+        ZXhhbXBsZS5jb20vIiwiZXhwIjo5OTk5OTk5OTk5LCJpYXQiOjE1NzEzMjQ4MDAs\
+        Im5vbmNlIjoiN2UwM2FkM2Y3NmNiMzMzOGMzYTU2NDJmZTc2MzQ0NzZhYTNhZDkz\
+        ZmExZDU4NDAxMWJhMjE1MGQ5ZGE0NzEzMyJ9.bDxmEWTGwKJJC8J5N16JHAR2ZBY\
+        tgWlhM_o_voJdXLnw_ScZMwGjZwNH6aQWKlgIaFWKonF88KNRFX2UAOAuBQ";
+
+    /// PKCE example values taken from RFC 7636, Appendix B.
+    pub const CODE_VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    pub const CODE_CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+    /// The `c_nonce` used in tests never expires.
+    pub const C_NONCE_EXPIRES_AT: i64 = 9999999999;
+
+    /// The Pre-Authorized Code used in tests never expires.
+    pub const PRE_AUTHORIZED_CODE_EXPIRES_AT: Option<i64> = Some(9999999999);
+
+    /// The Access Token used in tests never expires.
+    pub const ACCESS_TOKEN_EXPIRES_AT: Option<i64> = Some(9999999999);
+
+    /// The Refresh Token used in tests never expires.
+    pub const REFRESH_TOKEN_EXPIRES_AT: Option<i64> = Some(9999999999);
+
+    /// The Credential Offer used in tests never expires.
+    pub const OFFER_EXPIRES_AT: i64 = 9999999999;
+
     static PRE_AUTHORIZED_CODE: OnceCell<String> = OnceCell::new();
+    static ISSUER_STATE: OnceCell<String> = OnceCell::new();
+    static AUTHORIZATION_CODE: OnceCell<String> = OnceCell::new();
+    static TRANSACTION_CODE: OnceCell<String> = OnceCell::new();
+    static TRANSACTION_ID: OnceCell<String> = OnceCell::new();
+    static CREDENTIAL_OFFER_URI_ID: OnceCell<String> = OnceCell::new();
     static ACCESS_TOKEN: OnceCell<String> = OnceCell::new();
+    static REFRESH_TOKEN: OnceCell<String> = OnceCell::new();
     static C_NONCE: OnceCell<String> = OnceCell::new();
+    static NOTIFICATION_ID: OnceCell<String> = OnceCell::new();
 
     #[fixture]
     pub async fn pre_authorized_code() -> String {
         PRE_AUTHORIZED_CODE.get_or_init(generate_random_string).clone()
     }
 
+    #[fixture]
+    pub async fn issuer_state() -> String {
+        ISSUER_STATE.get_or_init(generate_random_string).clone()
+    }
+
+    #[fixture]
+    pub async fn authorization_code() -> String {
+        AUTHORIZATION_CODE.get_or_init(generate_random_string).clone()
+    }
+
+    #[fixture]
+    pub async fn transaction_code() -> String {
+        TRANSACTION_CODE
+            .get_or_init(|| agent_shared::generate_random_code(6, true))
+            .clone()
+    }
+
+    #[fixture]
+    pub async fn transaction_id() -> String {
+        TRANSACTION_ID.get_or_init(generate_random_string).clone()
+    }
+
+    #[fixture]
+    pub async fn credential_offer_uri_id() -> String {
+        CREDENTIAL_OFFER_URI_ID.get_or_init(generate_random_string).clone()
+    }
+
+    #[fixture]
+    pub fn tx_code() -> TransactionCode {
+        TransactionCode {
+            input_mode: Some("numeric".to_string()),
+            length: Some(6),
+            description: Some("Please provide the one-time code that was sent to you".to_string()),
+        }
+    }
+
     #[fixture]
     pub async fn access_token() -> String {
         ACCESS_TOKEN.get_or_init(generate_random_string).clone()
     }
 
+    #[fixture]
+    pub async fn refresh_token() -> String {
+        REFRESH_TOKEN.get_or_init(generate_random_string).clone()
+    }
+
     #[fixture]
     pub async fn c_nonce() -> String {
-        C_NONCE.get_or_init(generate_random_string).clone()
+        // The `nonce` that is used in the (pre-signed) Proofs of the Credential Requests in the REST API tests.
+        C_NONCE
+            .get_or_init(|| "7e03ad3f76cb3338c3a5642fe7634476aa3ad93fa1d584011ba2150d9da47133".to_string())
+            .clone()
+    }
+
+    #[fixture]
+    pub async fn notification_id() -> String {
+        NOTIFICATION_ID.get_or_init(generate_random_string).clone()
     }
 
     pub struct TestAttributes {
         pub pre_authorized_code: String,
+        pub issuer_state: String,
         pub access_token: String,
         pub c_nonce: String,
     }
@@ -519,11 +2766,13 @@ pub mod test_utils {
     #[fixture]
     pub async fn attributes(
         #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
         #[future(awt)] access_token: String,
         #[future(awt)] c_nonce: String,
     ) -> TestAttributes {
         TestAttributes {
             pre_authorized_code,
+            issuer_state,
             access_token,
             c_nonce,
         }
@@ -538,6 +2787,7 @@ pub mod test_utils {
     #[fixture]
     pub async fn credential_offer(
         #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
         credential_issuer_metadata: Box<CredentialIssuerMetadata>,
     ) -> CredentialOffer {
         CredentialOffer::CredentialOffer(Box::new(CredentialOfferParameters {
@@ -548,7 +2798,10 @@ pub mod test_utils {
                 .cloned()
                 .collect(),
             grants: Some(Grants {
-                authorization_code: None,
+                authorization_code: Some(AuthorizationCode {
+                    issuer_state: Some(issuer_state),
+                    ..Default::default()
+                }),
                 pre_authorized_code: Some(PreAuthorizedCode {
                     pre_authorized_code,
                     ..Default::default()
@@ -558,8 +2811,11 @@ pub mod test_utils {
     }
 
     #[fixture]
-    pub async fn form_url_encoded_credential_offer(#[future(awt)] pre_authorized_code: String) -> String {
-        format!("openid-credential-offer://?credential_offer=%7B%22credential_issuer%22%3A%22https%3A%2F%2Fexample.com%2F%22%2C%22credential_configuration_ids%22%3A%5B%22badge%22%5D%2C%22grants%22%3A%7B%22urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Apre-authorized_code%22%3A%7B%22pre-authorized_code%22%3A%22{pre_authorized_code}%22%7D%7D%7D")
+    pub async fn form_url_encoded_credential_offer(
+        #[future(awt)] pre_authorized_code: String,
+        #[future(awt)] issuer_state: String,
+    ) -> String {
+        format!("openid-credential-offer://?credential_offer=%7B%22credential_issuer%22%3A%22https%3A%2F%2Fexample.com%2F%22%2C%22credential_configuration_ids%22%3A%5B%22badge%22%5D%2C%22grants%22%3A%7B%22authorization_code%22%3A%7B%22issuer_state%22%3A%22{issuer_state}%22%7D%2C%22urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Apre-authorized_code%22%3A%7B%22pre-authorized_code%22%3A%22{pre_authorized_code}%22%7D%7D%7D")
     }
 
     #[fixture]
@@ -571,15 +2827,28 @@ pub mod test_utils {
     }
 
     #[fixture]
-    pub async fn token_response(#[future(awt)] access_token: String, #[future(awt)] c_nonce: String) -> TokenResponse {
+    pub async fn authorization_code_token_request(#[future(awt)] authorization_code: String) -> TokenRequest {
+        TokenRequest::AuthorizationCode {
+            code: authorization_code,
+            code_verifier: Some(CODE_VERIFIER.to_string()),
+            redirect_uri: Some("https://wallet.example.org/callback".to_string()),
+        }
+    }
+
+    #[fixture]
+    pub async fn token_response(
+        #[future(awt)] access_token: String,
+        #[future(awt)] refresh_token: String,
+        #[future(awt)] c_nonce: String,
+    ) -> TokenResponse {
         TokenResponse {
             access_token,
             token_type: "bearer".to_string(),
-            expires_in: None,
-            refresh_token: None,
+            expires_in: Some(DEFAULT_ACCESS_TOKEN_EXPIRES_IN),
+            refresh_token: Some(refresh_token),
             scope: None,
             c_nonce: Some(c_nonce),
-            c_nonce_expires_in: None,
+            c_nonce_expires_in: Some(C_NONCE_EXPIRES_IN),
         }
     }
 
@@ -618,14 +2887,32 @@ pub mod test_utils {
     }
 
     #[fixture]
-    pub fn credential_response() -> CredentialResponse {
+    pub async fn credential_response(#[future(awt)] notification_id: String) -> CredentialResponse {
         CredentialResponse {
             credential: CredentialResponseType::Immediate {
                 credential: json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT.to_string()),
-                notification_id: None,
+                notification_id: Some(notification_id),
             },
             c_nonce: None,
             c_nonce_expires_in: None,
         }
     }
+
+    #[fixture]
+    pub async fn batch_credential_response(#[future(awt)] notification_id: String) -> BatchCredentialResponse {
+        BatchCredentialResponse {
+            credential_responses: vec![
+                CredentialResponseType::Immediate {
+                    credential: json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT.to_string()),
+                    notification_id: Some(notification_id.clone()),
+                },
+                CredentialResponseType::Immediate {
+                    credential: json!(OPENBADGE_VERIFIABLE_CREDENTIAL_JWT.to_string()),
+                    notification_id: Some(notification_id),
+                },
+            ],
+            c_nonce: None,
+            c_nonce_expires_in: None,
+        }
+    }
 }
@@ -1,24 +1,33 @@
+use agent_shared::notification::NotificationRequest;
 use oid4vci::{
     credential_issuer::{
         authorization_server_metadata::AuthorizationServerMetadata,
         credential_issuer_metadata::CredentialIssuerMetadata,
     },
+    credential_offer::TransactionCode,
     credential_request::CredentialRequest,
     token_request::TokenRequest,
 };
 use serde::Deserialize;
 use url::Url;
 
+use super::aggregate::CodeChallengeMethod;
+
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum OfferCommand {
     CreateCredentialOffer {
         offer_id: String,
         credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        /// The Credential Configurations that are offered. When empty, all supported Credential Configurations are
+        /// offered.
+        credential_configuration_ids: Vec<String>,
     },
     AddCredentials {
         offer_id: String,
         credential_ids: Vec<String>,
+        /// The id of the Credential Configuration of the added credentials.
+        credential_configuration_id: String,
     },
     SendCredentialOffer {
         offer_id: String,
@@ -26,22 +35,81 @@ pub enum OfferCommand {
     },
 
     // OpenID4VCI Pre-Authorized Code Flow
-    // TODO: add option for credential_offer_uri (by reference)
     CreateFormUrlEncodedCredentialOffer {
         offer_id: String,
+        /// When `true`, the Credential Offer is passed by reference using the `credential_offer_uri` parameter.
+        by_reference: bool,
+    },
+    /// Requires the Wallet to provide the `transaction_code` in the Token Request. Only its hash is stored, so the
+    /// Transaction Code must be generated using `generate_transaction_code` and delivered to the holder by the caller.
+    CreateTransactionCode {
+        offer_id: String,
+        tx_code: TransactionCode,
+        transaction_code: String,
+    },
+    /// Registers an attempt to redeem the Pre-Authorized Code with a Transaction Code. Must precede every
+    /// `CreateTokenResponse` command that contains a Transaction Code, so that the number of attempts is limited even
+    /// for concurrent Token Requests, and every `CreateAuthorizationCode` command that contains a Transaction Code.
+    RegisterTransactionCodeAttempt {
+        offer_id: String,
+    },
+
+    // OpenID4VCI Authorization Code Flow
+    /// When the Credential Offer has a Transaction Code, `tx_code` must match it, since the `issuer_state` alone does
+    /// not authenticate the End-User.
+    CreateAuthorizationCode {
+        offer_id: String,
+        code_challenge: String,
+        code_challenge_method: CodeChallengeMethod,
+        redirect_uri: Option<String>,
+        tx_code: Option<String>,
     },
+
+    /// When `dpop_jkt` is set, the Access Token is bound to the key with this JWK Thumbprint (DPoP, RFC 9449).
     CreateTokenResponse {
         offer_id: String,
         token_request: TokenRequest,
+        dpop_jkt: Option<String>,
+    },
+    RefreshAccessToken {
+        offer_id: String,
+        refresh_token: String,
+        dpop_jkt: Option<String>,
+    },
+    CreateNonce {
+        offer_id: String,
     },
     VerifyCredentialRequest {
         offer_id: String,
         credential_issuer_metadata: Box<CredentialIssuerMetadata>,
         authorization_server_metadata: Box<AuthorizationServerMetadata>,
-        credential_request: CredentialRequest,
+        credential_requests: Vec<CredentialRequest>,
     },
     CreateCredentialResponse {
+        offer_id: String,
+        signed_credential: serde_json::Value,
+    },
+    CreateDeferredCredentialResponse {
+        offer_id: String,
+        credential_requests: Vec<CredentialRequest>,
+    },
+    CreateBatchCredentialResponse {
         offer_id: String,
         signed_credentials: Vec<serde_json::Value>,
     },
+    /// Redeems the `transaction_id` for the credential with `credential_id` and creates the Credential Response for its
+    /// `signed_credential` in the same step.
+    RedeemTransactionId {
+        offer_id: String,
+        transaction_id: String,
+        credential_id: String,
+        signed_credential: serde_json::Value,
+    },
+    HandleNotification {
+        offer_id: String,
+        notification_request: NotificationRequest,
+    },
+    CancelOffer {
+        offer_id: String,
+    },
 }
@@ -4,6 +4,8 @@ use thiserror::Error;
 pub enum OfferError {
     #[error("Credential Offer is missing")]
     MissingCredentialOfferError,
+    #[error("No Credential Configuration found with id: `{0}`")]
+    CredentialConfigurationNotFoundError(String),
     #[error("Something went wrong while trying to send the Credential Offer to the `target_url`: {0}")]
     SendCredentialOfferError(String),
     #[error("Credential is missing")]
@@ -14,4 +16,57 @@ pub enum OfferError {
     InvalidProofError(String),
     #[error("Missing `iss` claim in `Proof`")]
     MissingProofIssuerError,
+    #[error("No `c_nonce` has been issued for this Credential Offer")]
+    MissingNonceError,
+    #[error("The `nonce` in the `Proof` does not match the `c_nonce` that was issued for this Credential Offer")]
+    InvalidNonceError,
+    #[error("The `c_nonce` has expired")]
+    ExpiredNonceError,
+    #[error(
+        "The length of the Transaction Code must be between 1 and {}",
+        super::aggregate::MAX_TRANSACTION_CODE_LENGTH
+    )]
+    InvalidTransactionCodeLengthError,
+    #[error("Missing `tx_code`")]
+    MissingTransactionCodeError,
+    #[error("Invalid Transaction Code")]
+    InvalidTransactionCodeError,
+    #[error("The maximum number of Transaction Code attempts has been exceeded")]
+    TransactionCodeAttemptsExceededError,
+    #[error("The Pre-Authorized Code has expired")]
+    PreAuthorizedCodeExpiredError,
+    #[error("The Pre-Authorized Code has already been redeemed")]
+    PreAuthorizedCodeAlreadyRedeemedError,
+    #[error("Invalid Refresh Token")]
+    InvalidRefreshTokenError,
+    #[error("The Refresh Token has expired")]
+    RefreshTokenExpiredError,
+    #[error("The DPoP proof is not signed by the key the Refresh Token is bound to")]
+    DpopKeyMismatchError,
+    #[error("No Authorization Code has been issued for this Credential Offer")]
+    MissingAuthorizationCodeError,
+    #[error("Invalid Authorization Code")]
+    InvalidAuthorizationCodeError,
+    #[error("The Authorization Code has already been redeemed")]
+    AuthorizationCodeAlreadyRedeemedError,
+    #[error("An Access Token has already been issued for this Credential Offer")]
+    OfferAlreadyRedeemedError,
+    #[error("The `redirect_uri` does not match the one used in the Authorization Request")]
+    RedirectUriMismatchError,
+    #[error("Missing `code_verifier` in Token Request")]
+    MissingCodeVerifierError,
+    #[error("The `code_verifier` does not match the `code_challenge`")]
+    InvalidCodeVerifierError,
+    #[error("The `transaction_id` is invalid or all of its credentials have already been issued")]
+    InvalidTransactionIdError,
+    #[error("The `notification_id` does not belong to a Credential that was issued for this Credential Offer")]
+    InvalidNotificationIdError,
+    #[error("The Credential Offer has expired")]
+    OfferExpiredError,
+    #[error("The Credential Offer has been cancelled")]
+    OfferCancelledError,
+    #[error("A Credential Offer cannot be cancelled after its credentials have been issued")]
+    OfferNotCancellableError,
+    #[error("The Access Token has been revoked because the Credential Offer was cancelled")]
+    RevokedAccessTokenError,
 }
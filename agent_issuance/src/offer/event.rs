@@ -1,33 +1,104 @@
 use cqrs_es::DomainEvent;
 use oid4vci::{
-    credential_offer::CredentialOffer, credential_response::CredentialResponse, token_response::TokenResponse,
+    credential_offer::{CredentialOffer, TransactionCode},
+    credential_response::{BatchCredentialResponse, CredentialResponse},
+    token_response::TokenResponse,
 };
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use super::aggregate::CodeChallengeMethod;
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum OfferEvent {
     CredentialOfferCreated {
         offer_id: String,
         credential_offer: CredentialOffer,
         pre_authorized_code: String,
+        /// The timestamp at which the Pre-Authorized Code expires. Absent when the Pre-Authorized Code does not expire.
+        #[serde(default)]
+        pre_authorized_code_expires_at: Option<i64>,
+        /// The timestamp at which the Credential Offer expires. Absent for Credential Offers that were created before
+        /// Credential Offers could expire.
+        #[serde(default)]
+        expires_at: Option<i64>,
+        issuer_state: String,
         access_token: String,
     },
     CredentialsAdded {
         offer_id: String,
         credential_ids: Vec<String>,
+        /// The Credential Configuration of the added credentials. Absent for credentials that were added before it was
+        /// recorded.
+        #[serde(default)]
+        credential_configuration_id: Option<String>,
     },
     FormUrlEncodedCredentialOfferCreated {
         offer_id: String,
         form_url_encoded_credential_offer: String,
+        /// The random id that references the Credential Offer in its `credential_offer_uri`.
+        #[serde(default)]
+        credential_offer_uri_id: Option<String>,
     },
     CredentialOfferSent {
         offer_id: String,
         target_url: Url,
     },
+    TransactionCodeCreated {
+        offer_id: String,
+        tx_code: TransactionCode,
+        /// The Transaction Code itself. Only present in Transaction Codes that were created before their hash was
+        /// stored instead.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        transaction_code: Option<String>,
+        /// The base64url-encoded SHA-256 hash of the Transaction Code.
+        #[serde(default)]
+        transaction_code_hash: Option<String>,
+    },
+    TransactionCodeVerificationFailed {
+        offer_id: String,
+        failed_attempts: u8,
+    },
+    TransactionCodeAttemptRegistered {
+        offer_id: String,
+        attempts: u8,
+    },
+    AuthorizationCodeCreated {
+        offer_id: String,
+        authorization_code: String,
+        code_challenge: String,
+        code_challenge_method: CodeChallengeMethod,
+        redirect_uri: Option<String>,
+    },
+    AuthorizationCodeRedeemed {
+        offer_id: String,
+        authorization_code: String,
+    },
+    PreAuthorizedCodeRedeemed {
+        offer_id: String,
+        pre_authorized_code: String,
+    },
     TokenResponseCreated {
         offer_id: String,
         token_response: TokenResponse,
+        /// The timestamp at which the Access Token expires. Absent when the Access Token does not expire.
+        #[serde(default)]
+        access_token_expires_at: Option<i64>,
+        /// The timestamp at which the Refresh Token expires. Absent when the Refresh Token does not expire.
+        #[serde(default)]
+        refresh_token_expires_at: Option<i64>,
+        /// The JWK Thumbprint of the key the Access Token is bound to using DPoP.
+        #[serde(default)]
+        dpop_jkt: Option<String>,
+    },
+    AccessTokenRevoked {
+        offer_id: String,
+        access_token: String,
+    },
+    NonceCreated {
+        offer_id: String,
+        c_nonce: String,
+        c_nonce_expires_at: i64,
     },
     CredentialRequestVerified {
         offer_id: String,
@@ -37,6 +108,41 @@ pub enum OfferEvent {
         offer_id: String,
         credential_response: CredentialResponse,
     },
+    DeferredCredentialResponseCreated {
+        offer_id: String,
+        transaction_id: String,
+        credential_response: CredentialResponse,
+        /// The Credential Requests whose credentials can be obtained using the `transaction_id`.
+        #[serde(default)]
+        credential_requests: Vec<serde_json::Value>,
+    },
+    BatchCredentialResponseCreated {
+        offer_id: String,
+        batch_credential_response: BatchCredentialResponse,
+    },
+    TransactionIdRedeemed {
+        offer_id: String,
+        transaction_id: String,
+        credential_id: String,
+    },
+    CredentialAccepted {
+        offer_id: String,
+        notification_id: String,
+        event_description: Option<String>,
+    },
+    CredentialFailure {
+        offer_id: String,
+        notification_id: String,
+        event_description: Option<String>,
+    },
+    CredentialDeleted {
+        offer_id: String,
+        notification_id: String,
+        event_description: Option<String>,
+    },
+    OfferCancelled {
+        offer_id: String,
+    },
 }
 
 impl DomainEvent for OfferEvent {
@@ -48,9 +154,24 @@ impl DomainEvent for OfferEvent {
             CredentialsAdded { .. } => "CredentialsAdded",
             FormUrlEncodedCredentialOfferCreated { .. } => "FormUrlEncodedCredentialOfferCreated",
             CredentialOfferSent { .. } => "CredentialOfferSent",
+            TransactionCodeCreated { .. } => "TransactionCodeCreated",
+            TransactionCodeVerificationFailed { .. } => "TransactionCodeVerificationFailed",
+            TransactionCodeAttemptRegistered { .. } => "TransactionCodeAttemptRegistered",
+            AuthorizationCodeCreated { .. } => "AuthorizationCodeCreated",
+            AuthorizationCodeRedeemed { .. } => "AuthorizationCodeRedeemed",
+            PreAuthorizedCodeRedeemed { .. } => "PreAuthorizedCodeRedeemed",
             TokenResponseCreated { .. } => "TokenResponseCreated",
+            AccessTokenRevoked { .. } => "AccessTokenRevoked",
+            NonceCreated { .. } => "NonceCreated",
             CredentialRequestVerified { .. } => "CredentialRequestVerified",
             CredentialResponseCreated { .. } => "CredentialResponseCreated",
+            DeferredCredentialResponseCreated { .. } => "DeferredCredentialResponseCreated",
+            BatchCredentialResponseCreated { .. } => "BatchCredentialResponseCreated",
+            TransactionIdRedeemed { .. } => "TransactionIdRedeemed",
+            CredentialAccepted { .. } => "CredentialAccepted",
+            CredentialFailure { .. } => "CredentialFailure",
+            CredentialDeleted { .. } => "CredentialDeleted",
+            OfferCancelled { .. } => "OfferCancelled",
         };
         event_type.to_string()
     }
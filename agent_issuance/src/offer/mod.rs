@@ -3,3 +3,4 @@ pub mod command;
 pub mod error;
 pub mod event;
 pub mod queries;
+pub mod tx_channel_registry;
@@ -1,6 +1,7 @@
 use crate::offer::queries::{Offer, OfferEvent, ViewRepository};
 use agent_shared::custom_queries::CustomQuery;
 use async_trait::async_trait;
+use chrono::Utc;
 use cqrs_es::{
     persist::{PersistenceError, ViewContext},
     EventEnvelope, Query, View,
@@ -59,14 +60,18 @@ where
         }
     }
 
-    async fn apply_events(&self, view_id: &str, events: &[EventEnvelope<Offer>]) -> Result<(), PersistenceError> {
+    async fn apply_events(&self, _view_id: &str, events: &[EventEnvelope<Offer>]) -> Result<(), PersistenceError> {
         for event in events {
-            let (mut view, mut view_context) = self.load_mut(view_id.to_string()).await?;
-            if let OfferEvent::CredentialOfferCreated { access_token, .. } = &event.payload {
-                view_context.view_instance_id.clone_from(access_token);
-                view.update(event);
-                self.view_repository.update_view(view, view_context).await?;
-            }
+            let access_token = match &event.payload {
+                OfferEvent::TokenResponseCreated { token_response, .. } => &token_response.access_token,
+                OfferEvent::AccessTokenRevoked { access_token, .. } => access_token,
+                _ => continue,
+            };
+
+            let (mut view, mut view_context) = self.load_mut(access_token.clone()).await?;
+            view_context.view_instance_id.clone_from(access_token);
+            view.update(event);
+            self.view_repository.update_view(view, view_context).await?;
         }
         Ok(())
     }
@@ -75,14 +80,44 @@ where
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct AccessTokenView {
     pub offer_id: String,
+    /// The timestamp at which the Access Token expires. Absent when the Access Token does not expire.
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+    /// The JWK Thumbprint of the key the Access Token is bound to using DPoP.
+    #[serde(default)]
+    pub dpop_jkt: Option<String>,
+    /// Whether the Access Token has been replaced by a refreshed Access Token.
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+impl AccessTokenView {
+    /// Returns `true` if the Access Token can no longer be used.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| Utc::now().timestamp() >= expires_at)
+    }
 }
 
 impl View<Offer> for AccessTokenView {
     fn update(&mut self, event: &EventEnvelope<Offer>) {
         use crate::offer::event::OfferEvent::*;
 
-        if let CredentialOfferCreated { .. } = event.payload {
-            self.offer_id.clone_from(&event.aggregate_id)
+        match &event.payload {
+            TokenResponseCreated {
+                access_token_expires_at,
+                dpop_jkt,
+                ..
+            } => {
+                self.offer_id.clone_from(&event.aggregate_id);
+                self.expires_at = *access_token_expires_at;
+                self.dpop_jkt.clone_from(dpop_jkt);
+                self.revoked = false;
+            }
+            AccessTokenRevoked { .. } => {
+                self.revoked = true;
+            }
+            _ => {}
         }
     }
 }
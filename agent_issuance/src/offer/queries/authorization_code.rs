@@ -0,0 +1,101 @@
+use crate::offer::queries::{Offer, OfferEvent, ViewRepository};
+use agent_shared::custom_queries::CustomQuery;
+use async_trait::async_trait;
+use cqrs_es::{
+    persist::{PersistenceError, ViewContext},
+    EventEnvelope, Query, View,
+};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A custom query trait for the Offer aggregate. This query is used to update the `AuthorizationCodeView`.
+pub struct AuthorizationCodeQuery<R, V>
+where
+    R: ViewRepository<V, Offer>,
+    V: View<Offer>,
+{
+    view_repository: Arc<R>,
+    _phantom: PhantomData<V>,
+}
+
+impl<R, V> AuthorizationCodeQuery<R, V>
+where
+    R: ViewRepository<V, Offer>,
+    V: View<Offer>,
+{
+    pub fn new(view_repository: Arc<R>) -> Self {
+        AuthorizationCodeQuery {
+            view_repository,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<R, V> Query<Offer> for AuthorizationCodeQuery<R, V>
+where
+    R: ViewRepository<V, Offer>,
+    V: View<Offer>,
+{
+    async fn dispatch(&self, view_id: &str, events: &[EventEnvelope<Offer>]) {
+        self.apply_events(view_id, events).await.ok();
+    }
+}
+
+#[async_trait]
+impl<R, V> CustomQuery<R, V, Offer> for AuthorizationCodeQuery<R, V>
+where
+    R: ViewRepository<V, Offer>,
+    V: View<Offer>,
+{
+    async fn load_mut(&self, view_id: String) -> Result<(V, ViewContext), PersistenceError> {
+        match self.view_repository.load_with_context(&view_id).await? {
+            None => {
+                let view_context = ViewContext::new(view_id, 0);
+                Ok((Default::default(), view_context))
+            }
+            Some((view, context)) => Ok((view, context)),
+        }
+    }
+
+    async fn apply_events(&self, _view_id: &str, events: &[EventEnvelope<Offer>]) -> Result<(), PersistenceError> {
+        for event in events {
+            match &event.payload {
+                OfferEvent::AuthorizationCodeCreated { authorization_code, .. }
+                | OfferEvent::AuthorizationCodeRedeemed { authorization_code, .. } => {
+                    let (mut view, mut view_context) = self.load_mut(authorization_code.clone()).await?;
+                    view_context.view_instance_id.clone_from(authorization_code);
+                    view.update(event);
+                    self.view_repository.update_view(view, view_context).await?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct AuthorizationCodeView {
+    pub offer_id: String,
+    /// Whether the Authorization Code has already been exchanged for an Access Token.
+    #[serde(default)]
+    pub redeemed: bool,
+}
+
+impl View<Offer> for AuthorizationCodeView {
+    fn update(&mut self, event: &EventEnvelope<Offer>) {
+        use crate::offer::event::OfferEvent::*;
+
+        match event.payload {
+            AuthorizationCodeCreated { .. } => {
+                self.offer_id.clone_from(&event.aggregate_id);
+            }
+            AuthorizationCodeRedeemed { .. } => {
+                self.redeemed = true;
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,25 +1,58 @@
 pub mod access_token;
 pub mod all_offers;
+pub mod authorization_code;
+pub mod credential_offer_uri;
+pub mod issuer_state;
 pub mod pre_authorized_code;
+pub mod refresh_token;
 
 use super::event::OfferEvent;
-use crate::offer::aggregate::Offer;
+use crate::offer::aggregate::{add_credential_configuration_id, add_transaction_code, Offer, OfferStatus};
 use cqrs_es::{persist::ViewRepository, EventEnvelope, View};
 use oid4vci::{
-    credential_offer::CredentialOffer, credential_response::CredentialResponse, token_response::TokenResponse,
+    credential_offer::CredentialOffer,
+    credential_response::{BatchCredentialResponse, CredentialResponse},
+    token_response::TokenResponse,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct OfferView {
     pub credential_offer: Option<CredentialOffer>,
     pub subject_id: Option<String>,
     pub credential_ids: Vec<String>,
+    /// The id of the Credential Configuration of each credential, keyed by the id of the credential.
+    #[serde(default)]
+    pub credential_configuration_ids: HashMap<String, String>,
     pub pre_authorized_code: String,
+    pub issuer_state: String,
+    pub authorization_code: Option<String>,
     pub access_token: String,
+    pub c_nonce: Option<String>,
     pub form_url_encoded_credential_offer: String,
     pub token_response: Option<TokenResponse>,
     pub credential_response: Option<CredentialResponse>,
+    pub transaction_id: Option<String>,
+    /// The deferred Credential Requests that have not been issued using the `transaction_id` yet.
+    #[serde(default)]
+    pub deferred_credential_requests: Vec<serde_json::Value>,
+    /// The ids of the credentials that have been issued using the `transaction_id`.
+    #[serde(default)]
+    pub deferred_credential_ids: Vec<String>,
+    pub batch_credential_response: Option<BatchCredentialResponse>,
+    #[serde(default)]
+    pub status: OfferStatus,
+    /// The timestamp at which the Credential Offer expires when it has not been redeemed.
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
+
+impl OfferView {
+    /// Updates the `status` of a Credential Offer that has expired since the last event was applied.
+    pub fn refresh_status(&mut self) {
+        self.status = self.status.current(self.expires_at);
+    }
 }
 
 impl View<Offer> for OfferView {
@@ -28,18 +61,36 @@ impl View<Offer> for OfferView {
 
         match &event.payload {
             CredentialOfferCreated {
+                credential_offer,
                 pre_authorized_code,
+                issuer_state,
                 access_token,
+                expires_at,
                 ..
             } => {
+                self.status = OfferStatus::Created;
+                self.expires_at = *expires_at;
+                self.credential_offer.replace(credential_offer.clone());
                 self.pre_authorized_code.clone_from(pre_authorized_code);
+                self.issuer_state.clone_from(issuer_state);
                 self.access_token.clone_from(access_token)
             }
             CredentialsAdded {
-                credential_ids: credential_id,
+                credential_ids,
+                credential_configuration_id,
                 ..
             } => {
-                self.credential_ids.clone_from(credential_id);
+                if let Some(credential_configuration_id) = credential_configuration_id {
+                    if let Some(credential_offer) = self.credential_offer.as_mut() {
+                        add_credential_configuration_id(credential_offer, credential_configuration_id);
+                    }
+                    self.credential_configuration_ids.extend(
+                        credential_ids
+                            .iter()
+                            .map(|credential_id| (credential_id.clone(), credential_configuration_id.clone())),
+                    );
+                }
+                self.credential_ids.extend(credential_ids.iter().cloned());
             }
             FormUrlEncodedCredentialOfferCreated {
                 form_url_encoded_credential_offer,
@@ -47,17 +98,72 @@ impl View<Offer> for OfferView {
             } => self
                 .form_url_encoded_credential_offer
                 .clone_from(form_url_encoded_credential_offer),
-            CredentialOfferSent { .. } => {}
+            CredentialOfferSent { .. } => {
+                if self.status == OfferStatus::Created {
+                    self.status = OfferStatus::Sent;
+                }
+            }
+            TransactionCodeCreated { tx_code, .. } => {
+                if let Some(credential_offer) = self.credential_offer.as_mut() {
+                    add_transaction_code(credential_offer, tx_code.clone());
+                }
+            }
+            TransactionCodeVerificationFailed { .. } | TransactionCodeAttemptRegistered { .. } => {}
+            AuthorizationCodeCreated { authorization_code, .. } => {
+                self.authorization_code.replace(authorization_code.clone());
+            }
             CredentialRequestVerified { subject_id, .. } => {
                 self.subject_id.replace(subject_id.clone());
             }
+            AuthorizationCodeRedeemed { .. } | PreAuthorizedCodeRedeemed { .. } => {}
             TokenResponseCreated { token_response, .. } => {
+                if matches!(self.status, OfferStatus::Created | OfferStatus::Sent) {
+                    self.status = OfferStatus::TokenIssued;
+                }
+                self.access_token.clone_from(&token_response.access_token);
                 self.token_response.replace(token_response.clone());
             }
+            AccessTokenRevoked { .. } => {}
+            NonceCreated { c_nonce, .. } => {
+                self.c_nonce.replace(c_nonce.clone());
+            }
             CredentialResponseCreated {
                 credential_response, ..
             } => {
+                self.status = OfferStatus::CredentialIssued;
+                self.credential_response.replace(credential_response.clone());
+            }
+            DeferredCredentialResponseCreated {
+                transaction_id,
+                credential_response,
+                credential_requests,
+                ..
+            } => {
+                self.transaction_id.replace(transaction_id.clone());
                 self.credential_response.replace(credential_response.clone());
+                self.deferred_credential_requests.clone_from(credential_requests);
+                self.deferred_credential_ids.clear();
+            }
+            BatchCredentialResponseCreated {
+                batch_credential_response,
+                ..
+            } => {
+                self.status = OfferStatus::CredentialIssued;
+                self.batch_credential_response
+                    .replace(batch_credential_response.clone());
+            }
+            TransactionIdRedeemed { credential_id, .. } => {
+                if !self.deferred_credential_requests.is_empty() {
+                    self.deferred_credential_requests.remove(0);
+                }
+                self.deferred_credential_ids.push(credential_id.clone());
+                if self.deferred_credential_requests.is_empty() {
+                    self.transaction_id.take();
+                }
+            }
+            CredentialAccepted { .. } | CredentialFailure { .. } | CredentialDeleted { .. } => {}
+            OfferCancelled { .. } => {
+                self.status = OfferStatus::Cancelled;
             }
         }
     }
@@ -1,6 +1,7 @@
 use crate::offer::queries::{Offer, OfferEvent, ViewRepository};
 use agent_shared::custom_queries::CustomQuery;
 use async_trait::async_trait;
+use chrono::Utc;
 use cqrs_es::{
     persist::{PersistenceError, ViewContext},
     EventEnvelope, Query, View,
@@ -59,16 +60,21 @@ where
         }
     }
 
-    async fn apply_events(&self, view_id: &str, events: &[EventEnvelope<Offer>]) -> Result<(), PersistenceError> {
+    async fn apply_events(&self, _view_id: &str, events: &[EventEnvelope<Offer>]) -> Result<(), PersistenceError> {
         for event in events {
-            let (mut view, mut view_context) = self.load_mut(view_id.to_string()).await?;
-            if let OfferEvent::CredentialOfferCreated {
-                pre_authorized_code, ..
-            } = &event.payload
-            {
-                view_context.view_instance_id.clone_from(pre_authorized_code);
-                view.update(event);
-                self.view_repository.update_view(view, view_context).await?;
+            match &event.payload {
+                OfferEvent::CredentialOfferCreated {
+                    pre_authorized_code, ..
+                }
+                | OfferEvent::PreAuthorizedCodeRedeemed {
+                    pre_authorized_code, ..
+                } => {
+                    let (mut view, mut view_context) = self.load_mut(pre_authorized_code.clone()).await?;
+                    view_context.view_instance_id.clone_from(pre_authorized_code);
+                    view.update(event);
+                    self.view_repository.update_view(view, view_context).await?;
+                }
+                _ => {}
             }
         }
         Ok(())
@@ -78,14 +84,39 @@ where
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct PreAuthorizedCodeView {
     pub offer_id: String,
+    /// The timestamp at which the Pre-Authorized Code expires. Absent when the Pre-Authorized Code does not expire.
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+    /// Whether the Pre-Authorized Code has already been exchanged for an Access Token.
+    pub redeemed: bool,
+}
+
+impl PreAuthorizedCodeView {
+    /// Returns `true` if the Pre-Authorized Code can no longer be exchanged for an Access Token.
+    pub fn is_unusable(&self) -> bool {
+        self.redeemed
+            || self
+                .expires_at
+                .is_some_and(|expires_at| Utc::now().timestamp() >= expires_at)
+    }
 }
 
 impl View<Offer> for PreAuthorizedCodeView {
     fn update(&mut self, event: &EventEnvelope<Offer>) {
         use crate::offer::event::OfferEvent::*;
 
-        if let CredentialOfferCreated { .. } = event.payload {
-            self.offer_id.clone_from(&event.aggregate_id)
+        match event.payload {
+            CredentialOfferCreated {
+                pre_authorized_code_expires_at,
+                ..
+            } => {
+                self.offer_id.clone_from(&event.aggregate_id);
+                self.expires_at = pre_authorized_code_expires_at;
+            }
+            PreAuthorizedCodeRedeemed { .. } => {
+                self.redeemed = true;
+            }
+            _ => {}
         }
     }
 }
@@ -0,0 +1,92 @@
+use crate::offer::{aggregate::Offer, event::OfferEvent};
+use async_trait::async_trait;
+use cqrs_es::{EventEnvelope, Query};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::watch;
+
+/// An in-process registry of channels that are used to notify waiting request handlers as soon as credentials are
+/// added to an Offer. The registry is fed by the `Offer` event stream, so it must be appended to the `Offer`'s command
+/// handler after the query that updates the `OfferView`.
+#[derive(Clone, Default)]
+pub struct TxChannelRegistry {
+    channels: Arc<Mutex<HashMap<String, watch::Sender<()>>>>,
+}
+
+impl TxChannelRegistry {
+    /// Returns a receiver that is notified whenever credentials are added to the Offer with the given `offer_id`. To
+    /// make sure that no notification is missed, the receiver must be created before the `OfferView` is checked.
+    pub fn subscribe(&self, offer_id: &str) -> watch::Receiver<()> {
+        let mut channels = self.channels.lock().unwrap();
+
+        // Channels without any remaining receivers are no longer needed.
+        channels.retain(|_, sender| sender.receiver_count() > 0);
+
+        channels
+            .entry(offer_id.to_string())
+            .or_insert_with(|| watch::channel(()).0)
+            .subscribe()
+    }
+
+    fn notify(&self, offer_id: &str) {
+        if let Some(sender) = self.channels.lock().unwrap().get(offer_id) {
+            sender.send_replace(());
+        }
+    }
+}
+
+#[async_trait]
+impl Query<Offer> for TxChannelRegistry {
+    async fn dispatch(&self, offer_id: &str, events: &[EventEnvelope<Offer>]) {
+        if events
+            .iter()
+            .any(|event| matches!(event.payload, OfferEvent::CredentialsAdded { .. }))
+        {
+            self.notify(offer_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    fn credentials_added(offer_id: &str) -> EventEnvelope<Offer> {
+        EventEnvelope {
+            aggregate_id: offer_id.to_string(),
+            sequence: 1,
+            payload: OfferEvent::CredentialsAdded {
+                offer_id: offer_id.to_string(),
+                credential_ids: vec!["credential-id".to_string()],
+                credential_configuration_id: Some("badge".to_string()),
+            },
+            metadata: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tx_channel_registry() {
+        let registry = TxChannelRegistry::default();
+
+        let mut receiver = registry.subscribe("offer-1");
+        let mut other_receiver = registry.subscribe("offer-2");
+
+        registry.dispatch("offer-1", &[credentials_added("offer-1")]).await;
+
+        // Only the receiver of the Offer to which the credentials were added is notified.
+        assert!(timeout(Duration::from_millis(100), receiver.changed()).await.is_ok());
+        assert!(timeout(Duration::from_millis(100), other_receiver.changed())
+            .await
+            .is_err());
+
+        // Channels are removed once all of their receivers are dropped.
+        drop(receiver);
+        drop(other_receiver);
+        let _receiver = registry.subscribe("offer-3");
+        assert_eq!(registry.channels.lock().unwrap().len(), 1);
+    }
+}
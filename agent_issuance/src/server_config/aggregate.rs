@@ -1,7 +1,11 @@
-use agent_shared::config::config;
+use agent_shared::config::{
+    config, get_preferred_did_method, get_preferred_signing_algorithm, CredentialConfiguration,
+};
 use async_trait::async_trait;
 use cqrs_es::Aggregate;
-use jsonwebtoken::Algorithm;
+use jsonwebtoken::{Algorithm, Header};
+use oid4vc_core::jwt;
+use oid4vci::credential_format_profiles::CredentialFormats;
 use oid4vci::credential_issuer::credential_configurations_supported::CredentialConfigurationsSupportedObject;
 use oid4vci::credential_issuer::{
     authorization_server_metadata::AuthorizationServerMetadata, credential_issuer_metadata::CredentialIssuerMetadata,
@@ -9,12 +13,16 @@ use oid4vci::credential_issuer::{
 use oid4vci::proof::KeyProofMetadata;
 use oid4vci::ProofType;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use tracing::info;
 
+use crate::credential::{credential_schema, template};
 use crate::server_config::command::ServerConfigCommand;
-use crate::server_config::error::ServerConfigError;
+use crate::server_config::error::ServerConfigError::{self, *};
 use crate::server_config::event::ServerConfigEvent;
+use crate::services::IssuanceServices;
 
 /// An aggregate that holds the configuration of the server.
 #[derive(Clone, Default, Deserialize, Serialize, Debug)]
@@ -28,89 +36,123 @@ impl Aggregate for ServerConfig {
     type Command = ServerConfigCommand;
     type Event = ServerConfigEvent;
     type Error = ServerConfigError;
-    type Services = ();
+    type Services = Arc<IssuanceServices>;
 
     fn aggregate_type() -> String {
         "server_config".to_string()
     }
 
-    async fn handle(
-        &self,
-        command: Self::Command,
-        _services: &Self::Services,
-    ) -> Result<Vec<Self::Event>, Self::Error> {
+    async fn handle(&self, command: Self::Command, services: &Self::Services) -> Result<Vec<Self::Event>, Self::Error> {
         use ServerConfigCommand::*;
         use ServerConfigEvent::*;
 
         info!("Handling command: {:?}", command);
 
-        match command {
+        let events = match command {
             InitializeServerMetadata {
                 authorization_server_metadata,
-                credential_issuer_metadata,
-            } => Ok(vec![ServerMetadataInitialized {
-                authorization_server_metadata,
-                credential_issuer_metadata,
-            }]),
+                mut credential_issuer_metadata,
+            } => {
+                // Credential Configurations that were added before (e.g. through the REST API) are preserved when the
+                // server metadata is re-initialized on startup.
+                if credential_issuer_metadata
+                    .credential_configurations_supported
+                    .is_empty()
+                {
+                    credential_issuer_metadata.credential_configurations_supported = self
+                        .credential_issuer_metadata
+                        .credential_configurations_supported
+                        .clone();
+                }
 
+                vec![ServerMetadataInitialized {
+                    authorization_server_metadata,
+                    credential_issuer_metadata,
+                }]
+            }
             AddCredentialConfiguration {
                 credential_configuration,
             } => {
-                let mut cryptographic_binding_methods_supported: Vec<_> = config()
-                    .did_methods
-                    .iter()
-                    .filter(|&(_, options)| options.enabled)
-                    .map(|(did_method, _)| did_method.to_string())
-                    .collect();
-
-                cryptographic_binding_methods_supported.sort();
-
-                let signing_algorithms_supported: Vec<Algorithm> = config()
-                    .signing_algorithms_supported
-                    .iter()
-                    .filter(|(_, options)| options.enabled)
-                    .map(|(alg, _)| *alg)
-                    .collect();
-
-                let proof_types_supported = HashMap::from_iter([(
-                    ProofType::Jwt,
-                    KeyProofMetadata {
-                        proof_signing_alg_values_supported: signing_algorithms_supported.clone(),
-                    },
-                )]);
-
-                let credential_configuration_object = CredentialConfigurationsSupportedObject {
-                    credential_format: credential_configuration.credential_format_with_parameters,
-                    cryptographic_binding_methods_supported,
-                    credential_signing_alg_values_supported: signing_algorithms_supported
-                        .into_iter()
-                        .map(|algorithm| match algorithm {
-                            jsonwebtoken::Algorithm::EdDSA => "EdDSA".to_string(),
-                            jsonwebtoken::Algorithm::ES256 => "ES256".to_string(),
-                            _ => unimplemented!("Unsupported algorithm: {:?}", algorithm),
-                        })
-                        .collect(),
-                    proof_types_supported,
-                    display: credential_configuration.display,
-                    ..Default::default()
-                };
-
-                let credential_configurations = HashMap::from_iter([(
-                    credential_configuration.credential_configuration_id,
-                    credential_configuration_object,
-                )]);
-                // TODO: Uncomment this once we support Batch credentials.
-                // let mut credential_configurations = self
-                //     .credential_issuer_metadata
-                //     .credential_configurations_supported
-                //     .clone();
-                // credential_configurations.insert(credential_configuration_id, credential_configuration);
-
-                Ok(vec![CredentialConfigurationAdded {
+                let mut credential_configurations = self
+                    .credential_issuer_metadata
+                    .credential_configurations_supported
+                    .clone();
+
+                // An existing Credential Configuration can only be changed using `UpdateCredentialConfiguration`.
+                let credential_configuration_id = &credential_configuration.credential_configuration_id;
+                if credential_configurations.contains_key(credential_configuration_id) {
+                    return Err(CredentialConfigurationAlreadyExistsError(
+                        credential_configuration_id.clone(),
+                    ));
+                }
+
+                validate_credential_schema(&credential_configuration)?;
+                validate_credential_templates(&credential_configuration)?;
+
+                credential_configurations.insert(
+                    credential_configuration_id.clone(),
+                    credential_configurations_supported_object(&credential_configuration),
+                );
+
+                vec![CredentialConfigurationAdded {
+                    credential_configuration: Some(credential_configuration),
                     credential_configurations,
-                }])
+                }]
             }
-        }
+            UpdateCredentialConfiguration {
+                credential_configuration,
+            } => {
+                let mut credential_configurations = self
+                    .credential_issuer_metadata
+                    .credential_configurations_supported
+                    .clone();
+
+                let credential_configuration_id = &credential_configuration.credential_configuration_id;
+                if !credential_configurations.contains_key(credential_configuration_id) {
+                    return Err(CredentialConfigurationNotFoundError(
+                        credential_configuration_id.clone(),
+                    ));
+                }
+
+                validate_credential_schema(&credential_configuration)?;
+                validate_credential_templates(&credential_configuration)?;
+
+                credential_configurations.insert(
+                    credential_configuration_id.clone(),
+                    credential_configurations_supported_object(&credential_configuration),
+                );
+
+                vec![CredentialConfigurationUpdated {
+                    credential_configuration,
+                    credential_configurations,
+                }]
+            }
+            RemoveCredentialConfiguration {
+                credential_configuration_id,
+            } => {
+                if !self
+                    .credential_issuer_metadata
+                    .credential_configurations_supported
+                    .contains_key(&credential_configuration_id)
+                {
+                    return Err(CredentialConfigurationNotFoundError(credential_configuration_id));
+                }
+
+                vec![CredentialConfigurationRemoved {
+                    credential_configuration_id,
+                }]
+            }
+        };
+
+        // The signed metadata is refreshed whenever the Credential Issuer Metadata changes.
+        let mut server_config = self.clone();
+        events.iter().cloned().for_each(|event| server_config.apply(event));
+        let signed_metadata = sign_metadata(&server_config.credential_issuer_metadata, services).await?;
+
+        Ok(events
+            .into_iter()
+            .chain(std::iter::once(ServerMetadataSigned { signed_metadata }))
+            .collect())
     }
 
     fn apply(&mut self, event: Self::Event) {
@@ -128,8 +170,147 @@ impl Aggregate for ServerConfig {
             }
             CredentialConfigurationAdded {
                 credential_configurations,
+                ..
+            }
+            | CredentialConfigurationUpdated {
+                credential_configurations,
+                ..
             } => self.credential_issuer_metadata.credential_configurations_supported = credential_configurations,
+            CredentialConfigurationRemoved {
+                credential_configuration_id,
+            } => {
+                self.credential_issuer_metadata
+                    .credential_configurations_supported
+                    .remove(&credential_configuration_id);
+            }
+            ServerMetadataSigned { .. } => {}
+        }
+    }
+}
+
+/// Returns the `signed_metadata` JWT, which contains the Credential Issuer Metadata as its claims and is signed with
+/// the issuer key.
+async fn sign_metadata(
+    credential_issuer_metadata: &CredentialIssuerMetadata,
+    services: &IssuanceServices,
+) -> Result<String, ServerConfigError> {
+    let default_did_method = get_preferred_did_method();
+
+    let issuer_did = services
+        .issuer
+        .identifier(&default_did_method.to_string(), get_preferred_signing_algorithm())
+        .await
+        .map_err(|e| MetadataSigningError(e.to_string()))?;
+
+    #[cfg(feature = "test_utils")]
+    let iat = 0;
+    #[cfg(not(feature = "test_utils"))]
+    let iat = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    let mut claims = serde_json::to_value(credential_issuer_metadata)
+        .map_err(|e| MetadataSigningError(e.to_string()))?
+        .as_object()
+        .cloned()
+        .unwrap_or_default();
+
+    claims.remove("signed_metadata");
+    claims.insert("iss".to_string(), json!(issuer_did));
+    claims.insert("sub".to_string(), json!(credential_issuer_metadata.credential_issuer));
+    claims.insert("iat".to_string(), json!(iat));
+
+    let mut header = Header::new(get_preferred_signing_algorithm());
+    header.typ = Some("openidvci-issuer-metadata+jwt".to_string());
+
+    jwt::encode(services.issuer.clone(), header, claims, &default_did_method.to_string())
+        .await
+        .map_err(|e| MetadataSigningError(e.to_string()))
+}
+
+/// Makes sure that the JSON Schema of the Credential Configuration (if any) can be used to validate Credentials.
+fn validate_credential_schema(credential_configuration: &CredentialConfiguration) -> Result<(), ServerConfigError> {
+    match &credential_configuration.credential_schema {
+        Some(credential_schema) => credential_schema::compile(credential_schema)
+            .map(|_| ())
+            .map_err(InvalidCredentialSchemaError),
+        None => Ok(()),
+    }
+}
+
+/// Makes sure that the templates of the Credential Configuration can be used to render Credentials and that each of
+/// them can be referenced by a unique `template_id`.
+fn validate_credential_templates(credential_configuration: &CredentialConfiguration) -> Result<(), ServerConfigError> {
+    let mut template_ids = HashSet::new();
+
+    for credential_template in &credential_configuration.templates {
+        if !template_ids.insert(&credential_template.template_id) {
+            return Err(InvalidCredentialTemplateError(
+                credential_template.template_id.clone(),
+                "the `template_id` is used more than once".to_string(),
+            ));
         }
+
+        template::validate(credential_template)
+            .map_err(|e| InvalidCredentialTemplateError(credential_template.template_id.clone(), e))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the `CredentialConfigurationsSupportedObject` that is advertised in the Credential Issuer Metadata for the
+/// given Credential Configuration.
+fn credential_configurations_supported_object(
+    credential_configuration: &CredentialConfiguration,
+) -> CredentialConfigurationsSupportedObject {
+    let mut cryptographic_binding_methods_supported: Vec<_> = config()
+        .did_methods
+        .iter()
+        .filter(|&(_, options)| options.enabled)
+        .map(|(did_method, _)| did_method.to_string())
+        .collect();
+
+    cryptographic_binding_methods_supported.sort();
+
+    let signing_algorithms_supported: Vec<Algorithm> = config()
+        .signing_algorithms_supported
+        .iter()
+        .filter(|(_, options)| options.enabled)
+        .map(|(alg, _)| *alg)
+        .collect();
+
+    let proof_types_supported = HashMap::from_iter([(
+        ProofType::Jwt,
+        KeyProofMetadata {
+            proof_signing_alg_values_supported: signing_algorithms_supported.clone(),
+        },
+    )]);
+
+    let credential_signing_alg_values_supported = match credential_configuration.credential_format_with_parameters {
+        // The Mobile Security Object of `mso_mdoc` Credentials is always signed using the ES256 key.
+        CredentialFormats::MsoMdoc(_) => vec!["ES256".to_string()],
+        // For `ldp_vc` Credentials the proof suite is advertised instead of the signing algorithm.
+        CredentialFormats::LdpVc(_) => {
+            vec![credential_configuration.proof_suite.unwrap_or_default().to_string()]
+        }
+        _ => signing_algorithms_supported
+            .into_iter()
+            .map(|algorithm| match algorithm {
+                jsonwebtoken::Algorithm::EdDSA => "EdDSA".to_string(),
+                jsonwebtoken::Algorithm::ES256 => "ES256".to_string(),
+                _ => unimplemented!("Unsupported algorithm: {:?}", algorithm),
+            })
+            .collect(),
+    };
+
+    CredentialConfigurationsSupportedObject {
+        credential_format: credential_configuration.credential_format_with_parameters.clone(),
+        cryptographic_binding_methods_supported,
+        credential_signing_alg_values_supported,
+        proof_types_supported,
+        display: credential_configuration.display.clone(),
+        ..Default::default()
     }
 }
 
@@ -137,9 +318,12 @@ impl Aggregate for ServerConfig {
 pub mod server_config_tests {
     use super::test_utils::*;
     use super::*;
+    use crate::credential::aggregate::test_utils::SD_JWT_VC_CREDENTIAL_CONFIGURATION;
     use crate::server_config::aggregate::ServerConfig;
     use crate::server_config::event::ServerConfigEvent;
-    use agent_shared::config::CredentialConfiguration;
+    use agent_secret_manager::service::Service;
+    use agent_shared::config::{CredentialConfiguration, CredentialSchema};
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
     use cqrs_es::test::TestFramework;
     use oid4vci::credential_format_profiles::w3c_verifiable_credentials::jwt_vc_json::JwtVcJson;
     use oid4vci::credential_format_profiles::{w3c_verifiable_credentials, CredentialFormats, Parameters};
@@ -148,57 +332,417 @@ pub mod server_config_tests {
 
     type ServerConfigTestFramework = TestFramework<ServerConfig>;
 
+    /// Asserts that the `expected_events` are followed by a `ServerMetadataSigned` event and returns the claims of its
+    /// `signed_metadata`.
+    fn assert_signed_events(
+        events: Vec<ServerConfigEvent>,
+        expected_events: Vec<ServerConfigEvent>,
+    ) -> serde_json::Value {
+        let (signed_event, events) = events.split_last().unwrap();
+        assert_eq!(events, expected_events);
+
+        match signed_event {
+            ServerConfigEvent::ServerMetadataSigned { signed_metadata } => {
+                let payload = signed_metadata.split('.').nth(1).unwrap();
+                serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap()
+            }
+            event => panic!("Expected a `ServerMetadataSigned` event, found: {event:?}"),
+        }
+    }
+
     #[rstest]
-    fn test_load_server_metadata(
+    #[serial_test::serial]
+    async fn test_load_server_metadata(
         authorization_server_metadata: Box<AuthorizationServerMetadata>,
         credential_issuer_metadata: Box<CredentialIssuerMetadata>,
     ) {
-        ServerConfigTestFramework::with(())
+        let events = ServerConfigTestFramework::with(Service::default())
             .given_no_previous_events()
-            .when(ServerConfigCommand::InitializeServerMetadata {
+            .when_async(ServerConfigCommand::InitializeServerMetadata {
                 authorization_server_metadata: authorization_server_metadata.clone(),
                 credential_issuer_metadata: credential_issuer_metadata.clone(),
             })
-            .then_expect_events(vec![ServerConfigEvent::ServerMetadataInitialized {
+            .await
+            .inspect_result()
+            .unwrap();
+
+        let claims = assert_signed_events(
+            events,
+            vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata,
+                credential_issuer_metadata: credential_issuer_metadata.clone(),
+            }],
+        );
+
+        assert_eq!(claims["sub"], json!("https://example.com/"));
+        assert_eq!(
+            claims["credential_configurations_supported"],
+            json!(credential_issuer_metadata.credential_configurations_supported)
+        );
+    }
+
+    #[fixture]
+    fn badge_credential_configuration() -> CredentialConfiguration {
+        CredentialConfiguration {
+            credential_configuration_id: "badge".to_string(),
+            credential_format_with_parameters: CredentialFormats::JwtVcJson(Parameters::<JwtVcJson> {
+                parameters: w3c_verifiable_credentials::jwt_vc_json::JwtVcJsonParameters {
+                    credential_definition: w3c_verifiable_credentials::jwt_vc_json::CredentialDefinition {
+                        type_: vec!["VerifiableCredential".to_string()],
+                        credential_subject: Default::default(),
+                    },
+                    order: None,
+                },
+            }),
+            display: vec![json!({
+                "name": "Verifiable Credential",
+                "locale": "en",
+                "logo": {
+                    "uri": "https://impierce.com/images/logo-blue.png",
+                    "alt_text": "UniCore Logo"
+                }
+            })],
+            selectively_disclosable_claims: vec![],
+            proof_suite: None,
+            validity_period: None,
+            credential_schema: None,
+            templates: vec![],
+        }
+    }
+
+    #[fixture]
+    fn degree_credential_configuration() -> CredentialConfiguration {
+        CredentialConfiguration {
+            credential_configuration_id: "degree".to_string(),
+            credential_format_with_parameters: SD_JWT_VC_CREDENTIAL_CONFIGURATION.credential_format.clone(),
+            display: vec![json!({
+                "locale": "en",
+                "name": "Degree Credential"
+            })],
+            selectively_disclosable_claims: vec!["first_name".to_string(), "last_name".to_string()],
+            proof_suite: None,
+            validity_period: None,
+            credential_schema: None,
+            templates: vec![],
+        }
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_create_credentials_supported(
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        badge_credential_configuration: CredentialConfiguration,
+    ) {
+        let credential_configurations = credential_issuer_metadata.credential_configurations_supported.clone();
+
+        let events = ServerConfigTestFramework::with(Service::default())
+            .given(vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata,
+                credential_issuer_metadata: Box::new(CredentialIssuerMetadata {
+                    credential_configurations_supported: Default::default(),
+                    ..*credential_issuer_metadata
+                }),
+            }])
+            .when_async(ServerConfigCommand::AddCredentialConfiguration {
+                credential_configuration: badge_credential_configuration.clone(),
+            })
+            .await
+            .inspect_result()
+            .unwrap();
+
+        // The signed metadata is refreshed with the added Credential Configuration.
+        let claims = assert_signed_events(
+            events,
+            vec![ServerConfigEvent::CredentialConfigurationAdded {
+                credential_configuration: Some(badge_credential_configuration),
+                credential_configurations: credential_configurations.clone(),
+            }],
+        );
+
+        assert_eq!(
+            claims["credential_configurations_supported"],
+            json!(credential_configurations)
+        );
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_add_sd_jwt_vc_credential_configuration(
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        mut credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        degree_credential_configuration: CredentialConfiguration,
+    ) {
+        // Only the `vc+sd-jwt` Credential Configuration is part of the Credential Issuer Metadata.
+        credential_issuer_metadata.credential_configurations_supported = HashMap::new();
+        let credential_configurations =
+            HashMap::from_iter([("degree".to_string(), SD_JWT_VC_CREDENTIAL_CONFIGURATION.clone())]);
+
+        let events = ServerConfigTestFramework::with(Service::default())
+            .given(vec![ServerConfigEvent::ServerMetadataInitialized {
                 authorization_server_metadata,
                 credential_issuer_metadata,
-            }]);
+            }])
+            .when_async(ServerConfigCommand::AddCredentialConfiguration {
+                credential_configuration: degree_credential_configuration.clone(),
+            })
+            .await
+            .inspect_result()
+            .unwrap();
+
+        assert_signed_events(
+            events,
+            vec![ServerConfigEvent::CredentialConfigurationAdded {
+                credential_configuration: Some(degree_credential_configuration),
+                credential_configurations,
+            }],
+        );
     }
+
     #[rstest]
-    fn test_create_credentials_supported(
+    #[serial_test::serial]
+    async fn test_add_credential_configuration_keeps_existing_credential_configurations(
         authorization_server_metadata: Box<AuthorizationServerMetadata>,
         credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        degree_credential_configuration: CredentialConfiguration,
     ) {
-        ServerConfigTestFramework::with(())
+        let mut credential_configurations = credential_issuer_metadata.credential_configurations_supported.clone();
+        credential_configurations.insert("degree".to_string(), SD_JWT_VC_CREDENTIAL_CONFIGURATION.clone());
+
+        let events = ServerConfigTestFramework::with(Service::default())
             .given(vec![ServerConfigEvent::ServerMetadataInitialized {
                 authorization_server_metadata,
-                credential_issuer_metadata: credential_issuer_metadata.clone(),
+                credential_issuer_metadata,
+            }])
+            .when_async(ServerConfigCommand::AddCredentialConfiguration {
+                credential_configuration: degree_credential_configuration.clone(),
+            })
+            .await
+            .inspect_result()
+            .unwrap();
+
+        // The `badge` Credential Configuration is still advertised next to the added `degree` one.
+        let claims = assert_signed_events(
+            events,
+            vec![ServerConfigEvent::CredentialConfigurationAdded {
+                credential_configuration: Some(degree_credential_configuration),
+                credential_configurations: credential_configurations.clone(),
+            }],
+        );
+
+        assert_eq!(
+            claims["credential_configurations_supported"],
+            json!(credential_configurations)
+        );
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    fn test_add_existing_credential_configuration(
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        badge_credential_configuration: CredentialConfiguration,
+    ) {
+        ServerConfigTestFramework::with(Service::default())
+            .given(vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata,
+                credential_issuer_metadata,
             }])
             .when(ServerConfigCommand::AddCredentialConfiguration {
-                credential_configuration: CredentialConfiguration {
-                    credential_configuration_id: "badge".to_string(),
-                    credential_format_with_parameters: CredentialFormats::JwtVcJson(Parameters::<JwtVcJson> {
-                        parameters: w3c_verifiable_credentials::jwt_vc_json::JwtVcJsonParameters {
-                            credential_definition: w3c_verifiable_credentials::jwt_vc_json::CredentialDefinition {
-                                type_: vec!["VerifiableCredential".to_string()],
-                                credential_subject: Default::default(),
-                            },
-                            order: None,
-                        },
-                    }),
-                    display: vec![json!({
-                        "name": "Verifiable Credential",
-                        "locale": "en",
-                        "logo": {
-                            "uri": "https://impierce.com/images/logo-blue.png",
-                            "alt_text": "UniCore Logo"
-                        }
-                    })],
-                },
+                credential_configuration: badge_credential_configuration,
             })
-            .then_expect_events(vec![ServerConfigEvent::CredentialConfigurationAdded {
-                credential_configurations: credential_issuer_metadata.credential_configurations_supported,
-            }]);
+            .then_expect_error_message("A Credential Configuration with id `badge` already exists");
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_update_credential_configuration(
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        mut badge_credential_configuration: CredentialConfiguration,
+    ) {
+        badge_credential_configuration.display = vec![json!({
+            "name": "Badge",
+            "locale": "en"
+        })];
+
+        let mut credential_configurations = credential_issuer_metadata.credential_configurations_supported.clone();
+        credential_configurations.get_mut("badge").unwrap().display = badge_credential_configuration.display.clone();
+
+        let events = ServerConfigTestFramework::with(Service::default())
+            .given(vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata,
+                credential_issuer_metadata,
+            }])
+            .when_async(ServerConfigCommand::UpdateCredentialConfiguration {
+                credential_configuration: badge_credential_configuration.clone(),
+            })
+            .await
+            .inspect_result()
+            .unwrap();
+
+        assert_signed_events(
+            events,
+            vec![ServerConfigEvent::CredentialConfigurationUpdated {
+                credential_configuration: badge_credential_configuration,
+                credential_configurations,
+            }],
+        );
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    fn test_update_unknown_credential_configuration(
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        degree_credential_configuration: CredentialConfiguration,
+    ) {
+        ServerConfigTestFramework::with(Service::default())
+            .given(vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata,
+                credential_issuer_metadata,
+            }])
+            .when(ServerConfigCommand::UpdateCredentialConfiguration {
+                credential_configuration: degree_credential_configuration,
+            })
+            .then_expect_error_message("No Credential Configuration found with id: `degree`");
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    fn test_add_credential_configuration_with_invalid_credential_schema(
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        mut degree_credential_configuration: CredentialConfiguration,
+    ) {
+        degree_credential_configuration.credential_schema = Some(CredentialSchema {
+            id: "https://example.com/schemas/degree.json".parse().unwrap(),
+            schema: json!({ "type": "unknown" }),
+        });
+
+        ServerConfigTestFramework::with(Service::default())
+            .given(vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata,
+                credential_issuer_metadata,
+            }])
+            .when(ServerConfigCommand::AddCredentialConfiguration {
+                credential_configuration: degree_credential_configuration,
+            })
+            .then_expect_error_message(
+                r#"The JSON Schema of the Credential Configuration is invalid: "unknown" is not valid under any of the given schemas"#,
+            );
+    }
+
+    #[rstest]
+    #[case::duplicate_template_id(
+        vec![json!({ "template_id": "degree" }), json!({ "template_id": "degree" })],
+        "The credential template `degree` is invalid: the `template_id` is used more than once"
+    )]
+    #[case::reserved_property(
+        vec![json!({ "template_id": "degree", "properties": { "issuer": { "value": "Ferris" } } })],
+        "The credential template `degree` is invalid: the property `issuer` cannot be mapped"
+    )]
+    #[serial_test::serial]
+    fn test_add_credential_configuration_with_invalid_credential_templates(
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+        mut degree_credential_configuration: CredentialConfiguration,
+        #[case] templates: Vec<serde_json::Value>,
+        #[case] expected_error_message: &str,
+    ) {
+        degree_credential_configuration.templates = templates
+            .into_iter()
+            .map(|template| serde_json::from_value(template).unwrap())
+            .collect();
+
+        ServerConfigTestFramework::with(Service::default())
+            .given(vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata,
+                credential_issuer_metadata,
+            }])
+            .when(ServerConfigCommand::AddCredentialConfiguration {
+                credential_configuration: degree_credential_configuration,
+            })
+            .then_expect_error_message(expected_error_message);
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_remove_credential_configuration(
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+    ) {
+        let events = ServerConfigTestFramework::with(Service::default())
+            .given(vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata,
+                credential_issuer_metadata,
+            }])
+            .when_async(ServerConfigCommand::RemoveCredentialConfiguration {
+                credential_configuration_id: "badge".to_string(),
+            })
+            .await
+            .inspect_result()
+            .unwrap();
+
+        // The removed Credential Configuration is no longer part of the signed metadata.
+        let claims = assert_signed_events(
+            events,
+            vec![ServerConfigEvent::CredentialConfigurationRemoved {
+                credential_configuration_id: "badge".to_string(),
+            }],
+        );
+
+        assert_eq!(claims["credential_configurations_supported"], json!({}));
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    fn test_remove_unknown_credential_configuration(
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+    ) {
+        ServerConfigTestFramework::with(Service::default())
+            .given(vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata,
+                credential_issuer_metadata,
+            }])
+            .when(ServerConfigCommand::RemoveCredentialConfiguration {
+                credential_configuration_id: "degree".to_string(),
+            })
+            .then_expect_error_message("No Credential Configuration found with id: `degree`");
+    }
+
+    #[rstest]
+    #[serial_test::serial]
+    async fn test_reinitialize_server_metadata_preserves_credential_configurations(
+        authorization_server_metadata: Box<AuthorizationServerMetadata>,
+        credential_issuer_metadata: Box<CredentialIssuerMetadata>,
+    ) {
+        let empty_credential_issuer_metadata = Box::new(CredentialIssuerMetadata {
+            credential_configurations_supported: Default::default(),
+            ..*credential_issuer_metadata.clone()
+        });
+
+        let events = ServerConfigTestFramework::with(Service::default())
+            .given(vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata: authorization_server_metadata.clone(),
+                credential_issuer_metadata: credential_issuer_metadata.clone(),
+            }])
+            .when_async(ServerConfigCommand::InitializeServerMetadata {
+                authorization_server_metadata: authorization_server_metadata.clone(),
+                credential_issuer_metadata: empty_credential_issuer_metadata,
+            })
+            .await
+            .inspect_result()
+            .unwrap();
+
+        assert_signed_events(
+            events,
+            vec![ServerConfigEvent::ServerMetadataInitialized {
+                authorization_server_metadata,
+                credential_issuer_metadata,
+            }],
+        );
     }
 }
 
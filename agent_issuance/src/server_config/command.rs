@@ -14,4 +14,10 @@ pub enum ServerConfigCommand {
     AddCredentialConfiguration {
         credential_configuration: CredentialConfiguration,
     },
+    UpdateCredentialConfiguration {
+        credential_configuration: CredentialConfiguration,
+    },
+    RemoveCredentialConfiguration {
+        credential_configuration_id: String,
+    },
 }
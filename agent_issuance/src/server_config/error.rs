@@ -1,4 +1,19 @@
 use thiserror::Error;
 
 #[derive(Error, Debug)]
-pub enum ServerConfigError {}
+pub enum ServerConfigError {
+    #[error("No Credential Configuration found with id: `{0}`")]
+    CredentialConfigurationNotFoundError(String),
+
+    #[error("A Credential Configuration with id `{0}` already exists")]
+    CredentialConfigurationAlreadyExistsError(String),
+
+    #[error("The JSON Schema of the Credential Configuration is invalid: {0}")]
+    InvalidCredentialSchemaError(String),
+
+    #[error("The credential template `{0}` is invalid: {1}")]
+    InvalidCredentialTemplateError(String, String),
+
+    #[error("Failed to sign the Credential Issuer Metadata: {0}")]
+    MetadataSigningError(String),
+}
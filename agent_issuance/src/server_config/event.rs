@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use agent_shared::config::CredentialConfiguration;
 use cqrs_es::DomainEvent;
 use oid4vci::credential_issuer::{
     authorization_server_metadata::AuthorizationServerMetadata,
@@ -15,8 +16,22 @@ pub enum ServerConfigEvent {
         credential_issuer_metadata: Box<CredentialIssuerMetadata>,
     },
     CredentialConfigurationAdded {
+        /// The added Credential Configuration. Absent for Credential Configurations that were added before their
+        /// options were recorded.
+        #[serde(default)]
+        credential_configuration: Option<CredentialConfiguration>,
         credential_configurations: HashMap<String, CredentialConfigurationsSupportedObject>,
     },
+    CredentialConfigurationUpdated {
+        credential_configuration: CredentialConfiguration,
+        credential_configurations: HashMap<String, CredentialConfigurationsSupportedObject>,
+    },
+    CredentialConfigurationRemoved {
+        credential_configuration_id: String,
+    },
+    ServerMetadataSigned {
+        signed_metadata: String,
+    },
 }
 
 impl DomainEvent for ServerConfigEvent {
@@ -26,6 +41,9 @@ impl DomainEvent for ServerConfigEvent {
         let event_type: &str = match self {
             ServerMetadataInitialized { .. } => "ServerMetadataLoaded",
             CredentialConfigurationAdded { .. } => "CredentialConfigurationAdded",
+            CredentialConfigurationUpdated { .. } => "CredentialConfigurationUpdated",
+            CredentialConfigurationRemoved { .. } => "CredentialConfigurationRemoved",
+            ServerMetadataSigned { .. } => "ServerMetadataSigned",
         };
         event_type.to_string()
     }
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use agent_shared::config::CredentialConfiguration;
 use cqrs_es::{EventEnvelope, View};
 use oid4vci::credential_issuer::{
     authorization_server_metadata::AuthorizationServerMetadata, credential_issuer_metadata::CredentialIssuerMetadata,
@@ -10,6 +13,13 @@ use crate::server_config::{aggregate::ServerConfig, event::ServerConfigEvent};
 pub struct ServerConfigView {
     pub authorization_server_metadata: AuthorizationServerMetadata,
     pub credential_issuer_metadata: Option<CredentialIssuerMetadata>,
+    /// The Credential Configurations as they were added, including the options that are not part of the Credential
+    /// Issuer Metadata such as the `selectively_disclosable_claims` and the `validity_period`.
+    #[serde(default)]
+    pub credential_configurations: HashMap<String, CredentialConfiguration>,
+    /// The Credential Issuer Metadata as a JWT signed by the issuer, which is served as the `signed_metadata` parameter.
+    #[serde(default)]
+    pub signed_metadata: Option<String>,
 }
 
 impl View<ServerConfig> for ServerConfigView {
@@ -26,6 +36,7 @@ impl View<ServerConfig> for ServerConfigView {
                     .replace(*credential_issuer_metadata.clone());
             }
             CredentialConfigurationAdded {
+                credential_configuration,
                 credential_configurations,
             } => {
                 if let Some(credential_issuer_metadata) = self.credential_issuer_metadata.as_mut() {
@@ -33,6 +44,39 @@ impl View<ServerConfig> for ServerConfigView {
                         .credential_configurations_supported
                         .clone_from(credential_configurations)
                 }
+                if let Some(credential_configuration) = credential_configuration {
+                    self.credential_configurations.insert(
+                        credential_configuration.credential_configuration_id.clone(),
+                        credential_configuration.clone(),
+                    );
+                }
+            }
+            CredentialConfigurationUpdated {
+                credential_configuration,
+                credential_configurations,
+            } => {
+                if let Some(credential_issuer_metadata) = self.credential_issuer_metadata.as_mut() {
+                    credential_issuer_metadata
+                        .credential_configurations_supported
+                        .clone_from(credential_configurations)
+                }
+                self.credential_configurations.insert(
+                    credential_configuration.credential_configuration_id.clone(),
+                    credential_configuration.clone(),
+                );
+            }
+            CredentialConfigurationRemoved {
+                credential_configuration_id,
+            } => {
+                if let Some(credential_issuer_metadata) = self.credential_issuer_metadata.as_mut() {
+                    credential_issuer_metadata
+                        .credential_configurations_supported
+                        .remove(credential_configuration_id);
+                }
+                self.credential_configurations.remove(credential_configuration_id);
+            }
+            ServerMetadataSigned { signed_metadata } => {
+                self.signed_metadata.replace(signed_metadata.clone());
             }
         }
     }
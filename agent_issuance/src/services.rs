@@ -1,14 +1,76 @@
-use agent_secret_manager::service::Service;
+use crate::credential::credential_schema::CredentialSchemas;
+use agent_secret_manager::{service::Service, subject::Subject as SecretManagerSubject};
+use agent_shared::config::{config, Display, SecretManagerConfig, TenantConfiguration};
+use base64::{engine::general_purpose::STANDARD, Engine};
 use oid4vc_core::Subject;
 use std::sync::Arc;
 
 /// Issuance services. This struct is used to sign credentials and validate credential requests.
 pub struct IssuanceServices {
     pub issuer: Arc<dyn Subject>,
+    /// Signs raw bytes instead of JWTs, e.g. the Mobile Security Object of `mso_mdoc` Credentials or the Data
+    /// Integrity proofs of `ldp_vc` Credentials.
+    pub signer: Arc<SecretManagerSubject>,
+    /// The public URL of the Credential Issuer, which is used as the `id` of the `issuer` of the Credentials.
+    pub url: String,
+    /// The display properties of the Credential Issuer. The first `name` is used as the name of the `issuer` of the
+    /// Credentials.
+    pub display: Vec<Display>,
+    /// The DER encoded X.509 certificate chain of the ES256 key of the Credential Issuer, which is included as the
+    /// `x5chain` of the `IssuerAuth` of `mso_mdoc` Credentials.
+    pub certificate_chain: Vec<Vec<u8>>,
+    /// The compiled JSON Schemas against which the Credentials are validated before they are issued.
+    pub credential_schemas: CredentialSchemas,
+}
+
+impl IssuanceServices {
+    /// Returns the issuance services of a tenant, which issues Credentials under its own URL and name.
+    pub fn for_tenant(issuer: Arc<SecretManagerSubject>, tenant: &TenantConfiguration) -> Self {
+        Self {
+            url: tenant.url.clone(),
+            display: tenant.display.clone(),
+            certificate_chain: certificate_chain(&tenant.secret_manager),
+            ..Self::new(issuer)
+        }
+    }
 }
 
 impl Service for IssuanceServices {
-    fn new(issuer: Arc<dyn Subject>) -> Self {
-        Self { issuer }
+    fn new(issuer: Arc<SecretManagerSubject>) -> Self {
+        Self {
+            issuer: issuer.clone(),
+            signer: issuer,
+            url: config().url.clone(),
+            display: config().display.clone(),
+            certificate_chain: certificate_chain(&config().secret_manager),
+            credential_schemas: Default::default(),
+        }
     }
 }
+
+/// Reads the PEM encoded certificate chain from the `issuer_certificate_chain_path`, if any, and returns the DER
+/// encoded certificates in the same order.
+fn certificate_chain(secret_manager_config: &SecretManagerConfig) -> Vec<Vec<u8>> {
+    let Some(path) = &secret_manager_config.issuer_certificate_chain_path else {
+        return vec![];
+    };
+
+    let pem = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read the certificate chain at `{path}`: {e}"));
+
+    pem.split("-----BEGIN CERTIFICATE-----")
+        .skip(1)
+        .map(|block| {
+            let base64 = block
+                .split("-----END CERTIFICATE-----")
+                .next()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<String>();
+
+            STANDARD
+                .decode(base64)
+                .unwrap_or_else(|e| panic!("Failed to decode the certificate chain at `{path}`: {e}"))
+        })
+        .collect()
+}
@@ -1,4 +1,4 @@
-use agent_shared::config::config;
+use agent_shared::config::{config, CredentialConfiguration, Display, TenantConfiguration};
 use agent_shared::url_utils::UrlAppendHelpers;
 use oid4vci::credential_issuer::{
     authorization_server_metadata::AuthorizationServerMetadata, credential_issuer_metadata::CredentialIssuerMetadata,
@@ -8,38 +8,70 @@ use crate::server_config::command::ServerConfigCommand;
 
 /// Returns the startup commands for the application.
 pub fn startup_commands(host: url::Url) -> Vec<ServerConfigCommand> {
-    vec![load_server_metadata(host), create_credentials_supported()]
+    std::iter::once(load_server_metadata(host))
+        .chain(create_credentials_supported())
+        .collect()
+}
+
+/// Returns the startup commands for a tenant, which has its own display metadata and Credential Configurations.
+pub fn tenant_startup_commands(tenant: &TenantConfiguration) -> Vec<ServerConfigCommand> {
+    // The `url` of every tenant is validated when the configuration is loaded.
+    let host = url::Url::parse(&tenant.url).expect("Invalid tenant `url`");
+
+    std::iter::once(server_metadata(host, &tenant.display))
+        .chain(credential_configuration_commands(&tenant.credential_configurations))
+        .collect()
 }
 
 pub fn load_server_metadata(base_url: url::Url) -> ServerConfigCommand {
-    let display = config().display.first().map(|display| {
-        let display = serde_json::to_value(display).unwrap();
-        vec![display]
+    server_metadata(base_url, &config().display)
+}
+
+fn server_metadata(base_url: url::Url, display: &[Display]) -> ServerConfigCommand {
+    // Every configured locale is exposed, so that Wallets can pick the one that matches the language of the user.
+    let display = (!display.is_empty()).then(|| {
+        display
+            .iter()
+            .map(|display| serde_json::to_value(display).unwrap())
+            .collect()
     });
 
     ServerConfigCommand::InitializeServerMetadata {
         authorization_server_metadata: Box::new(AuthorizationServerMetadata {
             issuer: base_url.clone(),
+            authorization_endpoint: Some(base_url.append_path_segment("auth/authorize")),
             token_endpoint: Some(base_url.append_path_segment("auth/token")),
+            response_types_supported: Some(vec!["code".to_string()]),
+            code_challenge_methods_supported: Some(vec!["S256".to_string()]),
             ..Default::default()
         }),
         credential_issuer_metadata: Box::new(CredentialIssuerMetadata {
             credential_issuer: base_url.clone(),
             credential_endpoint: base_url.append_path_segment("openid4vci/credential"),
+            batch_credential_endpoint: Some(base_url.append_path_segment("openid4vci/batch_credential")),
+            deferred_credential_endpoint: Some(base_url.append_path_segment("openid4vci/deferred_credential")),
+            notification_endpoint: Some(base_url.append_path_segment("openid4vci/notification")),
             display,
             ..Default::default()
         }),
     }
 }
 
-pub fn create_credentials_supported() -> ServerConfigCommand {
-    let credential_configuration = config()
-        .credential_configurations
-        .first()
-        .expect("No credential_configurations found")
-        .clone();
+/// Returns a command for each of the Credential Configurations in the config.
+pub fn create_credentials_supported() -> Vec<ServerConfigCommand> {
+    credential_configuration_commands(&config().credential_configurations)
+}
 
-    ServerConfigCommand::AddCredentialConfiguration {
-        credential_configuration,
-    }
+fn credential_configuration_commands(
+    credential_configurations: &[CredentialConfiguration],
+) -> Vec<ServerConfigCommand> {
+    credential_configurations
+        .iter()
+        .cloned()
+        .map(
+            |credential_configuration| ServerConfigCommand::AddCredentialConfiguration {
+                credential_configuration,
+            },
+        )
+        .collect()
 }
@@ -1,6 +1,8 @@
 use agent_shared::application_state::CommandHandler;
+use agent_shared::dpop::JtiCache;
 use agent_shared::handlers::command_handler;
 use cqrs_es::persist::ViewRepository;
+use cqrs_es::AggregateError;
 use std::sync::Arc;
 use tracing::{info, warn};
 
@@ -10,16 +12,30 @@ use crate::credential::queries::CredentialView;
 use crate::offer::aggregate::Offer;
 use crate::offer::queries::access_token::AccessTokenView;
 use crate::offer::queries::all_offers::AllOffersView;
+use crate::offer::queries::authorization_code::AuthorizationCodeView;
+use crate::offer::queries::credential_offer_uri::CredentialOfferUriView;
+use crate::offer::queries::issuer_state::IssuerStateView;
 use crate::offer::queries::pre_authorized_code::PreAuthorizedCodeView;
+use crate::offer::queries::refresh_token::RefreshTokenView;
 use crate::offer::queries::OfferView;
+use crate::offer::tx_channel_registry::TxChannelRegistry;
 use crate::server_config::aggregate::ServerConfig;
 use crate::server_config::command::ServerConfigCommand;
+use crate::server_config::error::ServerConfigError;
 use crate::server_config::queries::ServerConfigView;
+use crate::status_list::aggregate::StatusList;
+use crate::status_list::queries::StatusListView;
 
 #[derive(Clone)]
 pub struct IssuanceState {
     pub command: CommandHandlers,
     pub query: Queries,
+    /// Used to wait for credentials to be added to an Offer, e.g. by an external server.
+    pub tx_channel_registry: TxChannelRegistry,
+    /// The public URL of the Credential Issuer, against which the `htu` of DPoP proofs is verified.
+    pub url: String,
+    /// Used to reject DPoP proofs that are replayed.
+    pub dpop_jti_cache: JtiCache,
 }
 
 /// The command handlers are used to execute commands on the aggregates.
@@ -28,6 +44,7 @@ pub struct CommandHandlers {
     pub server_config: CommandHandler<ServerConfig>,
     pub credential: CommandHandler<Credential>,
     pub offer: CommandHandler<Offer>,
+    pub status_list: CommandHandler<StatusList>,
 }
 
 /// This type is used to define the queries that are used to query the view repositories. We make use of `dyn` here, so
@@ -41,9 +58,14 @@ type Queries = ViewRepositories<
     dyn ViewRepository<AllOffersView, Offer>,
     dyn ViewRepository<PreAuthorizedCodeView, Offer>,
     dyn ViewRepository<AccessTokenView, Offer>,
+    dyn ViewRepository<IssuerStateView, Offer>,
+    dyn ViewRepository<AuthorizationCodeView, Offer>,
+    dyn ViewRepository<RefreshTokenView, Offer>,
+    dyn ViewRepository<CredentialOfferUriView, Offer>,
+    dyn ViewRepository<StatusListView, StatusList>,
 >;
 
-pub struct ViewRepositories<SC, C, C1, O, O1, O2, O3>
+pub struct ViewRepositories<SC, C, C1, O, O1, O2, O3, O4, O5, O6, O7, SL>
 where
     SC: ViewRepository<ServerConfigView, ServerConfig> + ?Sized,
     C: ViewRepository<CredentialView, Credential> + ?Sized,
@@ -52,6 +74,11 @@ where
     O1: ViewRepository<AllOffersView, Offer> + ?Sized,
     O2: ViewRepository<PreAuthorizedCodeView, Offer> + ?Sized,
     O3: ViewRepository<AccessTokenView, Offer> + ?Sized,
+    O4: ViewRepository<IssuerStateView, Offer> + ?Sized,
+    O5: ViewRepository<AuthorizationCodeView, Offer> + ?Sized,
+    O6: ViewRepository<RefreshTokenView, Offer> + ?Sized,
+    O7: ViewRepository<CredentialOfferUriView, Offer> + ?Sized,
+    SL: ViewRepository<StatusListView, StatusList> + ?Sized,
 {
     pub server_config: Arc<SC>,
     pub credential: Arc<C>,
@@ -60,6 +87,11 @@ where
     pub all_offers: Arc<O1>,
     pub pre_authorized_code: Arc<O2>,
     pub access_token: Arc<O3>,
+    pub issuer_state: Arc<O4>,
+    pub authorization_code: Arc<O5>,
+    pub refresh_token: Arc<O6>,
+    pub credential_offer_uri: Arc<O7>,
+    pub status_list: Arc<SL>,
 }
 
 impl Clone for Queries {
@@ -72,6 +104,11 @@ impl Clone for Queries {
             all_offers: self.all_offers.clone(),
             pre_authorized_code: self.pre_authorized_code.clone(),
             access_token: self.access_token.clone(),
+            issuer_state: self.issuer_state.clone(),
+            authorization_code: self.authorization_code.clone(),
+            refresh_token: self.refresh_token.clone(),
+            credential_offer_uri: self.credential_offer_uri.clone(),
+            status_list: self.status_list.clone(),
         }
     }
 }
@@ -79,6 +116,9 @@ impl Clone for Queries {
 /// The unique identifier for the server configuration.
 pub const SERVER_CONFIG_ID: &str = "SERVER-CONFIG-001";
 
+/// The unique identifier for the status list.
+pub const STATUS_LIST_ID: &str = "STATUS-LIST-001";
+
 /// Initialize the application state by executing the startup commands.
 pub async fn initialize(state: &IssuanceState, startup_commands: Vec<ServerConfigCommand>) {
     info!("Initializing ...");
@@ -87,6 +127,11 @@ pub async fn initialize(state: &IssuanceState, startup_commands: Vec<ServerConfi
         let command_string = format!("{:?}", command).split(' ').next().unwrap().to_string();
         match command_handler(SERVER_CONFIG_ID, &state.command.server_config, command).await {
             Ok(_) => info!("Startup task completed: `{}`", command_string),
+            // Credential Configurations from the config are only added once, so that changes that were made at runtime
+            // (e.g. through the REST API) are not overwritten on every startup.
+            Err(AggregateError::UserError(ServerConfigError::CredentialConfigurationAlreadyExistsError(id))) => {
+                info!("Startup task skipped: Credential Configuration `{id}` already exists")
+            }
             Err(err) => warn!("Startup task failed: {:#?}", err),
         }
     }
@@ -0,0 +1,629 @@
+use agent_shared::config::{get_preferred_did_method, get_preferred_signing_algorithm};
+use agent_shared::url_utils::UrlAppendHelpers;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use cqrs_es::Aggregate;
+use flate2::{write::GzEncoder, Compression};
+use jsonwebtoken::Header;
+use oid4vc_core::jwt;
+use oid4vci::VerifiableCredentialJwt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::Write;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::services::IssuanceServices;
+use crate::status_list::command::StatusListCommand;
+use crate::status_list::error::StatusListError::{self, *};
+use crate::status_list::event::StatusListEvent;
+
+/// The number of entries in a status list. The Bitstring Status List specification requires a minimum of 131,072
+/// entries (16KB), so that the status list does not reveal how many credentials have been issued.
+pub const STATUS_LIST_LENGTH: usize = 131_072;
+
+/// Returns the number of the status list that contains the `status_list_index`. A status list index is counted across
+/// all status lists, i.e. the first entry of the second status list has index `STATUS_LIST_LENGTH`.
+fn status_list_of(status_list_index: usize) -> usize {
+    status_list_index / STATUS_LIST_LENGTH
+}
+
+/// The purpose of a status list as described in Bitstring Status List v1.0, Section 2.1.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum StatusPurpose {
+    /// Revocation is permanent.
+    Revocation,
+    /// Suspension is temporary and can be lifted again.
+    Suspension,
+}
+
+/// An aggregate that holds the Bitstring Status Lists of the issuer. The same status list index is used for both the
+/// revocation and the suspension status list. Once a status list is full, a new status list is started.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct StatusList {
+    status_list_indices: HashMap<String, usize>,
+    revoked: BTreeSet<usize>,
+    suspended: BTreeSet<usize>,
+    published_status_lists: BTreeSet<usize>,
+}
+
+impl StatusList {
+    fn entries(&self, status_purpose: StatusPurpose) -> &BTreeSet<usize> {
+        match status_purpose {
+            StatusPurpose::Revocation => &self.revoked,
+            StatusPurpose::Suspension => &self.suspended,
+        }
+    }
+
+    /// Returns the status list that new credentials are added to, together with its allocated (relative) indices.
+    fn current_status_list(&self) -> (usize, HashSet<usize>) {
+        let status_list = self
+            .status_list_indices
+            .values()
+            .max()
+            .map_or(0, |index| status_list_of(*index));
+
+        let allocated: HashSet<usize> = self
+            .status_list_indices
+            .values()
+            .filter(|index| status_list_of(**index) == status_list)
+            .map(|index| index % STATUS_LIST_LENGTH)
+            .collect();
+
+        if allocated.len() < STATUS_LIST_LENGTH {
+            (status_list, allocated)
+        } else {
+            (status_list + 1, HashSet::new())
+        }
+    }
+}
+
+/// Returns the (relative) indices of the `entries` that belong to the given `status_list`.
+fn status_list_entries(entries: &BTreeSet<usize>, status_list: usize) -> BTreeSet<usize> {
+    entries
+        .iter()
+        .filter(|index| status_list_of(**index) == status_list)
+        .map(|index| index % STATUS_LIST_LENGTH)
+        .collect()
+}
+
+#[async_trait]
+impl Aggregate for StatusList {
+    type Command = StatusListCommand;
+    type Event = StatusListEvent;
+    type Error = StatusListError;
+    type Services = Arc<IssuanceServices>;
+
+    fn aggregate_type() -> String {
+        "status_list".to_string()
+    }
+
+    async fn handle(&self, command: Self::Command, services: &Self::Services) -> Result<Vec<Self::Event>, Self::Error> {
+        use StatusListCommand::*;
+        use StatusListEvent::*;
+
+        info!("Handling command: {:?}", command);
+
+        match command {
+            AllocateStatusListIndex { credential_id } => {
+                if self.status_list_indices.contains_key(&credential_id) {
+                    return Err(DuplicateCredentialError(credential_id));
+                }
+
+                let (status_list, allocated) = self.current_status_list();
+
+                // The index is picked at random from the free entries, so that the position of a credential in the
+                // status list does not reveal the order in which the credentials were issued.
+                #[cfg(feature = "test_utils")]
+                let position = 0;
+                #[cfg(not(feature = "test_utils"))]
+                let position = rand::Rng::gen_range(&mut rand::thread_rng(), 0..STATUS_LIST_LENGTH - allocated.len());
+
+                let status_list_index = (0..STATUS_LIST_LENGTH)
+                    .filter(|index| !allocated.contains(index))
+                    .nth(position)
+                    .expect("The status list has free entries")
+                    + status_list * STATUS_LIST_LENGTH;
+
+                let mut events = vec![StatusListIndexAllocated {
+                    credential_id,
+                    status_list_index,
+                }];
+
+                // The status lists are published as soon as the first credential refers to them.
+                if !self.published_status_lists.contains(&status_list) {
+                    for status_purpose in [StatusPurpose::Revocation, StatusPurpose::Suspension] {
+                        events.push(StatusListCredentialSigned {
+                            status_purpose,
+                            status_list,
+                            status_list_credential: sign_status_list_credential(
+                                services,
+                                status_list,
+                                status_purpose,
+                                &BTreeSet::new(),
+                            )
+                            .await?,
+                        });
+                    }
+                }
+
+                Ok(events)
+            }
+            ReleaseStatusListIndex { credential_id } => {
+                let status_list_index = *self
+                    .status_list_indices
+                    .get(&credential_id)
+                    .ok_or(UnknownCredentialError(credential_id.clone()))?;
+
+                // Releasing the index of a revoked or suspended credential would silently reinstate it.
+                if self.revoked.contains(&status_list_index) || self.suspended.contains(&status_list_index) {
+                    return Err(CredentialStatusSetError(credential_id));
+                }
+
+                Ok(vec![StatusListIndexReleased {
+                    credential_id,
+                    status_list_index,
+                }])
+            }
+            UpdateCredentialStatus {
+                credential_id,
+                status_purpose,
+                status,
+            } => {
+                let status_list_index = *self
+                    .status_list_indices
+                    .get(&credential_id)
+                    .ok_or(UnknownCredentialError(credential_id.clone()))?;
+
+                let mut entries = self.entries(status_purpose).clone();
+
+                if status_purpose == StatusPurpose::Revocation && !status && entries.contains(&status_list_index) {
+                    return Err(PermanentRevocationError(credential_id));
+                }
+
+                if entries.contains(&status_list_index) == status {
+                    return Ok(vec![]);
+                }
+
+                if status {
+                    entries.insert(status_list_index);
+                } else {
+                    entries.remove(&status_list_index);
+                }
+
+                // Only the status list that contains the credential needs to be republished.
+                let status_list = status_list_of(status_list_index);
+
+                Ok(vec![
+                    CredentialStatusUpdated {
+                        credential_id,
+                        status_list_index,
+                        status_purpose,
+                        status,
+                    },
+                    StatusListCredentialSigned {
+                        status_purpose,
+                        status_list,
+                        status_list_credential: sign_status_list_credential(
+                            services,
+                            status_list,
+                            status_purpose,
+                            &status_list_entries(&entries, status_list),
+                        )
+                        .await?,
+                    },
+                ])
+            }
+        }
+    }
+
+    fn apply(&mut self, event: Self::Event) {
+        use StatusListEvent::*;
+
+        info!("Applying event: {:?}", event);
+
+        match event {
+            StatusListIndexAllocated {
+                credential_id,
+                status_list_index,
+            } => {
+                self.status_list_indices.insert(credential_id, status_list_index);
+            }
+            StatusListIndexReleased { credential_id, .. } => {
+                self.status_list_indices.remove(&credential_id);
+            }
+            CredentialStatusUpdated {
+                status_list_index,
+                status_purpose,
+                status,
+                ..
+            } => {
+                let entries = match status_purpose {
+                    StatusPurpose::Revocation => &mut self.revoked,
+                    StatusPurpose::Suspension => &mut self.suspended,
+                };
+
+                if status {
+                    entries.insert(status_list_index);
+                } else {
+                    entries.remove(&status_list_index);
+                }
+            }
+            StatusListCredentialSigned { status_list, .. } => {
+                self.published_status_lists.insert(status_list);
+            }
+        }
+    }
+}
+
+/// Returns the path (relative to `status-lists/`) of the status list credential with the given `status_purpose`. The
+/// first status list is served without a number, so that the credentials that refer to it remain valid.
+pub fn status_list_path(status_list: usize, status_purpose: StatusPurpose) -> String {
+    match status_list {
+        0 => status_purpose.to_string(),
+        status_list => format!("{status_purpose}/{status_list}"),
+    }
+}
+
+/// Returns the URL from which the status list credential with the given `status_purpose` can be retrieved from the
+/// Credential Issuer at `url`.
+pub fn status_list_credential_url(url: &str, status_list: usize, status_purpose: StatusPurpose) -> url::Url {
+    url.parse::<url::Url>().unwrap().append_path_segment(&format!(
+        "status-lists/{}",
+        status_list_path(status_list, status_purpose)
+    ))
+}
+
+/// Returns the `BitstringStatusListEntry` objects that need to be included in the `credentialStatus` of a credential
+/// that is issued by the Credential Issuer at `url`.
+pub fn credential_status(url: &str, status_list_index: usize) -> serde_json::Value {
+    let status_list = status_list_of(status_list_index);
+    let status_list_index = status_list_index % STATUS_LIST_LENGTH;
+
+    json!([StatusPurpose::Revocation, StatusPurpose::Suspension]
+        .into_iter()
+        .map(|status_purpose| {
+            let status_list_credential = status_list_credential_url(url, status_list, status_purpose);
+            json!({
+                "id": format!("{status_list_credential}#{status_list_index}"),
+                "type": "BitstringStatusListEntry",
+                "statusPurpose": status_purpose,
+                "statusListIndex": status_list_index.to_string(),
+                "statusListCredential": status_list_credential,
+            })
+        })
+        .collect::<Vec<_>>())
+}
+
+/// Encodes the `entries` as a GZIP-compressed bitstring of `STATUS_LIST_LENGTH` bits, which is base64url-encoded and
+/// prefixed with `u` as described in Bitstring Status List v1.0, Section 3.1. The first index is the left-most bit.
+pub fn encode_list(entries: &BTreeSet<usize>) -> String {
+    let mut bitstring = vec![0u8; STATUS_LIST_LENGTH / 8];
+    for index in entries {
+        bitstring[index / 8] |= 0b1000_0000 >> (index % 8);
+    }
+
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(&bitstring).expect("Failed to compress the bitstring");
+
+    format!(
+        "u{}",
+        URL_SAFE_NO_PAD.encode(encoder.finish().expect("Failed to compress the bitstring"))
+    )
+}
+
+async fn sign_status_list_credential(
+    services: &IssuanceServices,
+    status_list: usize,
+    status_purpose: StatusPurpose,
+    entries: &BTreeSet<usize>,
+) -> Result<String, StatusListError> {
+    let default_did_method = get_preferred_did_method();
+
+    let issuer_did = services
+        .issuer
+        .identifier(&default_did_method.to_string(), get_preferred_signing_algorithm())
+        .await
+        .map_err(|e| StatusListSigningError(e.to_string()))?;
+
+    #[cfg(feature = "test_utils")]
+    let iat = 0;
+    #[cfg(not(feature = "test_utils"))]
+    let iat = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    let status_list_credential_url = status_list_credential_url(&services.url, status_list, status_purpose);
+
+    let status_list_credential = json!({
+        "@context": ["https://www.w3.org/ns/credentials/v2"],
+        "id": status_list_credential_url,
+        "type": ["VerifiableCredential", "BitstringStatusListCredential"],
+        "issuer": issuer_did,
+        "credentialSubject": {
+            "id": format!("{status_list_credential_url}#list"),
+            "type": "BitstringStatusList",
+            "statusPurpose": status_purpose,
+            "encodedList": encode_list(entries),
+        }
+    });
+
+    jwt::encode(
+        services.issuer.clone(),
+        Header::new(get_preferred_signing_algorithm()),
+        VerifiableCredentialJwt::builder()
+            .iss(issuer_did)
+            .iat(iat)
+            .verifiable_credential(status_list_credential)
+            .build()
+            .map_err(|e| StatusListSigningError(e.to_string()))?,
+        &default_did_method.to_string(),
+    )
+    .await
+    .map_err(|e| StatusListSigningError(e.to_string()))
+}
+
+#[cfg(test)]
+pub mod status_list_tests {
+    use super::*;
+    use agent_secret_manager::service::Service;
+    use cqrs_es::test::TestFramework;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    type StatusListTestFramework = TestFramework<StatusList>;
+
+    fn decode_list(encoded_list: &str) -> Vec<u8> {
+        let compressed = URL_SAFE_NO_PAD.decode(encoded_list.strip_prefix('u').unwrap()).unwrap();
+        let mut bitstring = vec![];
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut bitstring)
+            .unwrap();
+        bitstring
+    }
+
+    #[test]
+    fn test_encode_list() {
+        let bitstring = decode_list(&encode_list(&BTreeSet::from_iter([0, 9, STATUS_LIST_LENGTH - 1])));
+
+        assert_eq!(bitstring.len(), STATUS_LIST_LENGTH / 8);
+        assert_eq!(bitstring[0], 0b1000_0000);
+        assert_eq!(bitstring[1], 0b0100_0000);
+        assert_eq!(bitstring[STATUS_LIST_LENGTH / 8 - 1], 0b0000_0001);
+        assert!(bitstring[2..STATUS_LIST_LENGTH / 8 - 1].iter().all(|byte| *byte == 0));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_allocate_status_list_index() {
+        let status_list = StatusList::default();
+
+        let events = status_list
+            .handle(
+                StatusListCommand::AllocateStatusListIndex {
+                    credential_id: "credential-1".to_string(),
+                },
+                &Service::default(),
+            )
+            .await
+            .unwrap();
+
+        // The first allocation also publishes both status lists.
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            StatusListEvent::StatusListIndexAllocated {
+                credential_id: "credential-1".to_string(),
+                status_list_index: 0,
+            }
+        );
+        assert!(matches!(
+            &events[1],
+            StatusListEvent::StatusListCredentialSigned {
+                status_purpose: StatusPurpose::Revocation,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &events[2],
+            StatusListEvent::StatusListCredentialSigned {
+                status_purpose: StatusPurpose::Suspension,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_allocate_status_list_index_in_new_status_list() {
+        let mut status_list = StatusList::default();
+        for status_list_index in 0..STATUS_LIST_LENGTH {
+            status_list.apply(StatusListEvent::StatusListIndexAllocated {
+                credential_id: format!("credential-{status_list_index}"),
+                status_list_index,
+            });
+        }
+
+        let events = status_list
+            .handle(
+                StatusListCommand::AllocateStatusListIndex {
+                    credential_id: "credential-new".to_string(),
+                },
+                &Service::default(),
+            )
+            .await
+            .unwrap();
+
+        // A full status list is not an error, a new status list is started and published instead.
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            StatusListEvent::StatusListIndexAllocated {
+                credential_id: "credential-new".to_string(),
+                status_list_index: STATUS_LIST_LENGTH,
+            }
+        );
+        assert!(matches!(
+            &events[1],
+            StatusListEvent::StatusListCredentialSigned {
+                status_purpose: StatusPurpose::Revocation,
+                status_list: 1,
+                ..
+            }
+        ));
+
+        let credential_status = credential_status("https://example.com/", STATUS_LIST_LENGTH);
+        assert_eq!(credential_status[0]["statusListIndex"], "0");
+        assert!(credential_status[0]["statusListCredential"]
+            .as_str()
+            .unwrap()
+            .ends_with("/status-lists/revocation/1"));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_release_status_list_index() {
+        StatusListTestFramework::with(Service::default())
+            .given(vec![StatusListEvent::StatusListIndexAllocated {
+                credential_id: "credential-1".to_string(),
+                status_list_index: 9,
+            }])
+            .when(StatusListCommand::ReleaseStatusListIndex {
+                credential_id: "credential-1".to_string(),
+            })
+            .then_expect_events(vec![StatusListEvent::StatusListIndexReleased {
+                credential_id: "credential-1".to_string(),
+                status_list_index: 9,
+            }]);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_release_status_list_index_of_suspended_credential() {
+        StatusListTestFramework::with(Service::default())
+            .given(vec![
+                StatusListEvent::StatusListIndexAllocated {
+                    credential_id: "credential-1".to_string(),
+                    status_list_index: 9,
+                },
+                StatusListEvent::CredentialStatusUpdated {
+                    credential_id: "credential-1".to_string(),
+                    status_list_index: 9,
+                    status_purpose: StatusPurpose::Suspension,
+                    status: true,
+                },
+            ])
+            .when(StatusListCommand::ReleaseStatusListIndex {
+                credential_id: "credential-1".to_string(),
+            })
+            .then_expect_error_message(
+                "The status list index of the credential with id `credential-1` cannot be released, its status has been set",
+            );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_allocate_status_list_index_for_duplicate_credential() {
+        StatusListTestFramework::with(Service::default())
+            .given(vec![StatusListEvent::StatusListIndexAllocated {
+                credential_id: "credential-1".to_string(),
+                status_list_index: 0,
+            }])
+            .when(StatusListCommand::AllocateStatusListIndex {
+                credential_id: "credential-1".to_string(),
+            })
+            .then_expect_error_message(
+                "A status list index has already been allocated for the credential with id `credential-1`",
+            );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_update_status_of_unknown_credential() {
+        StatusListTestFramework::with(Service::default())
+            .given_no_previous_events()
+            .when(StatusListCommand::UpdateCredentialStatus {
+                credential_id: "credential-1".to_string(),
+                status_purpose: StatusPurpose::Revocation,
+                status: true,
+            })
+            .then_expect_error_message(
+                "No status list index has been allocated for the credential with id `credential-1`",
+            );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_revocation_is_permanent() {
+        StatusListTestFramework::with(Service::default())
+            .given(vec![
+                StatusListEvent::StatusListIndexAllocated {
+                    credential_id: "credential-1".to_string(),
+                    status_list_index: 0,
+                },
+                StatusListEvent::CredentialStatusUpdated {
+                    credential_id: "credential-1".to_string(),
+                    status_list_index: 0,
+                    status_purpose: StatusPurpose::Revocation,
+                    status: true,
+                },
+            ])
+            .when(StatusListCommand::UpdateCredentialStatus {
+                credential_id: "credential-1".to_string(),
+                status_purpose: StatusPurpose::Revocation,
+                status: false,
+            })
+            .then_expect_error_message("The credential with id `credential-1` is revoked, revocation is permanent");
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_suspend_credential() {
+        let mut status_list = StatusList::default();
+        status_list.apply(StatusListEvent::StatusListIndexAllocated {
+            credential_id: "credential-1".to_string(),
+            status_list_index: 9,
+        });
+
+        let events = status_list
+            .handle(
+                StatusListCommand::UpdateCredentialStatus {
+                    credential_id: "credential-1".to_string(),
+                    status_purpose: StatusPurpose::Suspension,
+                    status: true,
+                },
+                &Service::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0],
+            StatusListEvent::CredentialStatusUpdated {
+                credential_id: "credential-1".to_string(),
+                status_list_index: 9,
+                status_purpose: StatusPurpose::Suspension,
+                status: true,
+            }
+        );
+
+        let StatusListEvent::StatusListCredentialSigned {
+            status_purpose: StatusPurpose::Suspension,
+            status_list_credential,
+        } = &events[1]
+        else {
+            panic!("Expected a `StatusListCredentialSigned` event");
+        };
+
+        let payload = status_list_credential.split('.').nth(1).unwrap();
+        let claims: serde_json::Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap();
+        let encoded_list = claims["vc"]["credentialSubject"]["encodedList"].as_str().unwrap();
+
+        assert_eq!(decode_list(encoded_list)[1], 0b0100_0000);
+    }
+}
@@ -0,0 +1,20 @@
+use serde::Deserialize;
+
+use super::aggregate::StatusPurpose;
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StatusListCommand {
+    UpdateCredentialStatus {
+        credential_id: String,
+        status_purpose: StatusPurpose,
+        status: bool,
+    },
+    AllocateStatusListIndex {
+        credential_id: String,
+    },
+    /// Releases the status list index of a credential that could not be created, so that it can be allocated again.
+    ReleaseStatusListIndex {
+        credential_id: String,
+    },
+}
@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum StatusListError {
+    #[error("A status list index has already been allocated for the credential with id `{0}`")]
+    DuplicateCredentialError(String),
+
+    #[error("No status list index has been allocated for the credential with id `{0}`")]
+    UnknownCredentialError(String),
+
+    #[error("The status list index of the credential with id `{0}` cannot be released, its status has been set")]
+    CredentialStatusSetError(String),
+
+    #[error("The credential with id `{0}` is revoked, revocation is permanent")]
+    PermanentRevocationError(String),
+
+    #[error("Could not sign the status list credential: {0}")]
+    StatusListSigningError(String),
+}
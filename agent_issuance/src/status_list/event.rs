@@ -0,0 +1,46 @@
+use cqrs_es::DomainEvent;
+use serde::{Deserialize, Serialize};
+
+use super::aggregate::StatusPurpose;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum StatusListEvent {
+    StatusListIndexAllocated {
+        credential_id: String,
+        status_list_index: usize,
+    },
+    StatusListIndexReleased {
+        credential_id: String,
+        status_list_index: usize,
+    },
+    CredentialStatusUpdated {
+        credential_id: String,
+        status_list_index: usize,
+        status_purpose: StatusPurpose,
+        status: bool,
+    },
+    StatusListCredentialSigned {
+        status_purpose: StatusPurpose,
+        #[serde(default)]
+        status_list: usize,
+        status_list_credential: String,
+    },
+}
+
+impl DomainEvent for StatusListEvent {
+    fn event_type(&self) -> String {
+        use StatusListEvent::*;
+
+        let event_type: &str = match self {
+            StatusListIndexAllocated { .. } => "StatusListIndexAllocated",
+            StatusListIndexReleased { .. } => "StatusListIndexReleased",
+            CredentialStatusUpdated { .. } => "CredentialStatusUpdated",
+            StatusListCredentialSigned { .. } => "StatusListCredentialSigned",
+        };
+        event_type.to_string()
+    }
+
+    fn event_version(&self) -> String {
+        "1".to_string()
+    }
+}
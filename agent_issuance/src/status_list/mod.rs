@@ -0,0 +1,5 @@
+pub mod aggregate;
+pub mod command;
+pub mod error;
+pub mod event;
+pub mod queries;
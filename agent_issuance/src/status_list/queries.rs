@@ -0,0 +1,45 @@
+use cqrs_es::{EventEnvelope, View};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{
+    aggregate::{status_list_path, StatusList},
+    event::StatusListEvent,
+};
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct StatusListView {
+    pub status_list_indices: HashMap<String, usize>,
+    /// The signed status list credentials by their path, see [`status_list_path`].
+    pub status_list_credentials: HashMap<String, String>,
+}
+
+impl View<StatusList> for StatusListView {
+    fn update(&mut self, event: &EventEnvelope<StatusList>) {
+        use StatusListEvent::*;
+
+        match &event.payload {
+            StatusListIndexAllocated {
+                credential_id,
+                status_list_index,
+            } => {
+                self.status_list_indices
+                    .insert(credential_id.clone(), *status_list_index);
+            }
+            StatusListIndexReleased { credential_id, .. } => {
+                self.status_list_indices.remove(credential_id);
+            }
+            CredentialStatusUpdated { .. } => {}
+            StatusListCredentialSigned {
+                status_purpose,
+                status_list,
+                status_list_credential,
+            } => {
+                self.status_list_credentials.insert(
+                    status_list_path(*status_list, *status_purpose),
+                    status_list_credential.clone(),
+                );
+            }
+        }
+    }
+}
@@ -7,6 +7,15 @@ pub mod subject;
 
 // TODO: find better solution for this
 pub async fn secret_manager() -> SecretManager {
+    let secret_manager_config = config().secret_manager.clone();
+
+    secret_manager_from_config(secret_manager_config).await
+}
+
+/// Builds a `SecretManager` from the given `secret_manager_config`, e.g. the one of a tenant.
+pub async fn secret_manager_from_config(secret_manager_config: SecretManagerConfig) -> SecretManager {
+    info!("{:?}", secret_manager_config);
+
     let SecretManagerConfig {
         stronghold_path: snapshot_path,
         stronghold_password: password,
@@ -14,9 +23,8 @@ pub async fn secret_manager() -> SecretManager {
         issuer_es256_key_id,
         issuer_did,
         issuer_fragment,
-    } = config().secret_manager.clone();
-
-    info!("{:?}", config().secret_manager);
+        ..
+    } = secret_manager_config;
 
     let mut builder = SecretManager::builder()
         .snapshot_path(&snapshot_path)
@@ -1,15 +1,17 @@
 use std::sync::Arc;
 
+use crate::subject::Subject;
+
 /// Convenience trait for Services like `IssuanceServices`, `HolderServices`, and `VerifierServices`.
 pub trait Service {
-    fn new(subject: Arc<dyn oid4vc_core::Subject>) -> Self;
+    fn new(subject: Arc<Subject>) -> Self;
 
     #[cfg(feature = "test_utils")]
     fn default() -> Arc<Self>
     where
         Self: Sized,
     {
-        use crate::{secret_manager, subject::Subject};
+        use crate::secret_manager;
 
         Arc::new(Self::new(Arc::new(futures::executor::block_on(async {
             Subject {
@@ -13,21 +13,66 @@ pub struct Subject {
     pub secret_manager: Arc<Mutex<SecretManager>>,
 }
 
+impl Subject {
+    /// Signs the raw `message` bytes with the key that belongs to the given `algorithm`. Unlike [`Sign::sign`], the
+    /// message does not need to be valid UTF-8, which is required for signing CBOR structures such as COSE_Sign1.
+    pub async fn sign_bytes(&self, message: &[u8], algorithm: Algorithm) -> anyhow::Result<Vec<u8>> {
+        let secret_manager = self.secret_manager.lock().await;
+
+        Ok(secret_manager
+            .sign(message, from_jsonwebtoken_algorithm_to_jwsalgorithm(&algorithm))
+            .await?)
+    }
+
+    /// Returns the id of the verification method of the key that belongs to the given `algorithm`. Unlike
+    /// [`Sign::key_id`], which always returns the key of the preferred signing algorithm, this can be used when the
+    /// signature is created with a specific key, such as the EdDSA key for Data Integrity proofs.
+    pub async fn verification_method(&self, subject_syntax_type: &str, algorithm: Algorithm) -> Option<String> {
+        let method: DidMethod = serde_json::from_str(&format!("{subject_syntax_type:?}")).ok()?;
+
+        let mut secret_manager = self.secret_manager.lock().await;
+        if method == DidMethod::Web {
+            return secret_manager
+                .produce_document(
+                    method,
+                    Some(did_manager::MethodSpecificParameters::Web { origin: origin() }),
+                    from_jsonwebtoken_algorithm_to_jwsalgorithm(&algorithm),
+                )
+                .await
+                .ok()
+                .and_then(|document| document.verification_method().first().cloned())
+                .map(|first| first.id().to_string());
+        }
+
+        // TODO: refactor: https://github.com/impierce/ssi-agent/pull/31#discussion_r1634590990
+
+        secret_manager
+            .produce_document(method, None, from_jsonwebtoken_algorithm_to_jwsalgorithm(&algorithm))
+            .await
+            .ok()
+            .and_then(|document| document.verification_method().first().cloned())
+            .map(|first| first.id().to_string())
+    }
+}
+
 #[async_trait]
 impl Verify for Subject {
     async fn public_key(&self, did_url: &str) -> anyhow::Result<Vec<u8>> {
-        let did_url = identity_iota::did::DIDUrl::parse(did_url).unwrap();
+        let did_url = identity_iota::did::DIDUrl::parse(did_url)?;
 
         let resolver = Resolver::new().await;
 
-        let document = resolver.resolve(did_url.did().as_str()).await.unwrap();
+        let document = resolver
+            .resolve(did_url.did().as_str())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to resolve DID: {e:?}"))?;
 
         let verification_method = document
             .resolve_method(
                 DIDUrlQuery::from(&did_url),
                 Some(identity_iota::verification::MethodScope::VerificationMethod),
             )
-            .unwrap();
+            .ok_or(anyhow::anyhow!("No verification method found for DID URL: {}", did_url))?;
 
         // Try decode from `MethodData` directly, else use public JWK params.
         verification_method.data().try_decode().or_else(|_| {
@@ -61,36 +106,11 @@ impl Verify for Subject {
 #[async_trait]
 impl Sign for Subject {
     async fn key_id(&self, subject_syntax_type: &str, _algorithm: Algorithm) -> Option<String> {
-        let method: DidMethod = serde_json::from_str(&format!("{subject_syntax_type:?}")).ok()?;
-
-        let mut secret_manager = self.secret_manager.lock().await;
-        if method == DidMethod::Web {
-            return secret_manager
-                .produce_document(
-                    method,
-                    Some(did_manager::MethodSpecificParameters::Web { origin: origin() }),
-                    from_jsonwebtoken_algorithm_to_jwsalgorithm(
-                        &agent_shared::config::get_preferred_signing_algorithm(),
-                    ),
-                )
-                .await
-                .ok()
-                .and_then(|document| document.verification_method().first().cloned())
-                .map(|first| first.id().to_string());
-        }
-
-        // TODO: refactor: https://github.com/impierce/ssi-agent/pull/31#discussion_r1634590990
-
-        secret_manager
-            .produce_document(
-                method,
-                None,
-                from_jsonwebtoken_algorithm_to_jwsalgorithm(&agent_shared::config::get_preferred_signing_algorithm()),
-            )
-            .await
-            .ok()
-            .and_then(|document| document.verification_method().first().cloned())
-            .map(|first| first.id().to_string())
+        self.verification_method(
+            subject_syntax_type,
+            agent_shared::config::get_preferred_signing_algorithm(),
+        )
+        .await
     }
 
     async fn sign(&self, message: &str, _subject_syntax_type: &str, _algorithm: Algorithm) -> anyhow::Result<Vec<u8>> {
@@ -161,6 +181,7 @@ mod tests {
             issuer_es256_key_id: Some("es256-0".to_string()),
             issuer_did: Some("did:foo:bar".to_string()),
             issuer_fragment: Some("0".to_string()),
+            issuer_certificate_chain_path: None,
         };
     }
 
@@ -7,7 +7,7 @@ use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use serde_with::{skip_serializing_none, SerializeDisplay};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     sync::{RwLock, RwLockReadGuard},
 };
 use strum::VariantArray;
@@ -23,6 +23,20 @@ pub struct ApplicationConfiguration {
     pub cors_enabled: Option<bool>,
     pub did_methods: HashMap<SupportedDidMethod, ToggleOptions>,
     pub external_server_response_timeout_ms: Option<u64>,
+    /// The number of seconds during which a Pre-Authorized Code can be redeemed at the Token Endpoint.
+    pub pre_authorized_code_expires_in: Option<u64>,
+    /// The number of seconds during which an Access Token can be used before it must be refreshed.
+    pub access_token_expires_in: Option<u64>,
+    /// The number of seconds during which a Refresh Token can be used to obtain a new Access Token.
+    pub refresh_token_expires_in: Option<u64>,
+    /// The number of seconds during which a Credential Offer can be redeemed before it expires.
+    pub offer_expires_in: Option<u64>,
+    /// When `true`, Access Tokens can be bound to a key using DPoP proofs (RFC 9449).
+    #[serde(default)]
+    pub dpop_enabled: bool,
+    /// The Wallets that can obtain an Authorization Code at the Authorization Endpoint.
+    #[serde(default)]
+    pub authorization_clients: Vec<AuthorizationClient>,
     pub domain_linkage_enabled: bool,
     pub secret_manager: SecretManagerConfig,
     pub did_document_cache: Option<InMemoryCacheConfig>,
@@ -31,6 +45,9 @@ pub struct ApplicationConfiguration {
     pub display: Vec<Display>,
     pub event_publishers: Option<EventPublishers>,
     pub vp_formats: HashMap<ClaimFormatDesignation, ToggleOptions>,
+    /// Additional identities that are served by the same deployment next to the default one.
+    #[serde(default)]
+    pub tenants: Vec<TenantConfiguration>,
 }
 
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -69,6 +86,115 @@ pub struct SecretManagerConfig {
     pub issuer_es256_key_id: Option<String>,
     pub issuer_did: Option<String>,
     pub issuer_fragment: Option<String>,
+    /// Path to the PEM encoded X.509 certificate chain of the ES256 key, starting with the certificate of the key
+    /// itself. Required to issue `mso_mdoc` Credentials.
+    pub issuer_certificate_chain_path: Option<String>,
+}
+
+/// A tenant is an issuer, holder and verifier with its own keys, DIDs, display metadata, Credential Configurations and
+/// event publishers. The DID methods, signing algorithms and VP formats are shared with the default tenant.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TenantConfiguration {
+    /// Identifies the tenant. When the `postgres` event store is used, the events and views of the tenant are stored
+    /// in the database schema with this name.
+    pub tenant_id: String,
+    /// The public URL of the tenant, including its `base_path` when it is routed by path prefix.
+    pub url: String,
+    /// Routes the requests with this `Host` header to the tenant.
+    pub host: Option<String>,
+    /// Routes the requests with this path prefix to the tenant, e.g. `acme` for `https://ssi-agent.example.org/acme`.
+    pub base_path: Option<String>,
+    pub secret_manager: SecretManagerConfig,
+    #[serde(default)]
+    pub display: Vec<Display>,
+    #[serde(default)]
+    pub credential_configurations: Vec<CredentialConfiguration>,
+    pub event_publishers: Option<EventPublishers>,
+}
+
+/// Determines which requests are routed to a tenant.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TenantRoute {
+    /// The requests with this `Host` header.
+    Host(String),
+    /// The requests with this path prefix.
+    BasePath(String),
+}
+
+impl TenantConfiguration {
+    /// Returns how requests are routed to the tenant. Exactly one of `host` and `base_path` must be set.
+    pub fn route(&self) -> Result<TenantRoute, ConfigError> {
+        match (&self.host, &self.base_path) {
+            (Some(host), None) => Ok(TenantRoute::Host(host.clone())),
+            (None, Some(base_path)) if base_path.trim_matches('/').is_empty() => Err(ConfigError::Message(format!(
+                "The `base_path` of tenant `{}` cannot be empty",
+                self.tenant_id
+            ))),
+            (None, Some(base_path)) => Ok(TenantRoute::BasePath(base_path.clone())),
+            _ => Err(ConfigError::Message(format!(
+                "Tenant `{}` must be routed by either `host` or `base_path`",
+                self.tenant_id
+            ))),
+        }
+    }
+}
+
+/// Returns `true` when the `tenant_id` can be used as the name of the database schema of the tenant.
+pub fn is_valid_tenant_id(tenant_id: &str) -> bool {
+    !tenant_id.is_empty()
+        && tenant_id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Rejects tenants that would otherwise only fail (or silently shadow each other) once their state and routes are built.
+fn validate_tenants(tenants: &[TenantConfiguration]) -> Result<(), ConfigError> {
+    let mut tenant_ids = HashSet::new();
+    let mut hosts = HashSet::new();
+    let mut base_paths = HashSet::new();
+
+    for tenant in tenants {
+        let tenant_id = &tenant.tenant_id;
+
+        if !is_valid_tenant_id(tenant_id) {
+            return Err(ConfigError::Message(format!(
+                "The `tenant_id` `{tenant_id}` can only contain lowercase letters, digits and underscores"
+            )));
+        }
+
+        if !tenant_ids.insert(tenant_id) {
+            return Err(ConfigError::Message(format!(
+                "The `tenant_id` `{tenant_id}` is used by more than one tenant"
+            )));
+        }
+
+        Url::parse(&tenant.url)
+            .map_err(|e| ConfigError::Message(format!("The `url` of tenant `{tenant_id}` is invalid: {e}")))?;
+
+        match tenant.route()? {
+            TenantRoute::Host(host) if !hosts.insert(host.to_lowercase()) => {
+                return Err(ConfigError::Message(format!(
+                    "The `host` `{host}` is used by more than one tenant"
+                )));
+            }
+            TenantRoute::BasePath(base_path) if !base_paths.insert(base_path.trim_matches('/').to_string()) => {
+                return Err(ConfigError::Message(format!(
+                    "The `base_path` `{base_path}` is used by more than one tenant"
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// A Wallet that is registered for the Authorization Code Flow. The Authorization Code is only ever sent to one of its
+/// `redirect_uris`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AuthorizationClient {
+    pub client_id: String,
+    pub redirect_uris: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -78,13 +204,89 @@ pub struct InMemoryCacheConfig {
     pub ttl: Option<u64>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct CredentialConfiguration {
     pub credential_configuration_id: String,
     #[serde(flatten)]
     pub credential_format_with_parameters: CredentialFormats<WithParameters>,
     #[serde(default)]
     pub display: Vec<serde_json::Value>,
+    /// The claims that are issued as Selectively Disclosable claims. Only applies to `vc+sd-jwt` Credentials.
+    #[serde(default)]
+    pub selectively_disclosable_claims: Vec<String>,
+    /// The Data Integrity proof suite that is used to sign the Credentials. Only applies to `ldp_vc` Credentials.
+    pub proof_suite: Option<ProofSuite>,
+    /// The default number of seconds during which the Credentials are valid. When not set, the Credentials do not
+    /// expire.
+    pub validity_period: Option<u64>,
+    /// The JSON Schema against which the `credentialSubject` of the Credentials is validated before they are issued.
+    pub credential_schema: Option<CredentialSchema>,
+    /// Templates that map flat input records onto the Credentials of this Credential Configuration.
+    #[serde(default)]
+    pub templates: Vec<CredentialTemplate>,
+}
+
+/// A JSON Schema that is referenced by the Credentials as their `credentialSchema`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct CredentialSchema {
+    /// The URL at which the JSON Schema is published.
+    pub id: Url,
+    /// The JSON Schema itself, so that the `credentialSubject` can be validated without resolving the `id`.
+    pub schema: serde_json::Value,
+}
+
+/// Describes how the fields of a flat input record are mapped onto a Credential.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct CredentialTemplate {
+    pub template_id: String,
+    /// The claims of the `credentialSubject`. Nested claims are addressed with dot-separated keys, e.g. `degree.name`.
+    /// Keys that contain dots themselves are enclosed in square brackets, e.g. `[org.iso.18013.5.1].family_name`.
+    #[serde(default)]
+    pub credential_subject: BTreeMap<String, ClaimMapping>,
+    /// The top-level properties of the Credential, e.g. `name` or `description`. Only applies to `jwt_vc_json` and
+    /// `ldp_vc` Credentials.
+    #[serde(default)]
+    pub properties: BTreeMap<String, ClaimMapping>,
+}
+
+/// The source of a single claim in a `CredentialTemplate`.
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+pub struct ClaimMapping {
+    /// The (dot-separated) field of the input record that the claim is taken from.
+    pub field: Option<String>,
+    /// A constant value. Takes precedence over the `field`.
+    pub value: Option<serde_json::Value>,
+    /// The value that is used when the `field` is missing from the input record.
+    pub default: Option<serde_json::Value>,
+    /// When `true`, the claim is omitted if the `field` is missing and there is no `default`.
+    #[serde(default)]
+    pub optional: bool,
+    pub transform: Option<Transform>,
+}
+
+/// A transformation that is applied to the value of a claim.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Transform {
+    Uppercase,
+    Lowercase,
+    Trim,
+    String,
+    Number,
+    Boolean,
+}
+
+/// Supported Data Integrity proof suites for `ldp_vc` Credentials.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, strum::Display, strum::EnumString)]
+pub enum ProofSuite {
+    #[default]
+    #[serde(rename = "eddsa-rdfc-2022")]
+    #[strum(serialize = "eddsa-rdfc-2022")]
+    EddsaRdfc2022,
+    #[serde(rename = "Ed25519Signature2020")]
+    #[strum(serialize = "Ed25519Signature2020")]
+    Ed25519Signature2020,
 }
 
 #[skip_serializing_none]
@@ -136,6 +338,9 @@ pub struct Events {
 pub enum ServerConfigEvent {
     ServerMetadataInitialized,
     CredentialConfigurationAdded,
+    CredentialConfigurationUpdated,
+    CredentialConfigurationRemoved,
+    ServerMetadataSigned,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, strum::Display)]
@@ -150,14 +355,30 @@ pub enum OfferEvent {
     CredentialOfferCreated,
     CredentialsAdded,
     FormUrlEncodedCredentialOfferCreated,
+    TransactionCodeCreated,
+    TransactionCodeVerificationFailed,
+    TransactionCodeAttemptRegistered,
+    AuthorizationCodeCreated,
+    AuthorizationCodeRedeemed,
+    PreAuthorizedCodeRedeemed,
     TokenResponseCreated,
+    AccessTokenRevoked,
+    NonceCreated,
     CredentialRequestVerified,
     CredentialResponseCreated,
+    DeferredCredentialResponseCreated,
+    BatchCredentialResponseCreated,
+    TransactionIdRedeemed,
+    CredentialAccepted,
+    CredentialFailure,
+    CredentialDeleted,
+    OfferCancelled,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, strum::Display)]
 pub enum HolderCredentialEvent {
     CredentialAdded,
+    CredentialDeleted,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, strum::Display)]
@@ -166,7 +387,10 @@ pub enum ReceivedOfferEvent {
     CredentialOfferAccepted,
     TokenResponseReceived,
     CredentialResponseReceived,
+    CredentialIssuanceDeferred,
+    DeferredCredentialReceived,
     CredentialOfferRejected,
+    CredentialNotificationSent,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, strum::Display)]
@@ -265,7 +489,22 @@ impl ApplicationConfiguration {
                 .build()?
         };
 
-        config.try_deserialize().inspect(|config: &ApplicationConfiguration| {
+        let config: ApplicationConfiguration = config.try_deserialize()?;
+
+        if config
+            .base_path
+            .as_ref()
+            .is_some_and(|base_path| base_path.trim_matches('/').is_empty())
+        {
+            return Err(ConfigError::Message(
+                "`base_path` cannot be empty, remove or set a path".to_string(),
+            ));
+        }
+
+        // Invalid tenants are rejected up front instead of when their state and routes are built.
+        validate_tenants(&config.tenants)?;
+
+        Ok(config).inspect(|config: &ApplicationConfiguration| {
             // TODO: this won't be logged either because `tracing_subscriber` is not initialized yet at this point. To
             // fix this we can consider obtaining the `log_format` from the config file prior to loading the complete
             // configuration.
@@ -359,6 +598,20 @@ pub fn get_preferred_did_method() -> SupportedDidMethod {
         .expect("Please set a DID method as `preferred` in the configuration")
 }
 
+/// Returns the enabled signing algorithms, sorted so that the result is deterministic.
+pub fn get_all_enabled_signing_algorithms() -> Vec<jsonwebtoken::Algorithm> {
+    let mut signing_algorithms: Vec<_> = config()
+        .signing_algorithms_supported
+        .iter()
+        .filter(|(_, v)| v.enabled)
+        .map(|(k, _)| *k)
+        .collect();
+
+    signing_algorithms.sort_by_key(|algorithm| format!("{algorithm:?}"));
+
+    signing_algorithms
+}
+
 pub fn get_preferred_signing_algorithm() -> jsonwebtoken::Algorithm {
     config()
         .signing_algorithms_supported
@@ -382,4 +635,66 @@ mod tests {
             let _subject_syntax_type: SubjectSyntaxType = variant.clone().into();
         }
     }
+
+    fn tenant(tenant_id: &str, url: &str, host: Option<&str>, base_path: Option<&str>) -> TenantConfiguration {
+        TenantConfiguration {
+            tenant_id: tenant_id.to_string(),
+            url: url.to_string(),
+            host: host.map(ToString::to_string),
+            base_path: base_path.map(ToString::to_string),
+            secret_manager: SecretManagerConfig {
+                stronghold_path: "tenant.stronghold".to_string(),
+                stronghold_password: "sup3rSecr3t".to_string(),
+                issuer_eddsa_key_id: None,
+                issuer_es256_key_id: None,
+                issuer_did: None,
+                issuer_fragment: None,
+                issuer_certificate_chain_path: None,
+            },
+            display: vec![],
+            credential_configurations: vec![],
+            event_publishers: None,
+        }
+    }
+
+    #[test]
+    fn valid_tenants_are_accepted() {
+        assert!(validate_tenants(&[
+            tenant("acme", "https://acme.example.org", Some("acme.example.org"), None),
+            tenant("globex_2", "https://ssi-agent.example.org/globex", None, Some("globex")),
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn invalid_tenants_are_rejected() {
+        for tenants in [
+            // The `base_path` cannot be empty.
+            vec![tenant("acme", "https://ssi-agent.example.org", None, Some("/"))],
+            // Either `host` or `base_path` must be set.
+            vec![tenant("acme", "https://ssi-agent.example.org", None, None)],
+            vec![tenant(
+                "Acme-1",
+                "https://acme.example.org",
+                Some("acme.example.org"),
+                None,
+            )],
+            vec![tenant("acme", "acme.example.org", Some("acme.example.org"), None)],
+            vec![
+                tenant("acme", "https://acme.example.org", Some("acme.example.org"), None),
+                tenant("acme", "https://ssi-agent.example.org/acme", None, Some("acme")),
+            ],
+            // Hosts are case-insensitive.
+            vec![
+                tenant("acme", "https://acme.example.org", Some("acme.example.org"), None),
+                tenant("globex", "https://acme.example.org", Some("ACME.example.org"), None),
+            ],
+            vec![
+                tenant("acme", "https://ssi-agent.example.org/acme", None, Some("acme")),
+                tenant("globex", "https://ssi-agent.example.org/acme", None, Some("/acme/")),
+            ],
+        ] {
+            assert!(validate_tenants(&tenants).is_err());
+        }
+    }
 }
@@ -0,0 +1,310 @@
+//! Demonstrating Proof of Possession (DPoP, RFC 9449). A DPoP proof is a JWT that is signed by the client for every
+//! request to the Token Endpoint and to the protected endpoints of the Credential Issuer. The Access Token is bound to
+//! the thumbprint of the public key in the proof, so that an intercepted Access Token cannot be replayed without the
+//! corresponding private key.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{jwk::Jwk, Algorithm, DecodingKey, Header, Validation};
+use oid4vc_core::{Sign, Verify};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+use url::Url;
+
+/// The name of the HTTP header that contains the DPoP proof. It is also used as the authentication scheme and as the
+/// `token_type` of DPoP-bound Access Tokens.
+pub const DPOP: &str = "DPoP";
+
+/// The `typ` header parameter of a DPoP proof.
+pub const DPOP_JWT_TYPE: &str = "dpop+jwt";
+
+/// The number of seconds a DPoP proof is accepted after its `iat`.
+pub const DPOP_PROOF_MAX_AGE: i64 = 60;
+
+/// The number of seconds the `iat` of a DPoP proof may lie in the future, to allow for clock skew of the client.
+pub const DPOP_PROOF_MAX_CLOCK_SKEW: i64 = 5;
+
+#[derive(Error, Debug)]
+pub enum DpopError {
+    #[error("The DPoP proof is invalid: {0}")]
+    InvalidProofError(String),
+    #[error("The DPoP proof could not be created: {0}")]
+    ProofCreationError(String),
+}
+
+/// The claims of a DPoP proof (RFC 9449, section 4.2).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DpopClaims {
+    pub jti: String,
+    pub htm: String,
+    pub htu: String,
+    pub iat: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ath: Option<String>,
+}
+
+/// Keeps track of the `jti` of the DPoP proofs that have been accepted, so that a proof cannot be replayed while its
+/// `iat` is still within the acceptable window.
+#[derive(Debug, Clone, Default)]
+pub struct JtiCache {
+    /// The `iat` of the accepted proofs, keyed by the thumbprint of their key and their `jti`.
+    jtis: Arc<Mutex<HashMap<(String, String), i64>>>,
+}
+
+impl JtiCache {
+    /// Registers the `jti` of a proof that is signed with the key with thumbprint `jkt`. Returns `false` when the `jti`
+    /// has been used before.
+    fn register(&self, jkt: &str, jti: &str, iat: i64) -> bool {
+        let mut jtis = self.jtis.lock().unwrap();
+
+        // Proofs outside the acceptable window are rejected anyway, so their `jti` no longer needs to be remembered.
+        let oldest_iat = now() - DPOP_PROOF_MAX_AGE;
+        jtis.retain(|_, iat| *iat >= oldest_iat);
+
+        jtis.insert((jkt.to_string(), jti.to_string()), iat).is_none()
+    }
+}
+
+/// Returns the value of the `ath` claim for the `access_token`: the base64url-encoded SHA-256 hash of the token.
+pub fn access_token_hash(access_token: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(access_token.as_bytes()))
+}
+
+/// Returns the JWK SHA-256 Thumbprint (RFC 7638) of the public key `jwk`. Only the required members of the key are
+/// hashed, in lexicographic order.
+pub fn jwk_thumbprint(jwk: &Jwk) -> Result<String, DpopError> {
+    let jwk = json!(jwk);
+
+    let members: &[&str] = match jwk["kty"].as_str() {
+        Some("EC") => &["crv", "kty", "x", "y"],
+        Some("OKP") => &["crv", "kty", "x"],
+        Some("RSA") => &["e", "kty", "n"],
+        _ => return Err(DpopError::InvalidProofError("unsupported key type".to_string())),
+    };
+
+    let members = members
+        .iter()
+        .map(|member| match &jwk[member] {
+            Value::String(value) => Ok(format!("{}:{}", json!(member), json!(value))),
+            _ => Err(DpopError::InvalidProofError(format!(
+                "the `jwk` is missing the `{member}` member"
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(URL_SAFE_NO_PAD.encode(Sha256::digest(format!("{{{}}}", members.join(",")).as_bytes())))
+}
+
+/// Converts the raw public key of a Holder into a public JWK. Supports Ed25519 keys and P-256 keys as an uncompressed
+/// point.
+pub fn public_jwk(public_key: &[u8]) -> Result<Jwk, DpopError> {
+    let jwk = match public_key {
+        x if x.len() == 32 => json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": URL_SAFE_NO_PAD.encode(x),
+        }),
+        [0x04, coordinates @ ..] if coordinates.len() == 64 => json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": URL_SAFE_NO_PAD.encode(&coordinates[..32]),
+            "y": URL_SAFE_NO_PAD.encode(&coordinates[32..]),
+        }),
+        _ => return Err(DpopError::ProofCreationError("unsupported key type".to_string())),
+    };
+
+    serde_json::from_value(jwk).map_err(|e| DpopError::ProofCreationError(e.to_string()))
+}
+
+/// Validates the DPoP `proof` of a request with the HTTP method `htm` to the public URL `htu` and returns the thumbprint
+/// of the key it was signed with. The `access_token` must be provided for requests to protected endpoints, in which
+/// case the proof must contain its hash in the `ath` claim. The `jti` of an accepted proof is registered in the
+/// `jti_cache`, so that the proof cannot be used again.
+pub fn verify_proof(
+    proof: &str,
+    algorithms: &[Algorithm],
+    htm: &str,
+    htu: &Url,
+    access_token: Option<&str>,
+    jti_cache: &JtiCache,
+) -> Result<String, DpopError> {
+    use DpopError::*;
+
+    let header = jsonwebtoken::decode_header(proof).map_err(|e| InvalidProofError(e.to_string()))?;
+
+    if header.typ.as_deref() != Some(DPOP_JWT_TYPE) {
+        return Err(InvalidProofError(format!("the `typ` must be `{DPOP_JWT_TYPE}`")));
+    }
+
+    if !algorithms.contains(&header.alg) {
+        return Err(InvalidProofError(format!("unsupported algorithm: {:?}", header.alg)));
+    }
+
+    let jwk = header
+        .jwk
+        .ok_or(InvalidProofError("the `jwk` header parameter is missing".to_string()))?;
+    let decoding_key = DecodingKey::from_jwk(&jwk).map_err(|e| InvalidProofError(e.to_string()))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.required_spec_claims = HashSet::new();
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+
+    let claims = jsonwebtoken::decode::<DpopClaims>(proof, &decoding_key, &validation)
+        .map_err(|e| InvalidProofError(e.to_string()))?
+        .claims;
+
+    if claims.jti.is_empty() {
+        return Err(InvalidProofError("the `jti` claim is empty".to_string()));
+    }
+
+    if !claims.htm.eq_ignore_ascii_case(htm) {
+        return Err(InvalidProofError(
+            "the `htm` claim does not match the HTTP method".to_string(),
+        ));
+    }
+
+    if without_query_and_fragment(&claims.htu) != without_query_and_fragment(htu.as_str()) {
+        return Err(InvalidProofError(
+            "the `htu` claim does not match the HTTP URI".to_string(),
+        ));
+    }
+
+    let now = now();
+    if claims.iat > now + DPOP_PROOF_MAX_CLOCK_SKEW || claims.iat < now - DPOP_PROOF_MAX_AGE {
+        return Err(InvalidProofError(
+            "the `iat` claim is outside the acceptable window".to_string(),
+        ));
+    }
+
+    if let Some(access_token) = access_token {
+        if claims.ath != Some(access_token_hash(access_token)) {
+            return Err(InvalidProofError(
+                "the `ath` claim does not match the Access Token".to_string(),
+            ));
+        }
+    }
+
+    let jkt = jwk_thumbprint(&jwk)?;
+
+    if !jti_cache.register(&jkt, &claims.jti, claims.iat) {
+        return Err(InvalidProofError("the `jti` claim has been used before".to_string()));
+    }
+
+    Ok(jkt)
+}
+
+/// Creates a DPoP proof for a request with the HTTP method `htm` to `htu`, signed by the `subject`. The `access_token`
+/// must be provided for requests to protected endpoints.
+pub async fn create_proof(
+    subject: Arc<dyn oid4vc_core::Subject>,
+    subject_syntax_type: &str,
+    algorithm: Algorithm,
+    htm: &str,
+    htu: &Url,
+    access_token: Option<&str>,
+) -> Result<String, DpopError> {
+    use DpopError::*;
+
+    let key_id = subject
+        .key_id(subject_syntax_type, algorithm)
+        .await
+        .ok_or(ProofCreationError("no key identifier found".to_string()))?;
+    let public_key = subject
+        .public_key(&key_id)
+        .await
+        .map_err(|e| ProofCreationError(e.to_string()))?;
+
+    let mut header = Header::new(algorithm);
+    header.typ = Some(DPOP_JWT_TYPE.to_string());
+    header.jwk = Some(public_jwk(&public_key)?);
+
+    let mut htu = htu.clone();
+    htu.set_query(None);
+    htu.set_fragment(None);
+
+    let claims = DpopClaims {
+        jti: crate::generate_random_string(),
+        htm: htm.to_string(),
+        htu: htu.to_string(),
+        iat: now(),
+        ath: access_token.map(access_token_hash),
+    };
+
+    oid4vc_core::jwt::encode(subject, header, claims, subject_syntax_type)
+        .await
+        .map_err(|e| ProofCreationError(e.to_string()))
+}
+
+/// Returns the normalized `uri` without its query and fragment, or `None` when it is not an absolute URI.
+fn without_query_and_fragment(uri: &str) -> Option<Url> {
+    let mut uri = Url::parse(uri).ok()?;
+    uri.set_query(None);
+    uri.set_fragment(None);
+    Some(uri)
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jwk_thumbprint_and_access_token_hash() {
+        // Example values taken from RFC 9449, sections 6.1 and 7.1.
+        let jwk: Jwk = serde_json::from_value(json!({
+            "kty": "EC",
+            "x": "l8tFrhx-34tV3hRICRDY9zCkDlpBhF42UQUfWVAWBFs",
+            "y": "9VE4jf_Ok_o64zbTTlcuNJajHmt6v9TDVrU0CdvGRDA",
+            "crv": "P-256"
+        }))
+        .unwrap();
+
+        assert_eq!(
+            jwk_thumbprint(&jwk).unwrap(),
+            "0ZcOCORZNYy-DWpqq30jZyJGHTN0d2HglBV3uiguA4I"
+        );
+        assert_eq!(
+            access_token_hash("Kz~8mXK1EalYznwH-LC-1fBAo.4Ljp~zsPE_NeO.gxU"),
+            "fUHyO2r2Z3DZ53EsNrWBb0xWXoaNy59IiKCAqksmQEo"
+        );
+    }
+
+    #[test]
+    fn test_without_query_and_fragment() {
+        assert_eq!(
+            without_query_and_fragment("https://EXAMPLE.com:443/auth/token?x=1#y"),
+            without_query_and_fragment("https://example.com/auth/token")
+        );
+        assert_ne!(
+            without_query_and_fragment("https://example.com/acme/auth/token"),
+            without_query_and_fragment("https://example.com/auth/token")
+        );
+        assert_eq!(without_query_and_fragment("/auth/token"), None);
+    }
+
+    #[test]
+    fn test_jti_cache() {
+        let jti_cache = JtiCache::default();
+
+        assert!(jti_cache.register("jkt", "jti", now()));
+        assert!(!jti_cache.register("jkt", "jti", now()));
+        assert!(jti_cache.register("other-jkt", "jti", now()));
+
+        // The `jti` of proofs outside the acceptable window is forgotten.
+        assert!(jti_cache.register("jkt", "expired-jti", now() - DPOP_PROOF_MAX_AGE - 1));
+        assert!(jti_cache.register("jkt", "expired-jti", now()));
+    }
+}
@@ -2,14 +2,16 @@ pub mod application_state;
 pub mod config;
 pub mod custom_queries;
 pub mod domain_linkage;
+pub mod dpop;
 pub mod error;
 pub mod generic_query;
 pub mod handlers;
+pub mod notification;
 pub mod url_utils;
 
 pub use ::config::ConfigError;
 use identity_iota::verification::jws::JwsAlgorithm;
-use rand::Rng;
+use rand::{distributions::Alphanumeric, Rng};
 pub use url_utils::UrlAppendHelpers;
 
 pub fn generate_random_string() -> String {
@@ -27,6 +29,18 @@ pub fn generate_random_string() -> String {
     random_string
 }
 
+/// Generates a random code of the given `length`, e.g. to be used as a Transaction Code (PIN). When `numeric` is
+/// `true` the code will only consist of digits, otherwise alphanumeric characters are used.
+pub fn generate_random_code(length: usize, numeric: bool) -> String {
+    let mut rng = rand::thread_rng();
+
+    if numeric {
+        (0..length).map(|_| char::from(b'0' + rng.gen_range(0..10))).collect()
+    } else {
+        (0..length).map(|_| char::from(rng.sample(Alphanumeric))).collect()
+    }
+}
+
 /// Helper function that converts `jsonwebtoken::Algorithm` to `JwsAlgorithm`.
 pub fn from_jsonwebtoken_algorithm_to_jwsalgorithm(algorithm: &jsonwebtoken::Algorithm) -> JwsAlgorithm {
     match algorithm {
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// The type of the event that a Wallet reports to the Notification Endpoint of the Credential Issuer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// The Credential was successfully stored in the Wallet.
+    CredentialAccepted,
+    /// The Credential could not be stored in the Wallet, e.g. because it was invalid.
+    CredentialFailure,
+    /// The Credential was deleted or rejected by the End-User.
+    CredentialDeleted,
+}
+
+/// The request that is sent by the Wallet to the Notification Endpoint of the Credential Issuer, see:
+/// https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#name-notification-request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NotificationRequest {
+    pub notification_id: String,
+    pub event: NotificationEvent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_description: Option<String>,
+}
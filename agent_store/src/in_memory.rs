@@ -5,11 +5,17 @@ use agent_issuance::{
         aggregate::Offer,
         queries::{
             access_token::{AccessTokenQuery, AccessTokenView},
+            authorization_code::{AuthorizationCodeQuery, AuthorizationCodeView},
+            credential_offer_uri::{CredentialOfferUriQuery, CredentialOfferUriView},
+            issuer_state::{IssuerStateQuery, IssuerStateView},
             pre_authorized_code::{PreAuthorizedCodeQuery, PreAuthorizedCodeView},
+            refresh_token::{RefreshTokenQuery, RefreshTokenView},
         },
+        tx_channel_registry::TxChannelRegistry,
     },
     services::IssuanceServices,
     state::{IssuanceState, ViewRepositories},
+    status_list::{aggregate::StatusList, queries::StatusListView},
     SimpleLoggingQuery,
 };
 use agent_shared::{application_state::Command, custom_queries::ListAllQuery, generic_query::generic_query};
@@ -123,14 +129,29 @@ pub async fn issuance_state(
     let server_config = Arc::new(MemRepository::default());
     let pre_authorized_code = Arc::new(MemRepository::<PreAuthorizedCodeView, Offer>::new());
     let access_token = Arc::new(MemRepository::<AccessTokenView, Offer>::new());
+    let issuer_state = Arc::new(MemRepository::<IssuerStateView, Offer>::new());
+    let authorization_code = Arc::new(MemRepository::<AuthorizationCodeView, Offer>::new());
+    let refresh_token = Arc::new(MemRepository::<RefreshTokenView, Offer>::new());
+    let credential_offer_uri = Arc::new(MemRepository::<CredentialOfferUriView, Offer>::new());
     let credential = Arc::new(MemRepository::default());
     let offer = Arc::new(MemRepository::default());
     let all_credentials = Arc::new(MemRepository::default());
     let all_offers = Arc::new(MemRepository::default());
+    let status_list = Arc::new(MemRepository::<StatusListView, StatusList>::new());
 
     // Create custom-queries for the offer aggregate.
     let pre_authorized_code_query = PreAuthorizedCodeQuery::new(pre_authorized_code.clone());
     let access_token_query = AccessTokenQuery::new(access_token.clone());
+    let issuer_state_query = IssuerStateQuery::new(issuer_state.clone());
+    let authorization_code_query = AuthorizationCodeQuery::new(authorization_code.clone());
+    let refresh_token_query = RefreshTokenQuery::new(refresh_token.clone());
+    let credential_offer_uri_query = CredentialOfferUriQuery::new(credential_offer_uri.clone());
+
+    // Used to notify request handlers that are waiting for credentials to be added to an offer.
+    let tx_channel_registry = TxChannelRegistry::default();
+
+    // Used to verify the `htu` of DPoP proofs.
+    let url = issuance_services.url.clone();
 
     let all_credentials_query = ListAllQuery::new(all_credentials.clone(), "all_credentials");
     let all_offers_query = ListAllQuery::new(all_offers.clone(), "all_offers");
@@ -143,7 +164,7 @@ pub async fn issuance_state(
         command: agent_issuance::state::CommandHandlers {
             server_config: Arc::new(
                 server_config_event_publishers.into_iter().fold(
-                    AggregateHandler::new(())
+                    AggregateHandler::new(issuance_services.clone())
                         .append_query(SimpleLoggingQuery {})
                         .append_query(generic_query(server_config.clone())),
                     |aggregate_handler, event_publisher| aggregate_handler.append_event_publisher(event_publisher),
@@ -160,25 +181,43 @@ pub async fn issuance_state(
             ),
             offer: Arc::new(
                 offer_event_publishers.into_iter().fold(
-                    AggregateHandler::new(issuance_services)
+                    AggregateHandler::new(issuance_services.clone())
                         .append_query(SimpleLoggingQuery {})
                         .append_query(generic_query(offer.clone()))
                         .append_query(all_offers_query)
                         .append_query(pre_authorized_code_query)
-                        .append_query(access_token_query),
+                        .append_query(access_token_query)
+                        .append_query(issuer_state_query)
+                        .append_query(authorization_code_query)
+                        .append_query(refresh_token_query)
+                        .append_query(credential_offer_uri_query)
+                        .append_query(tx_channel_registry.clone()),
                     |aggregate_handler, event_publisher| aggregate_handler.append_event_publisher(event_publisher),
                 ),
             ),
+            status_list: Arc::new(
+                AggregateHandler::new(issuance_services)
+                    .append_query(SimpleLoggingQuery {})
+                    .append_query(generic_query(status_list.clone())),
+            ),
         },
         query: ViewRepositories {
             server_config,
             pre_authorized_code,
             access_token,
+            issuer_state,
+            authorization_code,
+            refresh_token,
+            credential_offer_uri,
             credential,
             all_credentials,
             offer,
             all_offers,
+            status_list,
         },
+        tx_channel_registry,
+        url,
+        dpop_jti_cache: Default::default(),
     }
 }
 
@@ -1,20 +1,33 @@
 use crate::{partition_event_publishers, EventPublisher};
 use agent_holder::{services::HolderServices, state::HolderState};
 use agent_issuance::{
-    offer::queries::{access_token::AccessTokenQuery, pre_authorized_code::PreAuthorizedCodeQuery},
+    offer::{
+        queries::{
+            access_token::AccessTokenQuery, authorization_code::AuthorizationCodeQuery,
+            credential_offer_uri::CredentialOfferUriQuery, issuer_state::IssuerStateQuery,
+            pre_authorized_code::PreAuthorizedCodeQuery, refresh_token::RefreshTokenQuery,
+        },
+        tx_channel_registry::TxChannelRegistry,
+    },
     services::IssuanceServices,
     state::{CommandHandlers, IssuanceState, ViewRepositories},
     SimpleLoggingQuery,
 };
 use agent_shared::{
-    application_state::Command, config::config, custom_queries::ListAllQuery, generic_query::generic_query,
+    application_state::Command,
+    config::{config, is_valid_tenant_id},
+    custom_queries::ListAllQuery,
+    generic_query::generic_query,
 };
 use agent_verification::{services::VerificationServices, state::VerificationState};
 use async_trait::async_trait;
 use cqrs_es::{Aggregate, Query};
 use postgres_es::{default_postgress_pool, PostgresCqrs, PostgresViewRepository};
-use sqlx::{Pool, Postgres};
-use std::{collections::HashMap, sync::Arc};
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    Pool, Postgres,
+};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 struct AggregateHandler<A>
 where
@@ -65,27 +78,81 @@ where
     }
 }
 
+/// Connects to the event store. When a `tenant_id` is given, the `search_path` is set to the database schema of the
+/// tenant, which isolates its events and views from those of the other tenants.
+async fn pool(tenant_id: Option<&str>) -> Pool<Postgres> {
+    let connection_string = config().event_store.connection_string.clone().expect(
+        "Missing config parameter `event_store.connection_string` or `UNICORE__EVENT_STORE__CONNECTION_STRING`",
+    );
+
+    let Some(tenant_id) = tenant_id else {
+        return default_postgress_pool(&connection_string).await;
+    };
+
+    // Invalid `tenant_id`s are already rejected when the configuration is loaded.
+    if !is_valid_tenant_id(tenant_id) {
+        panic!("The `tenant_id` `{tenant_id}` can only contain lowercase letters, digits and underscores");
+    }
+
+    let connect_options = PgConnectOptions::from_str(&connection_string)
+        .expect("Invalid `event_store.connection_string`")
+        .options([("search_path", tenant_id)]);
+
+    PgPoolOptions::new()
+        .max_connections(10)
+        .connect_with(connect_options)
+        .await
+        .expect("unable to connect to database")
+}
+
 pub async fn issuance_state(
     issuance_services: Arc<IssuanceServices>,
     event_publishers: Vec<Box<dyn EventPublisher>>,
 ) -> IssuanceState {
-    let connection_string = config().event_store.connection_string.clone().expect(
-        "Missing config parameter `event_store.connection_string` or `UNICORE__EVENT_STORE__CONNECTION_STRING`",
-    );
-    let pool = default_postgress_pool(&connection_string).await;
+    issuance_state_with_pool(pool(None).await, issuance_services, event_publishers)
+}
+
+/// Returns the `IssuanceState` of a tenant, whose events and views are stored in the database schema `tenant_id`.
+pub async fn tenant_issuance_state(
+    issuance_services: Arc<IssuanceServices>,
+    event_publishers: Vec<Box<dyn EventPublisher>>,
+    tenant_id: &str,
+) -> IssuanceState {
+    issuance_state_with_pool(pool(Some(tenant_id)).await, issuance_services, event_publishers)
+}
 
+fn issuance_state_with_pool(
+    pool: Pool<Postgres>,
+    issuance_services: Arc<IssuanceServices>,
+    event_publishers: Vec<Box<dyn EventPublisher>>,
+) -> IssuanceState {
     // Initialize the postgres repositories.
     let server_config = Arc::new(PostgresViewRepository::new("server_config", pool.clone()));
     let pre_authorized_code = Arc::new(PostgresViewRepository::new("pre_authorized_code", pool.clone()));
     let access_token = Arc::new(PostgresViewRepository::new("access_token", pool.clone()));
+    let issuer_state = Arc::new(PostgresViewRepository::new("issuer_state", pool.clone()));
+    let authorization_code = Arc::new(PostgresViewRepository::new("authorization_code", pool.clone()));
+    let refresh_token = Arc::new(PostgresViewRepository::new("refresh_token", pool.clone()));
+    let credential_offer_uri = Arc::new(PostgresViewRepository::new("credential_offer_uri", pool.clone()));
     let credential = Arc::new(PostgresViewRepository::new("credential", pool.clone()));
     let all_credentials = Arc::new(PostgresViewRepository::new("all_credentials", pool.clone()));
     let offer = Arc::new(PostgresViewRepository::new("offer", pool.clone()));
     let all_offers = Arc::new(PostgresViewRepository::new("all_offers", pool.clone()));
+    let status_list = Arc::new(PostgresViewRepository::new("status_list", pool.clone()));
 
     // Create custom-queries for the offer aggregate.
     let pre_authorized_code_query = PreAuthorizedCodeQuery::new(pre_authorized_code.clone());
     let access_token_query = AccessTokenQuery::new(access_token.clone());
+    let issuer_state_query = IssuerStateQuery::new(issuer_state.clone());
+    let authorization_code_query = AuthorizationCodeQuery::new(authorization_code.clone());
+    let refresh_token_query = RefreshTokenQuery::new(refresh_token.clone());
+    let credential_offer_uri_query = CredentialOfferUriQuery::new(credential_offer_uri.clone());
+
+    // Used to notify request handlers that are waiting for credentials to be added to an offer.
+    let tx_channel_registry = TxChannelRegistry::default();
+
+    // Used to verify the `htu` of DPoP proofs.
+    let url = issuance_services.url.clone();
 
     // Partition the event_publishers into the different aggregates.
     let (server_config_event_publishers, credential_event_publishers, offer_event_publishers, _, _, _, _) =
@@ -99,7 +166,7 @@ pub async fn issuance_state(
         command: CommandHandlers {
             server_config: Arc::new(
                 server_config_event_publishers.into_iter().fold(
-                    AggregateHandler::new(pool.clone(), ())
+                    AggregateHandler::new(pool.clone(), issuance_services.clone())
                         .append_query(SimpleLoggingQuery {})
                         .append_query(generic_query(server_config.clone())),
                     |aggregate_handler, event_publisher| aggregate_handler.append_event_publisher(event_publisher),
@@ -116,25 +183,43 @@ pub async fn issuance_state(
             ),
             offer: Arc::new(
                 offer_event_publishers.into_iter().fold(
-                    AggregateHandler::new(pool.clone(), issuance_services)
+                    AggregateHandler::new(pool.clone(), issuance_services.clone())
                         .append_query(SimpleLoggingQuery {})
                         .append_query(generic_query(offer.clone()))
                         .append_query(all_offers_query)
                         .append_query(pre_authorized_code_query)
-                        .append_query(access_token_query),
+                        .append_query(access_token_query)
+                        .append_query(issuer_state_query)
+                        .append_query(authorization_code_query)
+                        .append_query(refresh_token_query)
+                        .append_query(credential_offer_uri_query)
+                        .append_query(tx_channel_registry.clone()),
                     |aggregate_handler, event_publisher| aggregate_handler.append_event_publisher(event_publisher),
                 ),
             ),
+            status_list: Arc::new(
+                AggregateHandler::new(pool.clone(), issuance_services)
+                    .append_query(SimpleLoggingQuery {})
+                    .append_query(generic_query(status_list.clone())),
+            ),
         },
         query: ViewRepositories {
             server_config,
             pre_authorized_code,
             access_token,
+            issuer_state,
+            authorization_code,
+            refresh_token,
+            credential_offer_uri,
             credential,
             all_credentials,
             offer,
             all_offers,
+            status_list,
         },
+        tx_channel_registry,
+        url,
+        dpop_jti_cache: Default::default(),
     }
 }
 
@@ -142,11 +227,23 @@ pub async fn holder_state(
     holder_services: Arc<HolderServices>,
     event_publishers: Vec<Box<dyn EventPublisher>>,
 ) -> HolderState {
-    let connection_string = config().event_store.connection_string.clone().expect(
-        "Missing config parameter `event_store.connection_string` or `UNICORE__EVENT_STORE__CONNECTION_STRING`",
-    );
-    let pool = default_postgress_pool(&connection_string).await;
+    holder_state_with_pool(pool(None).await, holder_services, event_publishers)
+}
 
+/// Returns the `HolderState` of a tenant, whose events and views are stored in the database schema `tenant_id`.
+pub async fn tenant_holder_state(
+    holder_services: Arc<HolderServices>,
+    event_publishers: Vec<Box<dyn EventPublisher>>,
+    tenant_id: &str,
+) -> HolderState {
+    holder_state_with_pool(pool(Some(tenant_id)).await, holder_services, event_publishers)
+}
+
+fn holder_state_with_pool(
+    pool: Pool<Postgres>,
+    holder_services: Arc<HolderServices>,
+    event_publishers: Vec<Box<dyn EventPublisher>>,
+) -> HolderState {
     // Initialize the postgres repositories.
     let holder_credential: Arc<PostgresViewRepository<_, _>> =
         Arc::new(PostgresViewRepository::new("holder_credential", pool.clone()));
@@ -197,11 +294,23 @@ pub async fn verification_state(
     verification_services: Arc<VerificationServices>,
     event_publishers: Vec<Box<dyn EventPublisher>>,
 ) -> VerificationState {
-    let connection_string = config().event_store.connection_string.clone().expect(
-        "Missing config parameter `event_store.connection_string` or `UNICORE__EVENT_STORE__CONNECTION_STRING`",
-    );
-    let pool = default_postgress_pool(&connection_string).await;
+    verification_state_with_pool(pool(None).await, verification_services, event_publishers)
+}
 
+/// Returns the `VerificationState` of a tenant, whose events and views are stored in the database schema `tenant_id`.
+pub async fn tenant_verification_state(
+    verification_services: Arc<VerificationServices>,
+    event_publishers: Vec<Box<dyn EventPublisher>>,
+    tenant_id: &str,
+) -> VerificationState {
+    verification_state_with_pool(pool(Some(tenant_id)).await, verification_services, event_publishers)
+}
+
+fn verification_state_with_pool(
+    pool: Pool<Postgres>,
+    verification_services: Arc<VerificationServices>,
+    event_publishers: Vec<Box<dyn EventPublisher>>,
+) -> VerificationState {
     // Initialize the postgres repositories.
     let authorization_request = Arc::new(PostgresViewRepository::new("authorization_request", pool.clone()));
     let connection = Arc::new(PostgresViewRepository::new("connection", pool.clone()));
@@ -3,7 +3,7 @@ use crate::{
     generic_oid4vc::{GenericAuthorizationRequest, OID4VPAuthorizationRequest, SIOPv2AuthorizationRequest},
     services::VerificationServices,
 };
-use agent_shared::config::{config, get_preferred_signing_algorithm};
+use agent_shared::config::get_preferred_signing_algorithm;
 use async_trait::async_trait;
 use cqrs_es::Aggregate;
 use oid4vc_core::{authorization_request::ByReference, scope::Scope};
@@ -50,7 +50,7 @@ impl Aggregate for AuthorizationRequest {
                     .await
                     .unwrap();
 
-                let url = &config().url;
+                let url = &services.url;
                 let request_uri = format!("{url}/request/{state}").parse().unwrap();
                 let redirect_uri = format!("{url}/redirect").parse::<url::Url>().unwrap();
 
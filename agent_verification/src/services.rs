@@ -1,5 +1,7 @@
-use agent_secret_manager::service::Service;
-use agent_shared::config::{config, get_all_enabled_did_methods, get_preferred_did_method};
+use agent_secret_manager::{service::Service, subject::Subject as SecretManagerSubject};
+use agent_shared::config::{
+    config, get_all_enabled_did_methods, get_preferred_did_method, Display, TenantConfiguration,
+};
 use jsonwebtoken::Algorithm;
 use oid4vc_core::{client_metadata::ClientMetadataResource, Subject};
 use oid4vc_manager::RelyingPartyManager;
@@ -13,17 +15,47 @@ pub struct VerificationServices {
     pub relying_party: RelyingPartyManager,
     pub siopv2_client_metadata: ClientMetadataResource<siopv2::authorization_request::ClientMetadataParameters>,
     pub oid4vp_client_metadata: ClientMetadataResource<oid4vp::authorization_request::ClientMetadataParameters>,
+    /// The public URL of the Verifier, under which the Authorization Requests are served.
+    pub url: String,
 }
 
 impl Service for VerificationServices {
-    fn new(verifier: Arc<dyn Subject>) -> Self {
-        let client_name = config().display.first().as_ref().map(|display| display.name.clone());
+    fn new(verifier: Arc<SecretManagerSubject>) -> Self {
+        let (url, display) = (config().url.clone(), config().display.clone());
 
-        let logo_uri = config()
-            .display
+        Self::with_identity(verifier, url, &display)
+    }
+}
+
+impl VerificationServices {
+    /// Returns the verification services of a tenant, which presents itself to Wallets with its own URL, name and logo.
+    pub fn for_tenant(verifier: Arc<SecretManagerSubject>, tenant: &TenantConfiguration) -> Self {
+        Self::with_identity(verifier, tenant.url.clone(), &tenant.display)
+    }
+
+    fn with_identity(verifier: Arc<SecretManagerSubject>, url: String, display: &[Display]) -> Self {
+        let verifier: Arc<dyn Subject> = verifier;
+
+        let client_name = display.first().map(|display| display.name.clone());
+
+        let logo_uri = display
             .first()
             .and_then(|display| display.logo.as_ref().and_then(|logo| logo.uri.clone()));
 
+        // The first display is the default. The other locales are exposed through language-tagged Client Metadata
+        // values such as `client_name#de`, as described in RFC7591, Section 2.2.
+        let localized_client_metadata: HashMap<String, serde_json::Value> = display
+            .iter()
+            .skip(1)
+            .filter_map(|display| display.locale.as_ref().map(|locale| (locale, display)))
+            .flat_map(|(locale, display)| {
+                let logo_uri = display.logo.as_ref().and_then(|logo| logo.uri.clone());
+
+                std::iter::once((format!("client_name#{locale}"), json!(display.name)))
+                    .chain(logo_uri.map(|logo_uri| (format!("logo_uri#{locale}"), json!(logo_uri))))
+            })
+            .collect();
+
         let signing_algorithms_supported: Vec<Algorithm> = config()
             .signing_algorithms_supported
             .iter()
@@ -41,10 +73,14 @@ impl Service for VerificationServices {
                     .collect(),
                 id_token_signed_response_alg: signing_algorithms_supported.first().cloned(),
             },
-            other: HashMap::from_iter([(
-                "id_token_signing_alg_values_supported".to_string(),
-                json!(signing_algorithms_supported),
-            )]),
+            other: HashMap::from_iter(
+                [(
+                    "id_token_signing_alg_values_supported".to_string(),
+                    json!(signing_algorithms_supported),
+                )]
+                .into_iter()
+                .chain(localized_client_metadata.clone()),
+            ),
         };
 
         let oid4vp_client_metadata = ClientMetadataResource::ClientMetadata {
@@ -63,10 +99,14 @@ impl Service for VerificationServices {
                     })
                     .collect(),
             },
-            other: HashMap::from_iter([(
-                "subject_syntax_types_supported".to_string(),
-                json!(get_all_enabled_did_methods()),
-            )]),
+            other: HashMap::from_iter(
+                [(
+                    "subject_syntax_types_supported".to_string(),
+                    json!(get_all_enabled_did_methods()),
+                )]
+                .into_iter()
+                .chain(localized_client_metadata),
+            ),
         };
 
         let default_subject_syntax_type = get_preferred_did_method();
@@ -81,6 +121,7 @@ impl Service for VerificationServices {
             .unwrap(),
             siopv2_client_metadata,
             oid4vp_client_metadata,
+            url,
         }
     }
 }